@@ -0,0 +1,78 @@
+use alloy_primitives::Address;
+use serde_derive::Deserialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind};
+use std::str::FromStr;
+
+#[derive(Debug, Default, Deserialize)]
+struct AddressBookFile {
+    #[serde(default)]
+    global: HashMap<String, String>,
+    /// Keyed by network name
+    #[serde(default)]
+    networks: HashMap<String, HashMap<String, String>>,
+}
+
+/// Human readable labels for known addresses, either global or scoped to a network
+#[derive(Clone, Debug, Default)]
+pub struct AddressBook {
+    global: HashMap<Address, String>,
+    networks: HashMap<String, HashMap<Address, String>>,
+}
+
+impl AddressBook {
+    /// Reads the address book, returning an empty one if the file doesn't exist
+    pub fn read(file_path: &str) -> eyre::Result<Self> {
+        let file = match File::open(file_path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+        let book: AddressBookFile = serde_json::from_reader(BufReader::new(file))?;
+        let networks = book
+            .networks
+            .into_iter()
+            .map(|(network, entries)| Ok((network, parse_entries(entries)?)))
+            .collect::<eyre::Result<_>>()?;
+        Ok(Self { global: parse_entries(book.global)?, networks })
+    }
+
+    /// Labels for a network, with network specific entries taking precedence over global ones
+    pub fn for_network(&self, network: &str) -> AddressLabels {
+        let mut labels = self.global.clone();
+        if let Some(entries) = self.networks.get(network) {
+            labels.extend(entries.clone());
+        }
+        AddressLabels(labels)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AddressLabels(HashMap<Address, String>);
+
+impl AddressLabels {
+    /// The label given to the address, if any
+    pub fn get(&self, address: &Address) -> Option<&str> {
+        self.0.get(address).map(String::as_str)
+    }
+
+    /// Returns the known label for an address, or its abbreviated hex form
+    pub fn label(&self, address: &Address) -> String {
+        self.0.get(address).cloned().unwrap_or_else(|| {
+            let hex = address.to_string();
+            format!("{}…{}", &hex[..6], &hex[hex.len() - 4..])
+        })
+    }
+}
+
+fn parse_entries(entries: HashMap<String, String>) -> eyre::Result<HashMap<Address, String>> {
+    entries
+        .into_iter()
+        .map(|(address, label)| {
+            let address = Address::from_str(&address)
+                .map_err(|err| eyre::eyre!("invalid address `{address}` in address book: {err}"))?;
+            Ok((address, label))
+        })
+        .collect()
+}
@@ -0,0 +1,251 @@
+use crate::alerts::{Alert, Severity};
+use crate::thresholds::Threshold;
+use chrono::{Local, NaiveTime};
+use serde::de::{self, Deserializer};
+use serde::Serializer;
+use serde_derive::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, ErrorKind};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tokio::time;
+use tracing::{info, warn};
+
+#[derive(Debug, Default, Deserialize)]
+struct AlertSinksFile {
+    #[serde(default)]
+    sinks: Vec<AlertSink>,
+    #[serde(default)]
+    thresholds: Vec<Threshold>,
+}
+
+/// Where a sink delivers its alerts
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SinkTarget {
+    /// Posts a Slack compatible `{"text": ...}` payload
+    Webhook { webhook: String },
+    /// Runs a shell command with the alert in `ALERT_*` environment variables
+    Command { command: String },
+    /// Shows a desktop notification through `notify-send`, or `osascript` on macOS
+    Desktop { desktop: bool },
+}
+
+/// A webhook, command or desktop notification that receives the alerts matching its routing rules
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AlertSink {
+    #[serde(flatten)]
+    pub target: SinkTarget,
+    /// Alert rules routed to this sink, e.g. `stall`; all rules if empty
+    #[serde(default)]
+    pub rules: Vec<String>,
+    /// Network labels routed to this sink; all networks if empty
+    #[serde(default)]
+    pub networks: Vec<String>,
+    #[serde(default = "default_min_severity")]
+    pub min_severity: Severity,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+}
+
+/// Local time window during which only alerts at or above `min_severity` are delivered, or
+/// none at all if it is not set. The window may wrap around midnight.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QuietHours {
+    #[serde(deserialize_with = "parse_time", serialize_with = "format_time")]
+    pub start: NaiveTime,
+    #[serde(deserialize_with = "parse_time", serialize_with = "format_time")]
+    pub end: NaiveTime,
+    #[serde(default)]
+    pub min_severity: Option<Severity>,
+}
+
+fn default_min_severity() -> Severity {
+    Severity::Info
+}
+
+fn parse_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let time: String = serde::Deserialize::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&time, "%H:%M")
+        .map_err(|err| de::Error::custom(format!("invalid time `{time}`: {err}")))
+}
+
+fn format_time<S: Serializer>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&time.format("%H:%M"))
+}
+
+impl QuietHours {
+    fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl AlertSink {
+    fn accepts(&self, alert: &Alert, now: NaiveTime) -> bool {
+        let routed = (self.rules.is_empty() || self.rules.contains(&alert.rule))
+            && (self.networks.is_empty() || self.networks.contains(&alert.network));
+        let min_severity = match &self.quiet_hours {
+            Some(quiet) if quiet.contains(now) => match quiet.min_severity {
+                Some(severity) => severity,
+                None => return false,
+            },
+            _ => self.min_severity,
+        };
+        routed && alert.severity >= min_severity
+    }
+}
+
+/// Delivers fired alerts to the configured sinks
+#[derive(Clone, Debug, Default)]
+pub struct AlertRouter {
+    sinks: Vec<AlertSink>,
+    thresholds: Vec<Threshold>,
+    client: reqwest::Client,
+    /// File the sinks were read from, read again on reload
+    path: Option<String>,
+    /// Deliveries still running in the background
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl AlertRouter {
+    /// Reads the alert sinks, returning a router without sinks if the file doesn't exist
+    pub fn read(file_path: &str) -> eyre::Result<Self> {
+        let file = match File::open(file_path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                return Ok(Self { path: Some(file_path.to_string()), ..Self::default() });
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let config: AlertSinksFile = serde_json::from_reader(BufReader::new(file))?;
+        for threshold in &config.thresholds {
+            threshold.validate()?;
+        }
+        Ok(Self {
+            sinks: config.sinks,
+            thresholds: config.thresholds,
+            client: reqwest::Client::new(),
+            path: Some(file_path.to_string()),
+            in_flight: Arc::default(),
+        })
+    }
+
+    /// Reads the sinks again from their file. Thresholds are evaluated from the metrics as
+    /// configured at startup and take a restart to change.
+    pub fn reload(&mut self) -> eyre::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let reloaded = Self::read(path)?;
+        info!("reloaded {} alert sinks from {path}", reloaded.sinks.len());
+        self.sinks = reloaded.sinks;
+        Ok(())
+    }
+
+    /// Waits for the deliveries still running, at most `timeout`, e.g. before exiting
+    pub async fn flush(&self, timeout: Duration) {
+        let started = Instant::now();
+        while self.in_flight.load(Ordering::SeqCst) > 0 {
+            if started.elapsed() >= timeout {
+                let pending = self.in_flight.load(Ordering::SeqCst);
+                warn!("exiting with {pending} alert deliveries pending");
+                return;
+            }
+            time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    pub fn sinks(&self) -> &[AlertSink] {
+        &self.sinks
+    }
+
+    /// Threshold rules evaluated on the metrics, whose alerts are routed like any other
+    pub fn thresholds(&self) -> &[Threshold] {
+        &self.thresholds
+    }
+
+    /// Delivers the alert to every sink it is routed to, in the background. Delivery failures
+    /// are only logged, the alert is still listed in the UI.
+    pub fn route(&self, alert: &Alert) {
+        let now = Local::now().time();
+        let text =
+            format!("[{}] {}: {} {}", alert.severity, alert.network, alert.rule, alert.value);
+        for sink in self.sinks.iter().filter(|sink| sink.accepts(alert, now)) {
+            match &sink.target {
+                SinkTarget::Webhook { webhook } => {
+                    let request =
+                        self.client.post(webhook).json(&serde_json::json!({ "text": text }));
+                    let webhook = webhook.clone();
+                    let in_flight = self.in_flight.clone();
+                    in_flight.fetch_add(1, Ordering::SeqCst);
+                    tokio::spawn(async move {
+                        match request.send().await.and_then(|response| response.error_for_status())
+                        {
+                            Ok(_) => {}
+                            Err(err) => warn!("failed to deliver alert to {webhook}: {err}"),
+                        }
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+                SinkTarget::Command { command } => {
+                    let mut process = shell(command);
+                    process
+                        .env("ALERT_NETWORK", &alert.network)
+                        .env("ALERT_RULE", &alert.rule)
+                        .env("ALERT_VALUE", &alert.value)
+                        .env("ALERT_SEVERITY", alert.severity.to_string())
+                        .env("ALERT_TEXT", &text);
+                    self.run(process, command.clone());
+                }
+                SinkTarget::Desktop { desktop: true } => {
+                    let title = format!("{}: {}", alert.network, alert.rule);
+                    let process = notification(&title, &alert.value);
+                    self.run(process, "desktop notification".to_string());
+                }
+                SinkTarget::Desktop { desktop: false } => {}
+            }
+        }
+    }
+
+    /// Runs the process in the background, logging a failure to start it or a non-zero exit
+    fn run(&self, mut process: Command, name: String) {
+        process.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+        let in_flight = self.in_flight.clone();
+        in_flight.fetch_add(1, Ordering::SeqCst);
+        tokio::spawn(async move {
+            match process.status().await {
+                Ok(status) if status.success() => {}
+                Ok(status) => warn!("alert sink `{name}` failed with {status}"),
+                Err(err) => warn!("failed to run alert sink `{name}`: {err}"),
+            }
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+}
+
+fn shell(command: &str) -> Command {
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut process = Command::new(shell);
+    process.arg(flag).arg(command);
+    process
+}
+
+fn notification(title: &str, body: &str) -> Command {
+    if cfg!(target_os = "macos") {
+        // AppleScript strings are quoted and escaped like Rust's debug formatting
+        let mut process = Command::new("osascript");
+        process.arg("-e").arg(format!("display notification {body:?} with title {title:?}"));
+        process
+    } else {
+        let mut process = Command::new("notify-send");
+        process.arg(title).arg(body);
+        process
+    }
+}
@@ -0,0 +1,121 @@
+use crate::types::{EventKind, NetworkEvent};
+use crate::units::format_fee;
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Number of alerts kept in the history
+const MAX_ALERTS: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Alert {
+    pub at: DateTime<Utc>,
+    pub network: String,
+    pub rule: String,
+    pub value: String,
+    pub severity: Severity,
+    pub acknowledged: bool,
+}
+
+impl Alert {
+    pub fn from_event(event: &NetworkEvent) -> Self {
+        let (value, severity) = match &event.kind {
+            EventKind::FeeSpike { from, to, token, .. } => {
+                let ratio = *to as f64 / *from as f64;
+                (format!("{} ({ratio:.1}x)", format_fee(*to, token)), Severity::Warning)
+            }
+            EventKind::Stall { seconds } => (format!("{seconds}s"), Severity::Critical),
+            EventKind::InboxBacklog { depth, seconds } => {
+                (format!("{depth} pending, oldest {seconds}s"), Severity::Warning)
+            }
+            EventKind::TpsAnomaly { tps, z, .. } => {
+                // drops are more likely to mean trouble than spikes
+                let severity = if *z > 0.0 { Severity::Info } else { Severity::Warning };
+                (format!("{tps:.2} TPS (z {z:+.1})"), severity)
+            }
+            EventKind::Reorg { block, depth } => {
+                (format!("{depth} blocks from #{block}"), Severity::Warning)
+            }
+            EventKind::Threshold { value, cleared: false, severity, .. } => {
+                (format!("{value:.2}"), *severity)
+            }
+            EventKind::Threshold { value, cleared: true, .. } => {
+                (format!("cleared at {value:.2}"), Severity::Info)
+            }
+        };
+        // thresholds are routed by their own name
+        let rule = match &event.kind {
+            EventKind::Threshold { rule, .. } => rule.clone(),
+            kind => kind.name().to_string(),
+        };
+        Self {
+            at: event.at,
+            network: event.network.clone(),
+            rule,
+            value,
+            severity,
+            acknowledged: false,
+        }
+    }
+}
+
+/// History of fired alerts, newest first
+#[derive(Debug, Default)]
+pub struct AlertLog {
+    alerts: VecDeque<Alert>,
+}
+
+impl AlertLog {
+    pub fn push(&mut self, alert: Alert) {
+        if self.alerts.len() == MAX_ALERTS {
+            self.alerts.pop_back();
+        }
+        self.alerts.push_front(alert);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Alert> {
+        self.alerts.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.alerts.len()
+    }
+
+    pub fn unacknowledged(&self) -> usize {
+        self.alerts.iter().filter(|alert| !alert.acknowledged).count()
+    }
+
+    /// Toggles the acknowledgement of the alert at `index`
+    pub fn toggle_acknowledged(&mut self, index: usize) {
+        if let Some(alert) = self.alerts.get_mut(index) {
+            alert.acknowledged = !alert.acknowledged;
+        }
+    }
+
+    pub fn acknowledge_all(&mut self) {
+        self.alerts.iter_mut().for_each(|alert| alert.acknowledged = true);
+    }
+
+    pub fn clear_acknowledged(&mut self) {
+        self.alerts.retain(|alert| !alert.acknowledged);
+    }
+}
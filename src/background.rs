@@ -0,0 +1,60 @@
+use futures::FutureExt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+/// A periodic check of a monitor run as a task of its own, so the slow RPC calls it may make
+/// never hold up polling. The monitor is moved into the task and handed back with the result.
+pub struct BackgroundCheck<M, T> {
+    interval: Duration,
+    /// The monitor while no check is running
+    monitor: Option<M>,
+    running: Option<JoinHandle<(M, eyre::Result<T>)>>,
+    last_started: Option<Instant>,
+}
+
+impl<M: Send + 'static, T: Send + 'static> BackgroundCheck<M, T> {
+    pub fn new(monitor: M, interval: Duration) -> Self {
+        Self { interval, monitor: Some(monitor), running: None, last_started: None }
+    }
+
+    /// The monitor, `None` while it is being checked
+    pub fn monitor(&self) -> Option<&M> {
+        self.monitor.as_ref()
+    }
+
+    /// Returns the result of a check that finished since the last call, and starts the next one
+    /// with `check` once the interval has passed since the previous one started
+    pub fn poll<F>(&mut self, check: impl FnOnce(M) -> F) -> Option<eyre::Result<T>>
+    where
+        F: Future<Output = (M, eyre::Result<T>)> + Send + 'static,
+    {
+        let mut finished = None;
+        if self.running.as_ref().is_some_and(JoinHandle::is_finished) {
+            let joined = self.running.take().and_then(FutureExt::now_or_never);
+            finished = match joined {
+                Some(Ok((monitor, result))) => {
+                    self.monitor = Some(monitor);
+                    Some(result)
+                }
+                // the monitor is lost with a panicked check, which isn't retried
+                Some(Err(err)) => Some(Err(eyre::eyre!("check failed: {err}"))),
+                None => None,
+            };
+        }
+        let due = self.last_started.is_none_or(|at| at.elapsed() >= self.interval);
+        if let Some(monitor) = self.monitor.take_if(|_| due) {
+            self.last_started = Some(Instant::now());
+            self.running = Some(tokio::spawn(check(monitor)));
+        }
+        finished
+    }
+}
+
+impl<M, T> Drop for BackgroundCheck<M, T> {
+    fn drop(&mut self) {
+        if let Some(running) = &self.running {
+            running.abort();
+        }
+    }
+}
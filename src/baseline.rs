@@ -0,0 +1,23 @@
+use crate::timeseries::{current_hour, Rate, TimeSeries};
+
+/// Hours back of the hours compared against, the same hour yesterday and last week
+const BASELINE_HOURS: [i64; 2] = [24, 7 * 24];
+/// Baselines below this TPS are too noisy to compare against
+const MIN_BASELINE_TPS: f64 = 0.1;
+
+/// Mean TPS of the network at this hour yesterday and last week, as far as recorded, a seasonal
+/// baseline for the current TPS
+pub fn tps(series: &TimeSeries, network: &str) -> Option<f64> {
+    let hour = current_hour();
+    let means = BASELINE_HOURS
+        .iter()
+        .filter_map(|back| series.hour(network, hour - back).mean(Rate::Tps))
+        .collect::<Vec<_>>();
+    (!means.is_empty()).then(|| means.iter().sum::<f64>() / means.len() as f64)
+}
+
+/// Ratio of the current TPS to the baseline, `None` while the baseline is too low to compare
+pub fn ratio(series: &TimeSeries, network: &str, tps: f64) -> Option<f64> {
+    let baseline = self::tps(series, network).filter(|&tps| tps >= MIN_BASELINE_TPS)?;
+    Some(tps / baseline)
+}
@@ -1,80 +1,460 @@
-use crate::types::NetworkMetrics;
-use alloy_rpc_types::Block;
+use crate::address_book::AddressLabels;
+use crate::bridges::BridgeDirection;
+use crate::builders;
+use crate::types::{
+    BlockRecord, BridgeActivity, NetworkMetrics, RecentBlock, SessionTotals, TxGasStats, TxSample,
+    WindowMetrics,
+};
+use alloy_primitives::{Address, Selector, B256};
+use alloy_rpc_types::{Block, BlockTransactions, Transaction};
 use chrono::Utc;
-use std::collections::{HashSet, VecDeque};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
+use std::hash::Hash;
+use std::io::{BufReader, ErrorKind};
+use std::path::Path;
+use tokio::task;
+use tracing::debug;
 
-const WINDOW_SECONDS: u64 = 60;
+pub const WINDOW_SECONDS: u64 = 60;
+const TOP_COUNTS: usize = 5;
+/// Number of newest blocks reported individually
+const RECENT_BLOCKS: usize = 10;
+/// Number of transactions sampled from each block, spread over the block
+const TX_SAMPLES_PER_BLOCK: usize = 3;
+/// Number of sampled transactions kept
+const TX_SAMPLES: usize = 30;
+/// EIP-4844 blob gas per blob, a blob holding as many bytes
+const GAS_PER_BLOB: u64 = 131_072;
+const BYTES_PER_BLOB: u64 = 131_072;
+/// Longest time rates are held at the newest block when smoothing bursts, so they still fall
+/// once a chain halts
+const MAX_BURST_SECONDS: u64 = 30;
+/// Fraction of transaction data an L2 posts to L1 after batch compression, unless configured
+pub const DEFAULT_DA_RATIO: f64 = 0.4;
+
+/// Totals over the blocks within a window, kept up to date as blocks enter and leave it rather
+/// than summed on every poll
+#[derive(Clone, Copy, Debug, Default)]
+struct Window {
+    seconds: u64,
+    /// Number of the newest blocks of the buffer within the window
+    blocks: usize,
+    txs: usize,
+    gas: u64,
+    size: u64,
+    blob_gas: u64,
+}
+
+impl Window {
+    fn new(seconds: u64) -> Self {
+        Self { seconds, ..Default::default() }
+    }
+
+    fn add(&mut self, block: &BlockInfo) {
+        self.blocks += 1;
+        self.txs += block.txs;
+        self.gas += block.gas;
+        self.size += block.size.unwrap_or_default();
+        self.blob_gas += block.blob_gas;
+    }
+
+    fn remove(&mut self, block: &BlockInfo) {
+        self.blocks -= 1;
+        self.txs -= block.txs;
+        self.gas -= block.gas;
+        self.size -= block.size.unwrap_or_default();
+        self.blob_gas -= block.blob_gas;
+    }
+
+    /// Blobs carried by the blocks
+    fn blobs(&self) -> f64 {
+        self.blob_gas as f64 / GAS_PER_BLOB as f64
+    }
+}
 
 #[derive(Default)]
 pub struct BlockMetricsBuffer {
     network: String,
+    /// The windows, the first being the main window, which all metrics but the rates of the
+    /// other windows are computed over
+    windows: Vec<Window>,
+    /// Blocks within the longest window
     buffer: VecDeque<BlockInfo>,
     seen: HashSet<u64>,
-    total_txs: usize,
-    total_gas: u64,
-    total_data: u64,
+    /// Timestamp of the newest block, kept once it leaves the window
+    newest_timestamp: Option<u64>,
+    session: SessionTotals,
+    /// Transactions sampled from the newest blocks, newest first
+    tx_samples: VecDeque<TxSample>,
+    track_bridges: bool,
+    track_mints: bool,
+    track_builders: bool,
+    /// Labels of the network's addresses, naming builders by their fee recipient
+    labels: AddressLabels,
+    /// Fraction of transaction data assumed to be posted to L1, for chains without blobs
+    da_ratio: f64,
+    /// Whether any block carried blobs, as on Ethereum, L2 headers reporting no blob gas. The DA
+    /// of such an L1 is its blob bytes alone, never estimated from its transaction data.
+    carries_blobs: bool,
+    /// Current time in seconds when replaying recorded blocks, the system time otherwise
+    clock: Option<u64>,
+    /// Compute rates up to the newest block instead of the current time, for endpoints
+    /// delivering blocks in bursts
+    smooth_bursts: bool,
 }
 
 impl BlockMetricsBuffer {
-    pub fn new(network: String) -> Self {
-        Self {
-            network,
-            ..Default::default()
+    pub fn new(network: String, windows: &[u64]) -> Self {
+        let mut buffer = Self { network, da_ratio: DEFAULT_DA_RATIO, ..Default::default() };
+        buffer.set_windows(windows);
+        buffer
+    }
+
+    /// Sets the time the windows end at, for replaying recorded blocks at their own pace
+    pub fn set_clock(&mut self, now: u64) {
+        self.clock = Some(now);
+    }
+
+    fn now(&self) -> u64 {
+        self.clock.unwrap_or_else(|| Utc::now().timestamp() as u64)
+    }
+
+    /// Time the windows end at. When smoothing bursts this is the newest block's timestamp, up
+    /// to [`MAX_BURST_SECONDS`] behind, so rates don't decay while the next burst is awaited and
+    /// blocks delivered together count over the interval their timestamps span.
+    fn windows_end(&self) -> u64 {
+        let now = self.now();
+        match self.newest_timestamp {
+            Some(newest) if self.smooth_bursts => {
+                newest.clamp(now.saturating_sub(MAX_BURST_SECONDS), now)
+            }
+            _ => now,
+        }
+    }
+
+    /// Sets the labels of the network's addresses, which name the builders not known by default
+    pub fn set_address_labels(&mut self, labels: AddressLabels) {
+        self.labels = labels;
+    }
+
+    /// Changes the windows, longer windows fill up as new blocks arrive
+    pub fn set_windows(&mut self, windows: &[u64]) {
+        let windows = if windows.is_empty() { &[WINDOW_SECONDS][..] } else { windows };
+        self.windows = windows.iter().map(|&seconds| Window::new(seconds)).collect();
+        for window in &mut self.windows {
+            self.buffer.iter().for_each(|block| window.add(block));
+        }
+        self.update();
+    }
+
+    /// Blocks within the window, oldest first
+    fn blocks_in(&self, window: &Window) -> impl Iterator<Item = &BlockInfo> {
+        self.buffer.range(self.buffer.len() - window.blocks..)
+    }
+
+    /// Factor turning totals over the window into rates per second, extrapolating over blocks
+    /// that were skipped, e.g. to stay within a request budget.
+    fn rate_factor(&self, window: &Window, now: u64) -> Option<f64> {
+        let first = self.buffer.get(self.buffer.len() - window.blocks)?;
+        let last = self.buffer.back()?;
+        (last.timestamp > first.timestamp).then(|| {
+            let span = now.saturating_sub(first.timestamp).max(1);
+            let scale = (last.bn - first.bn + 1) as f64 / window.blocks as f64;
+            scale.max(1.0) / span as f64
+        })
+    }
+
+    fn window_metrics(&self, window: &Window, now: u64) -> WindowMetrics {
+        let seconds = window.seconds;
+        let Some(factor) = self.rate_factor(window, now) else {
+            return WindowMetrics { seconds, ..Default::default() };
+        };
+        let dps = window.size as f64 * factor;
+        WindowMetrics {
+            seconds,
+            tps: window.txs as f64 * factor,
+            gps: window.gas as f64 * factor,
+            dps,
+            da: match self.carries_blobs {
+                true => window.blobs() * (BYTES_PER_BLOB as f64) * factor,
+                false => dps * self.da_ratio,
+            },
         }
     }
 
+    /// Seconds since the newest block was produced, as of `now`
+    fn block_age(&self, now: u64) -> Option<u64> {
+        self.newest_timestamp.map(|timestamp| now.saturating_sub(timestamp))
+    }
+
+    /// Native value transferred over the blocks, `None` unless full transactions were fetched
+    fn value(blocks: &[&BlockInfo]) -> Option<f64> {
+        let values = blocks.iter().filter_map(|block| block.value).collect::<Vec<_>>();
+        (!values.is_empty()).then(|| values.iter().sum())
+    }
+
+    /// Reports bridge activity in the metrics, even if no bridge events have been seen yet
+    pub fn with_bridges(mut self, track_bridges: bool) -> Self {
+        self.track_bridges = track_bridges;
+        self
+    }
+
+    /// Reports mints/s in the metrics, even if no mints have been seen yet
+    pub fn with_mints(mut self, track_mints: bool) -> Self {
+        self.track_mints = track_mints;
+        self
+    }
+
+    /// Attributes blocks to their MEV-Boost builder, reporting the builders' shares of blocks
+    pub fn with_builders(mut self, track_builders: bool) -> Self {
+        self.track_builders = track_builders;
+        self
+    }
+
+    /// Computes rates up to the newest block, see [`Self::windows_end`]
+    pub fn with_burst_smoothing(mut self, smooth_bursts: bool) -> Self {
+        self.smooth_bursts = smooth_bursts;
+        self
+    }
+
+    /// Estimates the DA bytes of chains without blobs as this fraction of their transaction data
+    pub fn with_da_ratio(mut self, da_ratio: f64) -> Self {
+        self.da_ratio = da_ratio;
+        self
+    }
+
     pub fn get_metrics(&mut self) -> NetworkMetrics {
         self.update();
-        match (self.buffer.front(), self.buffer.back()) {
-            (Some(first), Some(last)) if last.timestamp > first.timestamp => {
-                let span = Utc::now().timestamp() as u64 - first.timestamp;
-                //let span = last.timestamp - first.timestamp;
+        let (now, end) = (self.now(), self.windows_end());
+        let main_window = &self.windows[0];
+        let blocks = self.blocks_in(main_window).collect::<Vec<_>>();
+        match (self.rate_factor(main_window, end), blocks.last()) {
+            (Some(factor), Some(last)) => {
+                let main = self.window_metrics(main_window, end);
                 NetworkMetrics {
                     network: self.network.clone(),
                     block: last.bn,
-                    gps: self.total_gas as f64 / span as f64,
-                    tps: self.total_txs as f64 / span as f64,
-                    dps: self.total_data as f64 / span as f64,
+                    gps: main.gps,
+                    tps: main.tps,
+                    dps: main.dps,
+                    da: main.da,
+                    blobs: self.carries_blobs.then(|| main_window.blobs() * factor),
+                    base_fees: blocks.iter().map(|block| block.base_fees).sum::<u128>() as f64
+                        * factor,
+                    base_fee: last.base_fee,
+                    value: Self::value(&blocks).map(|value| value * factor),
+                    priority_fee: average_priority_fee(&blocks),
+                    block_time: block_time(&blocks),
+                    block_age: self.block_age(now),
+                    windows: self.windows.iter().map(|w| self.window_metrics(w, end)).collect(),
+                    session: self.session,
+                    tx_gas: tx_gas_stats(&blocks),
+                    top_contracts: top_counts(blocks.iter().flat_map(|b| b.tx_to.iter())),
+                    top_selectors: top_counts(blocks.iter().flat_map(|b| b.tx_selectors.iter())),
+                    builders: builder_shares(&blocks),
+                    bridge: self.track_bridges.then(|| bridge_activity(&blocks)),
+                    mints: self.track_mints.then(|| {
+                        let mints = blocks.iter().map(|block| block.mints).sum::<u64>();
+                        mints as f64 * factor
+                    }),
+                    endpoint: None,
+                    l1_freshness: None,
+                    inbox: None,
+                    custom: Vec::new(),
+                    zk_batches: None,
+                    verification: None,
+                    budget: None,
+                    polling: None,
+                    recent_blocks: blocks
+                        .iter()
+                        .rev()
+                        .take(RECENT_BLOCKS)
+                        .map(|block| RecentBlock {
+                            number: block.bn,
+                            timestamp: block.timestamp,
+                            txs: block.txs,
+                        })
+                        .collect(),
+                    tx_samples: self.tx_samples.iter().cloned().collect(),
                 }
             }
             _ => NetworkMetrics {
                 network: self.network.clone(),
+                session: self.session,
+                block_age: self.block_age(now),
                 ..Default::default()
             },
         }
     }
 
     pub fn add_block(&mut self, block: &Block) {
-        if let Some(block_info) = BlockInfo::try_from_block(block) {
-            self.add_block_info(block_info);
+        match BlockInfo::try_from_block(block) {
+            Some(mut block_info) => {
+                if self.track_builders {
+                    let header = &block.header;
+                    block_info.builder =
+                        builders::identify(&header.extra_data, header.miner, &self.labels);
+                }
+                if !self.seen.contains(&block_info.bn) {
+                    self.sample_txs(block_info.bn, block);
+                }
+                self.add_block_info(block_info);
+            }
+            None => debug!("skipping block {:?} without number or gas used", block.header.hash),
         }
     }
 
-    fn add_block_info(&mut self, block: BlockInfo) {
-        if self.seen.contains(&block.bn) {
+    fn sample_txs(&mut self, bn: u64, block: &Block) {
+        let samples = match &block.transactions {
+            BlockTransactions::Full(txs) => spread(txs)
+                .map(|tx| TxSample {
+                    hash: tx.hash,
+                    block: bn,
+                    tx_type: tx.transaction_type,
+                    gas: Some(tx.gas as u64),
+                    value: Some(f64::from(tx.value)),
+                })
+                .collect::<Vec<_>>(),
+            BlockTransactions::Hashes(hashes) => spread(hashes)
+                .map(|&hash| TxSample { hash, block: bn, tx_type: None, gas: None, value: None })
+                .collect(),
+            BlockTransactions::Uncle => Vec::new(),
+        };
+        for sample in samples {
+            self.tx_samples.push_front(sample);
+        }
+        self.tx_samples.truncate(TX_SAMPLES);
+    }
+
+    /// Hash of the block in the window with this number, unless it was cached without one
+    pub fn hash(&self, bn: u64) -> Option<B256> {
+        self.buffer.iter().rev().find(|block| block.bn == bn)?.hash
+    }
+
+    /// Removes the blocks from `bn` on, replaced by a reorg, from the window and the session
+    /// totals. Returns the number of blocks removed.
+    pub fn remove_from(&mut self, bn: u64) -> u64 {
+        let mut removed = 0;
+        while self.buffer.back().is_some_and(|block| block.bn >= bn) {
+            let block = self.buffer.pop_back().unwrap();
+            for window in self.windows.iter_mut().filter(|window| window.blocks > 0) {
+                window.remove(&block);
+            }
+            self.seen.remove(&block.bn);
+            self.session.blocks = self.session.blocks.saturating_sub(1);
+            self.session.txs = self.session.txs.saturating_sub(block.txs as u64);
+            self.session.gas = self.session.gas.saturating_sub(block.gas);
+            self.session.data = self.session.data.saturating_sub(block.size.unwrap_or_default());
+            removed += 1;
+        }
+        self.tx_samples.retain(|sample| sample.block < bn);
+        if removed > 0 {
+            self.session.reorgs += 1;
+            self.newest_timestamp = self.buffer.back().map(|block| block.timestamp);
+        }
+        removed
+    }
+
+    /// Adds a block that was decoded before, e.g. from a recording
+    pub fn add_block_info(&mut self, block: BlockInfo) {
+        if !self.insert(&block) {
             return;
         }
 
+        self.session.blocks += 1;
+        self.session.txs += block.txs as u64;
+        self.session.gas += block.gas;
+        self.session.data += block.size.unwrap_or_default();
+    }
+
+    /// Adds a block to the window, returning whether it wasn't already part of it
+    fn insert(&mut self, block: &BlockInfo) -> bool {
+        if self.seen.contains(&block.bn) {
+            return false;
+        }
+
         self.update();
-        self.buffer.push_back(block.clone());
-        self.total_txs += block.txs;
-        self.total_gas += block.gas;
-        if let Some(size) = block.size {
-            self.total_data += size;
+        for window in &mut self.windows {
+            window.add(block);
         }
+        self.buffer.push_back(block.clone());
         self.seen.insert(block.bn);
+        self.carries_blobs |= block.blob_gas > 0;
+        self.newest_timestamp = self.newest_timestamp.max(Some(block.timestamp));
+        true
     }
 
+    /// Writes the blocks in the window to `path` on a blocking thread, replacing any previous
+    /// cache
+    pub async fn save(&self, path: &Path) -> eyre::Result<()> {
+        let blocks = serde_json::to_vec(&self.buffer)?;
+        let path = path.to_path_buf();
+        task::spawn_blocking(move || {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            // write to a temporary file first so a crash never leaves a truncated cache behind
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, blocks)?;
+            fs::rename(tmp, path)?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Restores the blocks cached at `path` that are still within the window. They don't count
+    /// towards the session totals.
+    pub fn load(&mut self, path: &Path) -> eyre::Result<()> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        let blocks: Vec<BlockInfo> = serde_json::from_reader(BufReader::new(file))?;
+        for block in &blocks {
+            self.insert(block);
+        }
+        // drop blocks that fell out of the window while the tool wasn't running
+        self.update();
+        Ok(())
+    }
+
+    /// Attributes a bridge event to a block in the window
+    pub fn add_bridge_event(&mut self, bn: u64, direction: BridgeDirection) {
+        if let Some(block) = self.buffer.iter_mut().find(|block| block.bn == bn) {
+            match direction {
+                BridgeDirection::Deposit => block.bridge.deposits += 1,
+                BridgeDirection::Withdrawal => block.bridge.withdrawals += 1,
+            }
+        }
+    }
+
+    /// Attributes an NFT mint to a block in the window
+    pub fn add_mint(&mut self, bn: u64) {
+        if let Some(block) = self.buffer.iter_mut().find(|block| block.bn == bn) {
+            block.mints += 1;
+        }
+    }
+
+    /// Takes the blocks that fell out of each window off its totals, and drops those that fell
+    /// out of the longest window
     fn update(&mut self) {
-        let current_time = Utc::now().timestamp() as u64;
+        let current_time = self.windows_end();
+        for window in &mut self.windows {
+            while let Some(block) = self.buffer.get(self.buffer.len() - window.blocks) {
+                if current_time.saturating_sub(block.timestamp) < window.seconds {
+                    break;
+                }
+                window.remove(block);
+            }
+        }
+        let longest = self.windows.iter().map(|window| window.seconds).max();
+        let longest = longest.unwrap_or(WINDOW_SECONDS);
         while let Some(front_block) = self.buffer.front() {
-            if current_time - front_block.timestamp >= WINDOW_SECONDS {
+            if current_time.saturating_sub(front_block.timestamp) >= longest {
                 let block = self.buffer.pop_front().unwrap();
-                self.total_txs -= block.txs;
-                self.total_gas -= block.gas;
-                if let Some(size) = block.size {
-                    self.total_data -= size;
-                }
                 self.seen.remove(&block.bn);
             } else {
                 break;
@@ -83,16 +463,162 @@ impl BlockMetricsBuffer {
     }
 }
 
-#[derive(Clone, Debug)]
-struct BlockInfo {
+/// Up to [`TX_SAMPLES_PER_BLOCK`] items evenly spaced over `items`, so samples aren't only the
+/// system transactions some rollups start their blocks with
+fn spread<T>(items: &[T]) -> impl Iterator<Item = &T> {
+    let step = items.len().div_ceil(TX_SAMPLES_PER_BLOCK).max(1);
+    items.iter().step_by(step)
+}
+
+/// Mean time between the first and last block, `None` with fewer than two blocks
+fn block_time(blocks: &[&BlockInfo]) -> Option<f64> {
+    match (blocks.first(), blocks.last()) {
+        (Some(first), Some(last)) if blocks.len() > 1 => {
+            Some((last.timestamp - first.timestamp) as f64 / (blocks.len() - 1) as f64)
+        }
+        _ => None,
+    }
+}
+
+fn bridge_activity(blocks: &[&BlockInfo]) -> BridgeActivity {
+    blocks.iter().fold(BridgeActivity::default(), |mut acc, block| {
+        acc.deposits += block.bridge.deposits;
+        acc.withdrawals += block.bridge.withdrawals;
+        acc
+    })
+}
+
+/// Mean of the median priority fees of the blocks that have them
+fn average_priority_fee(blocks: &[&BlockInfo]) -> Option<u128> {
+    let fees = blocks.iter().filter_map(|block| block.priority_fee).collect::<Vec<_>>();
+    (!fees.is_empty()).then(|| fees.iter().sum::<u128>() / fees.len() as u128)
+}
+
+fn tx_gas_stats(blocks: &[&BlockInfo]) -> Option<TxGasStats> {
+    let mut tx_gas =
+        blocks.iter().flat_map(|block| block.tx_gas.iter().copied()).collect::<Vec<_>>();
+    if tx_gas.is_empty() {
+        return None;
+    }
+    tx_gas.sort_unstable();
+    Some(TxGasStats { median: tx_gas[tx_gas.len() / 2], max: tx_gas[tx_gas.len() - 1] })
+}
+
+/// Fraction of the MEV-Boost blocks, those attributed to a builder, each of the most frequent
+/// builders built
+fn builder_shares(blocks: &[&BlockInfo]) -> Vec<(String, f64)> {
+    let attributed = blocks.iter().filter(|block| block.builder.is_some()).count();
+    top_counts(blocks.iter().filter_map(|block| block.builder.as_ref()))
+        .into_iter()
+        .map(|(builder, blocks)| (builder, blocks as f64 / attributed as f64))
+        .collect()
+}
+
+/// Returns the most frequent values with their counts
+fn top_counts<'a, T: Clone + Eq + Hash + Ord + 'a>(
+    values: impl Iterator<Item = &'a T>,
+) -> Vec<(T, usize)> {
+    let mut counts = HashMap::new();
+    for value in values {
+        *counts.entry(value.clone()).or_insert(0) += 1;
+    }
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    counts.truncate(TOP_COUNTS);
+    counts
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockInfo {
     bn: u64,
     gas: u64,
     size: Option<u64>,
     timestamp: u64,
     txs: usize,
+    /// Gas limit of each transaction, empty unless full transactions were fetched
+    tx_gas: Vec<u64>,
+    /// Recipient of each transaction, excluding contract creations
+    tx_to: Vec<Address>,
+    /// Function selector of each call, `None` for calls without calldata
+    tx_selectors: Vec<Option<Selector>>,
+    bridge: BridgeActivity,
+    mints: u64,
+    /// Base fee times gas used, in the smallest unit of the gas token
+    #[serde(default)]
+    base_fees: u128,
+    /// EIP-4844 blob gas used, 0 on chains without blobs
+    #[serde(default)]
+    blob_gas: u64,
+    #[serde(default)]
+    base_fee: Option<u128>,
+    /// Median effective tip of the transactions, only available when full transactions were
+    /// fetched
+    #[serde(default)]
+    priority_fee: Option<u128>,
+    /// Native value transferred in the smallest unit of the gas token, only available when full
+    /// transactions were fetched
+    #[serde(default)]
+    value: Option<f64>,
+    /// MEV-Boost builder, only attributed when builder tracking is enabled
+    #[serde(default)]
+    builder: Option<String>,
+    /// Hashes to detect reorgs with, missing in blocks cached by earlier versions
+    #[serde(default)]
+    hash: Option<B256>,
+    #[serde(default)]
+    parent_hash: Option<B256>,
 }
 
 impl BlockInfo {
+    pub fn number(&self) -> u64 {
+        self.bn
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// A block restored from its recorded datapoints, without per-transaction details
+    pub fn recorded(record: &BlockRecord) -> Self {
+        Self {
+            bn: record.number,
+            gas: record.gas,
+            size: record.size,
+            timestamp: record.timestamp,
+            txs: record.txs as usize,
+            tx_gas: Vec::new(),
+            tx_to: Vec::new(),
+            tx_selectors: Vec::new(),
+            bridge: BridgeActivity::default(),
+            mints: 0,
+            base_fees: 0,
+            blob_gas: 0,
+            base_fee: None,
+            priority_fee: None,
+            value: None,
+            builder: None,
+            hash: None,
+            parent_hash: None,
+        }
+    }
+
+    /// The datapoints of the block that are recorded
+    pub fn record(&self, network: &str) -> BlockRecord {
+        BlockRecord {
+            network: network.to_string(),
+            number: self.bn,
+            timestamp: self.timestamp,
+            txs: self.txs as u64,
+            gas: self.gas,
+            size: self.size,
+        }
+    }
+
+    /// The block renumbered and moved in time, for replaying recorded blocks
+    pub fn replayed(&self, bn: u64, timestamp: u64) -> Self {
+        Self { bn, timestamp, ..self.clone() }
+    }
+
     fn try_from_block(block: &Block) -> Option<Self> {
         match (block.header.number, block.header.gas_used) {
             (Some(bn), gas) if gas < u64::MAX as u128 => Some(Self {
@@ -101,8 +627,50 @@ impl BlockInfo {
                 size: block.size.map(|s| s.as_limbs()[0]),
                 timestamp: block.header.timestamp,
                 txs: block.transactions.len(),
+                tx_gas: block.transactions.txns().map(|tx| tx.gas as u64).collect(),
+                tx_to: block.transactions.txns().filter_map(|tx| tx.to).collect(),
+                tx_selectors: block
+                    .transactions
+                    .txns()
+                    .filter(|tx| tx.to.is_some())
+                    .map(|tx| tx.input.get(..4).map(Selector::from_slice))
+                    .collect(),
+                bridge: BridgeActivity::default(),
+                mints: 0,
+                base_fees: block.header.base_fee_per_gas.unwrap_or_default() * gas,
+                blob_gas: block.header.blob_gas_used.unwrap_or_default() as u64,
+                base_fee: block.header.base_fee_per_gas,
+                priority_fee: median_priority_fee(block),
+                value: match &block.transactions {
+                    BlockTransactions::Full(txs) => {
+                        Some(txs.iter().map(|tx| f64::from(tx.value)).sum())
+                    }
+                    _ => None,
+                },
+                builder: None,
+                hash: block.header.hash,
+                parent_hash: Some(block.header.parent_hash),
             }),
             _ => None,
         }
     }
 }
+
+/// Median effective tip per gas of the block's transactions, only available with full
+/// transactions
+pub fn median_priority_fee(block: &Block) -> Option<u128> {
+    let base_fee = block.header.base_fee_per_gas.unwrap_or_default();
+    let mut fees =
+        block.transactions.txns().filter_map(|tx| effective_tip(tx, base_fee)).collect::<Vec<_>>();
+    fees.sort_unstable();
+    fees.get(fees.len() / 2).copied()
+}
+
+/// Tip per gas a transaction pays the block producer: its max priority fee, capped by what its max
+/// fee leaves above the base fee, or for legacy transactions its gas price above the base fee
+fn effective_tip(tx: &Transaction, base_fee: u128) -> Option<u128> {
+    match (tx.max_priority_fee_per_gas, tx.max_fee_per_gas) {
+        (Some(tip), Some(max_fee)) => Some(tip.min(max_fee.saturating_sub(base_fee))),
+        _ => Some(tx.gas_price?.saturating_sub(base_fee)),
+    }
+}
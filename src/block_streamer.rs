@@ -1,18 +1,130 @@
-use crate::block_metrics::BlockMetricsBuffer;
-use crate::networks::Network;
-use crate::types::BlockMessage;
-use alloy_provider::{Provider, ProviderBuilder, ReqwestProvider};
-use alloy_rpc_types::{Block, BlockTransactionsKind};
+use crate::address_book::AddressLabels;
+use crate::background::BackgroundCheck;
+use crate::block_metrics::{BlockMetricsBuffer, DEFAULT_DA_RATIO};
+use crate::bridges::BridgeTracker;
+use crate::capabilities::{self, Capabilities, Capability};
+use crate::custom_metrics::CustomMetrics;
+use crate::endpoints::EndpointSelector;
+use crate::fee_spikes::FeeSpikeDetector;
+use crate::inbox::InboxMonitor;
+use crate::mints::{is_nft_mint, mint_filters};
+use crate::networks::{url_authority, MetricOverrides, Network};
+use crate::rpc_cache::RpcCache;
+use crate::rpc_pool::RpcPool;
+use crate::tps_anomalies::{TpsAnomalyDetector, DEFAULT_Z_SCORE};
+use crate::types::{
+    BlockMessage, BlockRecord, EventKind, InboxQueue, Log, NetworkEvent, NetworkHealth, PollStatus,
+    VerificationLag, ZkBatches,
+};
+use crate::verification::VerificationMonitor;
+use crate::zk_batches::ZkBatchTracker;
+use alloy::pubsub::{PubSubFrontend, Subscription};
+use alloy_provider::{Provider, ProviderBuilder, RootProvider, WsConnect};
+use alloy_rpc_types::{Block, BlockNumberOrTag, BlockTransactionsKind};
+use chrono::{DateTime, Utc};
 use futures::future::join_all;
-use std::time::Duration;
-use tokio::sync::mpsc::Sender;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::oneshot;
 use tokio::time;
+use tracing::{debug, info, warn};
 
 const POLL_INTERVAL: Duration = Duration::from_millis(750);
+/// Bounds of configured and adaptive poll intervals
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const MAX_ADAPTIVE_INTERVAL: Duration = Duration::from_secs(10);
+/// Longest interval adaptive polling backs off to after failures
+const MAX_BACKOFF_INTERVAL: Duration = Duration::from_secs(60);
+/// How often the block window is written to the cache, bounding what a restart loses
+const CACHE_INTERVAL: Duration = Duration::from_secs(30);
+const CACHE_DIR: &str = "cache";
+/// A network is considered stalled once its newest block is older than this
+const STALL_AFTER: Duration = Duration::from_secs(60);
+/// How often the safe head is requested when tracking batches, about one L1 block
+const SAFE_HEAD_INTERVAL: Duration = Duration::from_secs(12);
+/// How often the L1 inbox queue is checked
+const INBOX_INTERVAL: Duration = Duration::from_secs(60);
+/// A queued inbox message older than this suggests a censoring or stuck sequencer
+const INBOX_BACKLOG_AFTER: Duration = Duration::from_secs(3600);
+/// How often the state verified on L1 is read
+const VERIFICATION_INTERVAL: Duration = Duration::from_secs(60);
+/// How often the L1 progress of ZK batches is checked
+const ZK_BATCH_INTERVAL: Duration = Duration::from_secs(60);
+/// How long to wait for a new head over WebSocket before polling over HTTP instead
+const HEAD_TIMEOUT: Duration = Duration::from_secs(5);
+/// Minimum time between attempts to subscribe to new heads
+const SUBSCRIBE_INTERVAL: Duration = Duration::from_secs(30);
+/// Most blocks walked back to find where a reorg forked off
+const MAX_REORG_DEPTH: usize = 64;
+
+/// Capabilities probed of every endpoint, and the inbox's L1 host with whether it serves logs
+type CapabilityProbe = (Vec<Capabilities>, Option<(String, bool)>);
+
+/// Runtime requests to a running streamer
+#[derive(Clone, Debug)]
+pub enum StreamerCommand {
+    /// Sets the metrics windows in seconds, the first being the main window
+    SetWindows(Vec<u64>),
+    /// Stops or restarts polling, keeping the metrics gathered so far
+    SetPaused(bool),
+}
 
 pub struct BlockStreamer {
-    provider: ReqwestProvider,
+    network: String,
+    endpoints: EndpointSelector,
+    cache: RpcCache,
     metrics: BlockMetricsBuffer,
+    overrides: MetricOverrides,
+    transactions_kind: BlockTransactionsKind,
+    bridges: BridgeTracker,
+    track_mints: bool,
+    track_batches: bool,
+    /// When the safe head was last requested and its timestamp
+    safe_head: Option<(Instant, Option<u64>)>,
+    inbox: Option<BackgroundCheck<InboxMonitor, InboxQueue>>,
+    /// Capabilities of every endpoint and whether the inbox's L1 host serves logs, while still
+    /// probed
+    capability_probe: Option<oneshot::Receiver<CapabilityProbe>>,
+    /// The inbox queue as of the last check, if it succeeded
+    inbox_queue: Option<InboxQueue>,
+    inbox_backlogged: bool,
+    custom_metrics: CustomMetrics,
+    zk_batches: Option<BackgroundCheck<ZkBatchTracker, ZkBatches>>,
+    /// When the ZK batches were last checked and their progress, if the check succeeded
+    zk_status: Option<ZkBatches>,
+    verifier: Option<VerificationMonitor>,
+    /// When the verified state was last read and its lag, if the read succeeded
+    verification: Option<(Instant, Option<VerificationLag>)>,
+    fee_spikes: FeeSpikeDetector,
+    tps_anomalies: TpsAnomalyDetector,
+    last_block_timestamp: Option<u64>,
+    stalled: bool,
+    cache_path: PathBuf,
+    commands: Receiver<StreamerCommand>,
+    paused: bool,
+    /// WebSocket endpoint to subscribe to new heads on instead of polling
+    ws: Option<String>,
+    /// Live new heads subscription, `None` while polling over HTTP
+    heads: Option<(RootProvider<PubSubFrontend>, Subscription<Block>)>,
+    last_subscribe: Option<Instant>,
+    /// Smoothed number of requests spent per poll, used to pace polls within a request budget
+    requests_per_poll: f64,
+    /// Configured interval between polls
+    base_interval: Duration,
+    adaptive: bool,
+    /// Mean block time in seconds, which adaptive polling follows
+    block_time: Option<f64>,
+    /// Failed or rate limited polls in a row
+    backoff: u32,
+    /// Whether a request of the current poll was rejected for exceeding the host's rate limit
+    rate_limited: bool,
+    /// Skipping blocks and log scans since the request budget is spent ahead of pace
+    degraded: bool,
+    health: NetworkHealth,
+    /// Sends a [`BlockRecord`] of every block, for recordings and the watchers of a collector
+    record_blocks: bool,
     tx: Sender<BlockMessage>,
 }
 
@@ -21,45 +133,723 @@ pub struct BlockStreamer {
 ///
 /// Sends metrics to the provided channel
 impl BlockStreamer {
-    pub async fn new(network: Network, tx: Sender<BlockMessage>) -> eyre::Result<Self> {
-        let rpc_url = network.http.parse()?;
-        let provider = ProviderBuilder::new().on_http(rpc_url);
-        let metrics = BlockMetricsBuffer::new(network.label.clone());
-        Ok(Self { provider, tx, metrics })
+    pub async fn new(
+        network: Network,
+        rpc_pool: &RpcPool,
+        windows: &[u64],
+        commands: Receiver<StreamerCommand>,
+        tx: Sender<BlockMessage>,
+    ) -> eyre::Result<Self> {
+        let endpoints = EndpointSelector::new(&network, rpc_pool)?;
+        let inbox = network
+            .inbox
+            .as_ref()
+            .map(|inbox| InboxMonitor::new(inbox, rpc_pool))
+            .transpose()?
+            .map(|monitor| BackgroundCheck::new(monitor, INBOX_INTERVAL));
+        let verifier = network
+            .verifier
+            .as_ref()
+            .map(|verifier| VerificationMonitor::new(verifier, rpc_pool))
+            .transpose()?;
+        let bridges = BridgeTracker::new(&network.bridges);
+        let metrics = BlockMetricsBuffer::new(network.label.clone(), windows)
+            .with_bridges(!bridges.is_empty())
+            .with_mints(network.track_mints)
+            .with_builders(network.track_builders)
+            .with_burst_smoothing(network.smooth_bursts)
+            .with_da_ratio(network.da_ratio.unwrap_or(DEFAULT_DA_RATIO));
+        let cache_path = PathBuf::from(CACHE_DIR).join(format!("{}.json", network.name));
+        let transactions_kind = if network.full_transactions {
+            BlockTransactionsKind::Full
+        } else {
+            BlockTransactionsKind::Hashes
+        };
+        Ok(Self {
+            health: NetworkHealth { network: network.label.clone(), ..Default::default() },
+            network: network.label,
+            endpoints,
+            cache: RpcCache::default(),
+            tx,
+            metrics,
+            overrides: network.overrides,
+            transactions_kind,
+            bridges,
+            track_mints: network.track_mints,
+            track_batches: network.track_batches,
+            safe_head: None,
+            inbox,
+            capability_probe: None,
+            inbox_queue: None,
+            inbox_backlogged: false,
+            custom_metrics: CustomMetrics::new(&network.custom_metrics),
+            zk_batches: network
+                .track_zk_batches
+                .then(|| BackgroundCheck::new(ZkBatchTracker::default(), ZK_BATCH_INTERVAL)),
+            zk_status: None,
+            verifier,
+            verification: None,
+            fee_spikes: FeeSpikeDetector::new(network.gas_token.clone()),
+            tps_anomalies: TpsAnomalyDetector::new(network.tps_z_score.unwrap_or(DEFAULT_Z_SCORE)),
+            last_block_timestamp: None,
+            stalled: false,
+            cache_path,
+            commands,
+            paused: false,
+            ws: network.ws,
+            heads: None,
+            last_subscribe: None,
+            requests_per_poll: 1.0,
+            base_interval: network
+                .poll_interval_ms
+                .map_or(POLL_INTERVAL, Duration::from_millis)
+                .max(MIN_POLL_INTERVAL),
+            adaptive: network.adaptive_polling,
+            block_time: None,
+            backoff: 0,
+            rate_limited: false,
+            degraded: false,
+            record_blocks: false,
+        })
+    }
+
+    /// Names builders by the labels of the network's addresses too
+    pub fn with_address_labels(mut self, labels: AddressLabels) -> Self {
+        self.metrics.set_address_labels(labels);
+        self
+    }
+
+    /// Sends a [`BlockRecord`] of every block along with the metrics
+    pub fn with_block_records(mut self, record_blocks: bool) -> Self {
+        self.record_blocks = record_blocks;
+        self
+    }
+
+    /// Keeps the block cache in `dir` instead of [`CACHE_DIR`], for tests
+    #[cfg(test)]
+    pub fn with_cache_dir(mut self, dir: &std::path::Path) -> Self {
+        if let Some(file) = self.cache_path.file_name() {
+            self.cache_path = dir.join(file);
+        }
+        self
     }
 
     pub async fn start(&mut self) -> eyre::Result<()> {
-        let mut last_block = self.get_next_batch(None).await; // bootstrap
+        // a stale or unreadable cache only costs the warm start
+        if let Err(err) = self.metrics.load(&self.cache_path) {
+            warn!("ignoring block cache {}: {err}", self.cache_path.display());
+        }
+        self.probe_capabilities();
+        let mut last_block = None;
+        let mut last_cached = Instant::now();
+        let mut head = None;
         loop {
-            last_block = self.get_next_batch(last_block).await;
-            let latest = self.metrics.get_metrics();
-            self.tx.send(BlockMessage::UpdateNetwork(latest)).await?;
-            time::sleep(POLL_INTERVAL).await;
+            while let Ok(command) = self.commands.try_recv() {
+                match command {
+                    StreamerCommand::SetWindows(windows) => self.metrics.set_windows(&windows),
+                    StreamerCommand::SetPaused(paused) => self.paused = paused,
+                }
+            }
+            if self.paused {
+                // heads received while paused would only pile up
+                self.heads = None;
+                time::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+            let endpoint = self.endpoints.active_host().to_string();
+            // only report metrics after a successful poll so the UI can tell when a network has
+            // gone quiet
+            let bootstrap = last_block.is_none();
+            self.rate_limited = false;
+            let batch = self.get_next_batch(last_block, head).await;
+            self.update_health(batch.as_ref().err()).await?;
+            self.backoff = match batch.is_err() || self.rate_limited {
+                true => self.backoff.saturating_add(1),
+                false => 0,
+            };
+            // a stale head mustn't move the last block fetched back
+            match batch {
+                Ok(latest_block) if bootstrap => last_block = Some(latest_block),
+                Ok(latest_block) => {
+                    last_block = last_block.max(Some(latest_block));
+                    let mut latest = self.metrics.get_metrics();
+                    latest.endpoint = Some(self.endpoints.active_host().to_string());
+                    latest.budget = self
+                        .endpoints
+                        .active()
+                        .provider
+                        .budget()
+                        .map(|budget| budget.status(self.degraded));
+                    latest.l1_freshness = self.safe_head.and_then(|(_, timestamp)| {
+                        Some((Utc::now().timestamp() as u64).saturating_sub(timestamp?))
+                    });
+                    latest.inbox = self.inbox_queue;
+                    latest.custom = self.custom_metrics.values();
+                    latest.zk_batches = self.zk_status;
+                    latest.verification = self.verification.and_then(|(_, lag)| lag);
+                    self.block_time = latest.block_time.or(self.block_time);
+                    latest.polling = Some(PollStatus {
+                        interval_ms: self.poll_interval().as_millis() as u64,
+                        adaptive: self.adaptive,
+                        backoff: self.backoff,
+                    });
+                    self.overrides.apply(&mut latest);
+                    if let Some(kind) = self.tps_anomalies.observe(latest.tps) {
+                        self.send_event(Utc::now(), kind).await?;
+                    }
+                    self.tx.send(BlockMessage::UpdateNetwork(Box::new(latest))).await?;
+                    self.check_stall().await?;
+                }
+                Err(err) => {
+                    warn!("poll failed: {err}");
+                    self.log(format!("poll failed: {err}"));
+                }
+            }
+            self.apply_capabilities();
+            if !self.degraded {
+                self.update_safe_head().await;
+                self.check_inbox().await?;
+                self.update_custom_metrics().await;
+                self.check_zk_batches();
+                self.check_verification().await;
+                self.endpoints.probe().await;
+                self.endpoints.reselect();
+            }
+            if self.endpoints.active_host() != endpoint {
+                self.log(format!("switched endpoint to {}", self.endpoints.active_host()));
+            }
+            if last_cached.elapsed() >= CACHE_INTERVAL {
+                self.save_cache().await;
+                last_cached = Instant::now();
+            }
+            head = self.next_head(self.poll_interval()).await;
+        }
+    }
+
+    /// Writes the block window to the cache loaded on the next start
+    pub async fn save_cache(&self) {
+        if let Err(err) = self.metrics.save(&self.cache_path).await {
+            warn!("failed to write block cache {}: {err}", self.cache_path.display());
+        }
+    }
+
+    /// Waits for the next head over WebSocket, or for `interval` when polling over HTTP. Heads
+    /// already queued are skipped to the newest. Polling takes over when no head arrives in time
+    /// or the subscription drops, until resubscribed.
+    async fn next_head(&mut self, interval: Duration) -> Option<u64> {
+        let started = Instant::now();
+        self.subscribe().await;
+        let Some((_, heads)) = &mut self.heads else {
+            time::sleep(interval).await;
+            return None;
+        };
+        let head = match time::timeout(HEAD_TIMEOUT, heads.recv()).await {
+            Ok(Ok(block)) => {
+                let mut head = block.header.number;
+                while let Ok(block) = heads.try_recv() {
+                    head = head.max(block.header.number);
+                }
+                head
+            }
+            // the heads skipped are fetched with the latest block number
+            Ok(Err(RecvError::Lagged(skipped))) => {
+                debug!("skipped {skipped} new heads the streamer fell behind on");
+                None
+            }
+            Ok(Err(err)) => {
+                warn!("new heads subscription dropped: {err}");
+                self.log(format!("new heads subscription dropped, polling over HTTP: {err}"));
+                self.heads = None;
+                self.health.websocket = Some(false);
+                None
+            }
+            Err(_) => None,
+        };
+        // a request budget or backoff still limits how often blocks are fetched
+        if interval > self.base_interval {
+            time::sleep(interval.saturating_sub(started.elapsed())).await;
+        }
+        head
+    }
+
+    /// Subscribes to new heads when a WebSocket endpoint is configured and no subscription is
+    /// live, at most every [`SUBSCRIBE_INTERVAL`]
+    async fn subscribe(&mut self) {
+        let Some(url) = &self.ws else {
+            return;
+        };
+        if self.heads.is_some()
+            || self.last_subscribe.is_some_and(|at| at.elapsed() < SUBSCRIBE_INTERVAL)
+        {
+            return;
         }
+        self.last_subscribe = Some(Instant::now());
+        let subscribed = async {
+            let provider = ProviderBuilder::new().on_ws(WsConnect::new(url.as_str())).await?;
+            let heads = provider.subscribe_blocks().await?;
+            eyre::Ok((provider, heads))
+        };
+        match subscribed.await {
+            Ok(subscription) => {
+                info!("subscribed to new heads on {url}");
+                self.log(format!("subscribed to new heads on {}", url_authority(url)));
+                self.heads = Some(subscription);
+                self.health.websocket = Some(true);
+            }
+            Err(err) => {
+                warn!("failed to subscribe to new heads on {url}: {err}");
+                self.health.websocket = Some(false);
+                self.log(format!("failed to subscribe to new heads: {err}"));
+            }
+        }
+    }
+
+    /// Starts probing the optional RPC methods of every endpoint, and whether the inbox's L1
+    /// serves logs, as a task of its own so polling starts right away
+    fn probe_capabilities(&mut self) {
+        let endpoints = self.endpoints.probe_capabilities();
+        let l1 = self
+            .inbox
+            .as_ref()
+            .and_then(BackgroundCheck::monitor)
+            .map(|monitor| (monitor.l1_host().to_string(), monitor.l1().clone()));
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let inbox_logs = match l1 {
+                Some((host, l1)) => {
+                    capabilities::probe(&l1, Capability::Logs).await.map(|logs| (host, logs))
+                }
+                None => None,
+            };
+            let _ = tx.send((endpoints.await, inbox_logs));
+        });
+        self.capability_probe = Some(rx);
+    }
+
+    /// Applies the capabilities once probed, logging the features skipped on endpoints lacking
+    /// them rather than failing their requests on every poll. Endpoints are assumed capable until
+    /// then.
+    fn apply_capabilities(&mut self) {
+        let Some(probe) = &mut self.capability_probe else {
+            return;
+        };
+        let (probed, inbox_logs) = match probe.try_recv() {
+            Ok(probed) => probed,
+            Err(oneshot::error::TryRecvError::Empty) => return,
+            Err(oneshot::error::TryRecvError::Closed) => {
+                self.capability_probe = None;
+                return;
+            }
+        };
+        self.capability_probe = None;
+        if let Some((host, false)) = inbox_logs {
+            let message = format!("{host} doesn't support eth_getLogs, skipping inbox tracking");
+            info!("{message}");
+            self.log(message);
+            self.inbox = None;
+        }
+        for (host, lacking) in self.endpoints.set_capabilities(probed) {
+            let methods = lacking.iter().map(|c| c.title()).collect::<Vec<_>>().join(", ");
+            let features = lacking.iter().filter_map(|c| c.features()).collect::<Vec<_>>();
+            let message = match features.is_empty() {
+                true => format!("{host} doesn't support {methods}"),
+                false => format!(
+                    "{host} doesn't support {methods}, skipping {} there",
+                    features.join(" and ")
+                ),
+            };
+            info!("{message}");
+            self.log(message);
+        }
+    }
+
+    /// The configured interval, or the block time when adaptive, doubled for each failed or rate
+    /// limited poll in a row. Polls are spread over the hour when the active endpoint has a
+    /// request budget.
+    fn poll_interval(&self) -> Duration {
+        let mut interval = self.base_interval;
+        if self.adaptive {
+            if let Some(block_time) = self.block_time.filter(|&seconds| seconds > 0.0) {
+                interval = Duration::from_secs_f64(block_time)
+                    .clamp(MIN_POLL_INTERVAL, MAX_ADAPTIVE_INTERVAL);
+            }
+            let factor = 2u32.saturating_pow(self.backoff.min(16));
+            interval = interval.saturating_mul(factor).min(MAX_BACKOFF_INTERVAL.max(interval));
+        }
+        match self.endpoints.active().provider.budget() {
+            Some(budget) => budget.poll_interval(self.requests_per_poll, interval),
+            None => interval,
+        }
+    }
+
+    /// Requests the safe head at most every [`SAFE_HEAD_INTERVAL`] when tracking batches
+    async fn update_safe_head(&mut self) {
+        if !self.track_batches
+            || !self.endpoints.active().supports(Capability::SafeHead)
+            || self.safe_head.is_some_and(|(at, _)| at.elapsed() < SAFE_HEAD_INTERVAL)
+        {
+            return;
+        }
+        let provider = &self.endpoints.active().provider;
+        let safe_head =
+            provider.call(|provider| provider.get_block_by_number(BlockNumberOrTag::Safe, false));
+        let timestamp = match safe_head.await {
+            Ok(block) => block.map(|block| block.header.timestamp),
+            Err(err) => {
+                warn!("failed to fetch safe head: {err}");
+                self.log(format!("failed to fetch safe head: {err}"));
+                None
+            }
+        };
+        self.safe_head = Some((Instant::now(), timestamp));
     }
 
-    async fn get_next_batch(&mut self, previous_block: Option<u64>) -> Option<u64> {
-        let latest_block_number = match self.provider.get_block_number().await {
-            Ok(block_number) => block_number,
-            Err(_) => return previous_block,
+    /// Checks the L1 inbox queue at most every [`INBOX_INTERVAL`] off the poll loop, emitting a
+    /// backlog event once the oldest queued message becomes older than [`INBOX_BACKLOG_AFTER`]
+    async fn check_inbox(&mut self) -> eyre::Result<()> {
+        let Some(inbox) = &mut self.inbox else {
+            return Ok(());
         };
+        let l2 = self.endpoints.active().provider.clone();
+        let checked = inbox.poll(|mut monitor| async move {
+            let queue = monitor.check(&l2).await;
+            (monitor, queue)
+        });
+        let Some(checked) = checked else {
+            return Ok(());
+        };
+        let queue = match checked {
+            Ok(queue) => Some(queue),
+            Err(err) => {
+                warn!("failed to check inbox: {err}");
+                self.log(format!("failed to check inbox: {err}"));
+                None
+            }
+        };
+        self.inbox_queue = queue;
+        let Some(queue) = queue else {
+            return Ok(());
+        };
+        let seconds = queue.oldest_age.unwrap_or_default();
+        let backlogged = seconds >= INBOX_BACKLOG_AFTER.as_secs();
+        if backlogged && !self.inbox_backlogged {
+            info!("{} inbox messages pending, oldest for {seconds}s", queue.depth);
+            let kind = EventKind::InboxBacklog { depth: queue.depth, seconds };
+            self.send_event(Utc::now(), kind).await?;
+        }
+        self.inbox_backlogged = backlogged;
+        Ok(())
+    }
 
-        let previous_block = previous_block.unwrap_or_default().max(latest_block_number - 10);
+    /// Checks the L1 progress of the ZK batches at most every [`ZK_BATCH_INTERVAL`] off the poll
+    /// loop
+    fn check_zk_batches(&mut self) {
+        let Some(tracker) = &mut self.zk_batches else {
+            return;
+        };
+        let provider = self.endpoints.active().provider.clone();
+        let checked = tracker.poll(|mut tracker| async move {
+            let batches = tracker.check(&provider).await;
+            (tracker, batches)
+        });
+        let Some(checked) = checked else {
+            return;
+        };
+        let batches = match checked {
+            Ok(batches) => Some(batches),
+            Err(err) => {
+                warn!("failed to check ZK batches: {err}");
+                self.log(format!("failed to check ZK batches: {err}"));
+                None
+            }
+        };
+        self.zk_status = batches;
+    }
+
+    /// Reads the state verified on L1 at most every [`VERIFICATION_INTERVAL`]
+    async fn check_verification(&mut self) {
+        let Some(verifier) = &self.verifier else {
+            return;
+        };
+        if self.verification.is_some_and(|(at, _)| at.elapsed() < VERIFICATION_INTERVAL) {
+            return;
+        }
+        let lag = match verifier.check(&self.endpoints.active().provider, &self.cache).await {
+            Ok(lag) => Some(lag),
+            Err(err) => {
+                warn!("failed to read verified state: {err}");
+                self.log(format!("failed to read verified state: {err}"));
+                None
+            }
+        };
+        self.verification = Some((Instant::now(), lag));
+    }
+
+    /// Reads the custom metrics due, logging the failed reads
+    async fn update_custom_metrics(&mut self) {
+        let provider = &self.endpoints.active().provider;
+        for err in self.custom_metrics.update(provider).await {
+            warn!("{err}");
+            self.log(err);
+        }
+    }
+
+    /// Emits a stall event once the newest block becomes older than [`STALL_AFTER`]
+    async fn check_stall(&mut self) -> eyre::Result<()> {
+        let Some(timestamp) = self.last_block_timestamp else {
+            return Ok(());
+        };
+        let seconds = (Utc::now().timestamp() as u64).saturating_sub(timestamp);
+        let stalled = seconds >= STALL_AFTER.as_secs();
+        if stalled && !self.stalled {
+            info!("no new block for {seconds}s");
+            self.send_event(Utc::now(), EventKind::Stall { seconds }).await?;
+        }
+        self.stalled = stalled;
+        Ok(())
+    }
+
+    async fn send_event(&self, at: DateTime<Utc>, kind: EventKind) -> eyre::Result<()> {
+        let event = NetworkEvent { network: self.network.clone(), at, kind };
+        self.tx.send(BlockMessage::Event(event)).await?;
+        Ok(())
+    }
+
+    /// Records the outcome of a poll and reports the resulting health
+    async fn update_health(&mut self, err: Option<&eyre::Report>) -> eyre::Result<()> {
+        match err {
+            Some(err) => {
+                self.health.consecutive_errors += 1;
+                self.health.last_error = Some(err.to_string());
+            }
+            None => {
+                self.health.consecutive_errors = 0;
+                self.health.last_success = Some(Utc::now());
+            }
+        }
+        let latency = self.endpoints.active().latency();
+        self.health.latency_ms = latency.map(|latency| latency.as_millis() as u64);
+        self.health.endpoints = self.endpoints.stats();
+        self.tx.send(BlockMessage::Health(self.health.clone())).await?;
+        Ok(())
+    }
+
+    /// Shows a line in the network log pane of the UI. Logs are dropped rather than waited on
+    /// when the channel is full, they must never hold up polling.
+    fn log(&self, message: String) {
+        let log = Log { network: self.network.clone(), at: Utc::now(), message };
+        let _ = self.tx.try_send(BlockMessage::Log(log));
+    }
+
+    async fn add_block(&mut self, block: &Block) -> eyre::Result<()> {
+        self.metrics.add_block(block);
+        self.last_block_timestamp = Some(block.header.timestamp);
+        if self.record_blocks {
+            let record = BlockRecord {
+                network: self.network.clone(),
+                number: block.header.number.unwrap_or_default(),
+                timestamp: block.header.timestamp,
+                txs: block.transactions.len() as u64,
+                gas: block.header.gas_used as u64,
+                size: block.size.map(|size| size.as_limbs()[0]),
+            };
+            self.tx.send(BlockMessage::Block(record)).await?;
+        }
+        for kind in self.fee_spikes.observe(block) {
+            let at = DateTime::from_timestamp(block.header.timestamp as i64, 0).unwrap_or_default();
+            let event = NetworkEvent { network: self.network.clone(), at, kind };
+            self.tx.send(BlockMessage::Event(event)).await?;
+        }
+        Ok(())
+    }
+
+    /// Whether the block conflicts with the window, which then holds another block with its
+    /// number or a parent other than the block's
+    fn is_reorged(&self, block: &Block) -> bool {
+        let Some(bn) = block.header.number else {
+            return false;
+        };
+        let replaced = self.metrics.hash(bn).is_some_and(|hash| Some(hash) != block.header.hash);
+        let orphaned = bn
+            .checked_sub(1)
+            .and_then(|parent| self.metrics.hash(parent))
+            .is_some_and(|hash| hash != block.header.parent_hash);
+        replaced || orphaned
+    }
+
+    /// Replaces the blocks of an abandoned fork in the window with the canonical chain ending in
+    /// `block`, fetched by parent hash until it meets the window, and reports the reorg. If the
+    /// chain can't be fetched down to the window, the whole window below it is dropped as the
+    /// rest of the fork can't be told apart. Returns the number of requests made.
+    async fn replace_fork(&mut self, block: Block) -> eyre::Result<usize> {
+        let provider = self.endpoints.active().provider.clone();
+        let mut canonical = vec![block];
+        let mut connected = false;
+        while canonical.len() <= MAX_REORG_DEPTH {
+            let oldest = &canonical[canonical.len() - 1];
+            let Some(parent) = oldest.header.number.and_then(|bn| bn.checked_sub(1)) else {
+                connected = true;
+                break;
+            };
+            if self.metrics.hash(parent).is_none_or(|hash| hash == oldest.header.parent_hash) {
+                connected = true;
+                break;
+            }
+            let (hash, kind) = (oldest.header.parent_hash, self.transactions_kind);
+            match provider.call(|provider| provider.get_block(hash.into(), kind)).await {
+                Ok(Some(parent)) => canonical.push(parent),
+                Ok(None) => break,
+                Err(err) => {
+                    warn!("failed to fetch block {parent} of the canonical chain: {err}");
+                    break;
+                }
+            }
+        }
+        let requests = canonical.len() - 1;
+        let Some(first) = canonical.last().and_then(|block| block.header.number) else {
+            return Ok(requests);
+        };
+        let from = match connected {
+            true => first,
+            false => {
+                warn!("canonical chain not fetched down to the window, dropping the window");
+                0
+            }
+        };
+        let depth = self.metrics.remove_from(from);
+        self.cache.invalidate_from(from);
+        for block in canonical.iter().rev() {
+            self.add_block(block).await?;
+        }
+        if depth > 0 {
+            info!("reorg replaced {depth} blocks from {first}");
+            self.log(format!("reorg replaced {depth} blocks from #{first}"));
+            self.send_event(Utc::now(), EventKind::Reorg { block: first, depth }).await?;
+        }
+        Ok(requests)
+    }
+
+    /// Fetches the blocks after `previous_block` up to `head`, or the latest block number if the
+    /// head isn't known from a subscription. Returns the newest block fetched.
+    async fn get_next_batch(
+        &mut self,
+        previous_block: Option<u64>,
+        head: Option<u64>,
+    ) -> eyre::Result<u64> {
+        let provider = self.endpoints.active().provider.clone();
+        // once ahead of pace, only the newest block is fetched and backfill is skipped
+        self.degraded = provider.budget().is_some_and(|budget| budget.is_ahead_of_pace());
+        let latest_block_number = match head {
+            Some(head) => head,
+            None => {
+                self.cache.head(|| provider.call(|provider| provider.get_block_number())).await?
+            }
+        };
+
+        let backfill = if self.degraded { 1 } else { 10 };
+        let oldest_block = latest_block_number.saturating_sub(backfill);
+        if let Some(previous) = previous_block.filter(|&previous| previous < oldest_block) {
+            self.log(format!(
+                "skipped blocks {} to {oldest_block}, more than {backfill} behind",
+                previous + 1
+            ));
+        }
+        let previous_block = previous_block.unwrap_or_default().max(oldest_block);
+        let mut requests = 1.0 + latest_block_number.saturating_sub(previous_block) as f64;
         let fetch_futures = (previous_block + 1..=latest_block_number)
             .map(|bn| {
-                let provider = self.provider.clone();
-                async move { provider.get_block(bn.into(), BlockTransactionsKind::Hashes).await }
+                let provider = provider.clone();
+                let (cache, kind) = (self.cache.clone(), self.transactions_kind);
+                async move {
+                    let block = cache.block(bn, || provider.get_block_counted(bn, kind)).await;
+                    (bn, block)
+                }
             })
             .collect::<Vec<_>>();
 
-        let mut blocks: Vec<Block> =
-            join_all(fetch_futures).await.into_iter().filter_map(Result::ok).flatten().collect();
+        let mut blocks: Vec<Block> = Vec::new();
+        let mut unavailable = None;
+        for (bn, block) in join_all(fetch_futures).await {
+            match block {
+                Ok(Some(block)) => blocks.push(block),
+                Ok(None) => {
+                    debug!("block {bn} not available yet");
+                    unavailable.get_or_insert(bn);
+                }
+                Err(err) => {
+                    warn!("failed to fetch block {bn}: {err}");
+                    self.log(format!("skipped block {bn}, fetching it failed: {err}"));
+                    self.rate_limited |= is_rate_limited(&err);
+                }
+            }
+        }
+        // a subscription may announce heads the HTTP endpoint doesn't serve yet, those blocks are
+        // fetched again with the next batch
+        let latest_block_number = unavailable.map_or(latest_block_number, |bn| bn - 1);
+        blocks.retain(|block| block.header.number.is_some_and(|bn| bn <= latest_block_number));
 
         blocks.sort_by_key(|block| block.header.number);
         for block in blocks {
-            self.metrics.add_block(&block);
+            match self.is_reorged(&block) {
+                true => requests += self.replace_fork(block).await? as f64,
+                false => self.add_block(&block).await?,
+            }
         }
 
-        Some(latest_block_number)
+        let scan_logs = !self.degraded
+            && previous_block < latest_block_number
+            && self.endpoints.active().supports(Capability::Logs);
+        if !self.bridges.is_empty() && scan_logs {
+            let filter = self.bridges.filter(previous_block + 1, latest_block_number);
+            requests += 1.0;
+            match provider.call(|provider| provider.get_logs(&filter)).await {
+                Ok(logs) => {
+                    for log in logs {
+                        let direction = self.bridges.classify(&log);
+                        if let (Some(bn), Some(direction)) = (log.block_number, direction) {
+                            self.metrics.add_bridge_event(bn, direction);
+                        }
+                    }
+                }
+                Err(err) => {
+                    warn!("failed to fetch bridge logs: {err}");
+                    self.log(format!("failed to fetch bridge logs: {err}"));
+                }
+            }
+        }
+
+        if self.track_mints && scan_logs {
+            let filters = mint_filters(previous_block + 1, latest_block_number);
+            requests += filters.len() as f64;
+            let logs = join_all(
+                filters.iter().map(|filter| provider.call(|provider| provider.get_logs(filter))),
+            )
+            .await;
+            for logs in logs {
+                let logs = match logs {
+                    Ok(logs) => logs,
+                    Err(err) => {
+                        warn!("failed to fetch mint logs: {err}");
+                        self.log(format!("failed to fetch mint logs: {err}"));
+                        continue;
+                    }
+                };
+                for bn in
+                    logs.iter().filter(|log| is_nft_mint(log)).filter_map(|log| log.block_number)
+                {
+                    self.metrics.add_mint(bn);
+                }
+            }
+        }
+
+        // an upper bound, cached heads and blocks cost nothing
+        self.requests_per_poll += 0.2 * (requests - self.requests_per_poll);
+        Ok(latest_block_number)
     }
 }
+
+/// Whether a request was rejected with HTTP 429 for exceeding the host's rate limit
+fn is_rate_limited(err: &impl std::fmt::Display) -> bool {
+    let message = err.to_string();
+    message.contains("429") || message.contains("Too Many Requests")
+}
@@ -0,0 +1,66 @@
+use alloy_primitives::{keccak256, Address, B256};
+use alloy_rpc_types::{Filter, Log};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A bridge contract whose events are counted as deposits into or withdrawals out of a network
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BridgeContract {
+    pub address: Address,
+    /// Event signatures, e.g. `DepositFinalized(address,address,address,address,uint256,bytes)`
+    #[serde(default)]
+    pub deposit_events: Vec<String>,
+    #[serde(default)]
+    pub withdrawal_events: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BridgeDirection {
+    Deposit,
+    Withdrawal,
+}
+
+/// Classifies logs emitted by the configured bridge contracts
+#[derive(Clone, Debug, Default)]
+pub struct BridgeTracker {
+    addresses: Vec<Address>,
+    topics: Vec<B256>,
+    events: HashMap<(Address, B256), BridgeDirection>,
+}
+
+impl BridgeTracker {
+    pub fn new(bridges: &[BridgeContract]) -> Self {
+        let mut tracker = Self::default();
+        for bridge in bridges {
+            let events =
+                bridge.deposit_events.iter().map(|event| (event, BridgeDirection::Deposit)).chain(
+                    bridge.withdrawal_events.iter().map(|e| (e, BridgeDirection::Withdrawal)),
+                );
+            for (event, direction) in events {
+                let topic = keccak256(event.as_bytes());
+                tracker.events.insert((bridge.address, topic), direction);
+                tracker.topics.push(topic);
+            }
+            tracker.addresses.push(bridge.address);
+        }
+        tracker
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Log filter matching all tracked bridge events within the given block range
+    pub fn filter(&self, from_block: u64, to_block: u64) -> Filter {
+        Filter::new()
+            .from_block(from_block)
+            .to_block(to_block)
+            .address(self.addresses.clone())
+            .event_signature(self.topics.clone())
+    }
+
+    pub fn classify(&self, log: &Log) -> Option<BridgeDirection> {
+        let topic = *log.topics().first()?;
+        self.events.get(&(log.address(), topic)).copied()
+    }
+}
@@ -0,0 +1,69 @@
+use crate::types::BudgetStatus;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+const HOUR: Duration = Duration::from_secs(3600);
+/// Share of the hourly budget that may be spent ahead of an even pace before degrading
+const SLACK: f64 = 0.05;
+
+/// Hourly request quota of an endpoint, such as a provider's free tier. Shared by every network
+/// polling the endpoint.
+#[derive(Debug)]
+pub struct RequestBudget {
+    per_hour: u32,
+    /// Start of the current hour and requests spent since
+    window: Mutex<(Instant, u32)>,
+}
+
+impl RequestBudget {
+    pub fn new(per_hour: u32) -> Self {
+        Self { per_hour: per_hour.max(1), window: Mutex::new((Instant::now(), 0)) }
+    }
+
+    /// Returns the start of the current hour and the requests spent since
+    fn window(&self) -> (Instant, u32) {
+        *self.current()
+    }
+
+    /// Locks the window, starting a new hour once the previous one is over
+    fn current(&self) -> MutexGuard<'_, (Instant, u32)> {
+        let mut window = self.window.lock().unwrap();
+        if window.0.elapsed() >= HOUR {
+            *window = (Instant::now(), 0);
+        }
+        window
+    }
+
+    pub fn spend(&self, requests: u32) {
+        let mut window = self.current();
+        window.1 = window.1.saturating_add(requests);
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.per_hour.saturating_sub(self.window().1)
+    }
+
+    /// Whether more than an even share of the budget has been spent so far this hour
+    pub fn is_ahead_of_pace(&self) -> bool {
+        let (start, used) = self.window();
+        let elapsed = start.elapsed().as_secs_f64() / HOUR.as_secs_f64();
+        used as f64 / self.per_hour as f64 > elapsed + SLACK
+    }
+
+    /// Interval between polls costing `per_poll` requests each that spreads the remaining
+    /// budget over the rest of the hour, but no shorter than `min`
+    pub fn poll_interval(&self, per_poll: f64, min: Duration) -> Duration {
+        let (start, used) = self.window();
+        let left = HOUR.saturating_sub(start.elapsed());
+        let remaining = self.per_hour.saturating_sub(used);
+        if remaining == 0 {
+            return left.max(min);
+        }
+        let polls = remaining as f64 / per_poll.max(1.0);
+        left.div_f64(polls).max(min)
+    }
+
+    pub fn status(&self, degraded: bool) -> BudgetStatus {
+        BudgetStatus { remaining: self.remaining(), per_hour: self.per_hour, degraded }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::address_book::AddressLabels;
+use alloy_primitives::{address, Address};
+
+/// Words major MEV-Boost builders stamp into the extra data of their blocks, matched
+/// case-insensitively, with the builder's name
+const EXTRA_DATA: [(&str, &str); 9] = [
+    ("beaverbuild", "beaverbuild"),
+    ("titan", "Titan"),
+    ("rsync", "rsync"),
+    ("illuminate dmocratize", "Flashbots"),
+    ("flashbots", "Flashbots"),
+    ("builder0x69", "builder0x69"),
+    ("bloxroute", "bloXroute"),
+    ("buildai", "BuildAI"),
+    ("penguinbuild", "Penguin"),
+];
+
+/// Fee recipients of major builders, for blocks whose extra data doesn't name them
+const FEE_RECIPIENTS: [(Address, &str); 5] = [
+    (address!("95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfe5"), "beaverbuild"),
+    (address!("4838B106FCe9647Bdf1E7877BF73cE8B0BAD5f97"), "Titan"),
+    (address!("1f9090aaE28b8a3dCeaDf281B0F12828e676c326"), "rsync"),
+    (address!("DAFEA492D9c6733ae3d56b7Ed1ADB60692c98Bc5"), "Flashbots"),
+    (address!("690B9A9E9aa1C9dB991C7721a92d351Db4FaC990"), "builder0x69"),
+];
+
+/// Words execution clients stamp into the extra data of the blocks they build, marking blocks a
+/// validator built itself rather than took from a MEV-Boost relay
+const CLIENTS: [&str; 5] = ["geth", "nethermind", "erigon", "besu", "reth"];
+
+/// Longest extra data of an unknown builder kept as its name
+const MAX_NAME_LEN: usize = 16;
+
+/// Names the builder of an Ethereum block from its extra data and fee recipient, labeled in
+/// `labels` for builders not known here. Blocks of unknown builders are named by their extra data
+/// if it's text. Blocks stamped by an execution client, or not at all, were built by their
+/// validator and have no builder.
+pub fn identify(
+    extra_data: &[u8],
+    fee_recipient: Address,
+    labels: &AddressLabels,
+) -> Option<String> {
+    let text = String::from_utf8_lossy(extra_data);
+    let lowercase = text.to_lowercase();
+    let known = EXTRA_DATA
+        .iter()
+        .find(|(word, _)| lowercase.contains(word))
+        .map(|&(_, name)| name)
+        .or_else(|| {
+            FEE_RECIPIENTS
+                .iter()
+                .find(|(address, _)| *address == fee_recipient)
+                .map(|&(_, name)| name)
+        })
+        .or_else(|| labels.get(&fee_recipient));
+    if let Some(name) = known {
+        return Some(name.to_string());
+    }
+    if lowercase.split(|c: char| !c.is_alphanumeric()).any(|word| CLIENTS.contains(&word)) {
+        return None;
+    }
+    let text = text.trim_matches(|c: char| c.is_control() || c.is_whitespace());
+    if !text.is_empty() && text.chars().all(|c| c.is_ascii_graphic() || c == ' ') {
+        return Some(text.chars().take(MAX_NAME_LEN).collect());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address_book::AddressBook;
+    use std::io::Write;
+
+    const VALIDATOR: Address = address!("388C818CA8B9251b393131C08a736A67ccB19297");
+
+    #[test]
+    fn names_known_builders_by_extra_data_or_fee_recipient() {
+        let labels = AddressLabels::default();
+        assert_eq!(
+            identify(b"beaverbuild.org", VALIDATOR, &labels).as_deref(),
+            Some("beaverbuild")
+        );
+        assert_eq!(
+            identify(b"Titan (titanbuilder.xyz)", VALIDATOR, &labels).as_deref(),
+            Some("Titan")
+        );
+        let rsync = FEE_RECIPIENTS[2].0;
+        assert_eq!(identify(&[0xff, 0x01], rsync, &labels).as_deref(), Some("rsync"));
+    }
+
+    #[test]
+    fn names_unknown_builders_by_their_stamp_or_label() {
+        let labels = AddressLabels::default();
+        let name = identify(b"  some new builder v2 ", VALIDATOR, &labels);
+        assert_eq!(name.as_deref(), Some("some new builder"));
+        let path =
+            std::env::temp_dir().join(format!("rollups-addressbook-{}.json", std::process::id()));
+        let mut file = std::fs::File::create(&path).unwrap();
+        write!(file, r#"{{ "global": {{ "{VALIDATOR}": "My builder" }} }}"#).unwrap();
+        let book = AddressBook::read(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let labels = book.for_network("ethereum");
+        assert_eq!(identify(&[0xff, 0x01], VALIDATOR, &labels).as_deref(), Some("My builder"));
+    }
+
+    #[test]
+    fn leaves_blocks_built_by_validators_unattributed() {
+        let labels = AddressLabels::default();
+        assert_eq!(identify(b"Nethermind v1.25.4", VALIDATOR, &labels), None);
+        assert_eq!(identify(b"reth/v1.0.0/linux", VALIDATOR, &labels), None);
+        assert_eq!(
+            identify(b"\xd8\x83\x01\x0e\x03\x84geth\x88go1.22.2\x85linux", VALIDATOR, &labels),
+            None
+        );
+        assert_eq!(identify(b"", VALIDATOR, &labels), None);
+        assert_eq!(identify(&[0xff, 0x01], VALIDATOR, &labels), None);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::rpc_pool::MeteredProvider;
+use alloy_provider::Provider;
+use futures::future::join_all;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Optional RPC methods probed on each endpoint at startup
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Capability {
+    Logs,
+    SafeHead,
+    FeeHistory,
+    BlockReceipts,
+    TxPool,
+    Trace,
+}
+
+/// Whether each capability was found supported, those whose probe failed for another reason
+/// being left out
+pub type Capabilities = BTreeMap<Capability, bool>;
+
+impl Capability {
+    pub const ALL: [Capability; 6] = [
+        Capability::Logs,
+        Capability::SafeHead,
+        Capability::FeeHistory,
+        Capability::BlockReceipts,
+        Capability::TxPool,
+        Capability::Trace,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Capability::Logs => "eth_getLogs",
+            Capability::SafeHead => "safe head",
+            Capability::FeeHistory => "eth_feeHistory",
+            Capability::BlockReceipts => "eth_getBlockReceipts",
+            Capability::TxPool => "txpool",
+            Capability::Trace => "debug_trace",
+        }
+    }
+
+    /// Features relying on the capability, which are skipped on endpoints lacking it
+    pub fn features(self) -> Option<&'static str> {
+        match self {
+            Capability::Logs => Some("bridge and mint tracking"),
+            Capability::SafeHead => Some("batch tracking"),
+            _ => None,
+        }
+    }
+
+    /// A cheap request only an endpoint supporting the capability answers without a method error
+    fn request(self) -> (&'static str, Value) {
+        match self {
+            Capability::Logs => {
+                ("eth_getLogs", json!([{ "fromBlock": "latest", "toBlock": "latest" }]))
+            }
+            Capability::SafeHead => ("eth_getBlockByNumber", json!(["safe", false])),
+            Capability::FeeHistory => ("eth_feeHistory", json!([1, "latest", []])),
+            Capability::BlockReceipts => ("eth_getBlockReceipts", json!(["latest"])),
+            Capability::TxPool => ("txpool_status", json!([])),
+            // an unknown transaction fails with a method error only when tracing is unavailable
+            Capability::Trace => {
+                ("debug_traceTransaction", json!([format!("0x{}", "0".repeat(64))]))
+            }
+        }
+    }
+}
+
+/// Calls the capability's method on the endpoint, returning whether it is supported or `None`
+/// if the request failed before the node answered, e.g. on a timeout. Other errors from the node
+/// mean the method exists.
+pub async fn probe(provider: &MeteredProvider, capability: Capability) -> Option<bool> {
+    let (method, params) = capability.request();
+    let request = provider.call(|provider| provider.raw_request::<_, Value>(method.into(), params));
+    let err = match request.await {
+        Ok(_) => return Some(true),
+        Err(err) => err.to_string(),
+    };
+    match is_unsupported(&err) {
+        true => Some(false),
+        false => err.contains("error response").then_some(true),
+    }
+}
+
+/// Probes the capabilities on the endpoint at once
+pub async fn probe_all(provider: &MeteredProvider, capabilities: &[Capability]) -> Capabilities {
+    let probes = capabilities
+        .iter()
+        .map(|&capability| async move { (capability, probe(provider, capability).await) });
+    join_all(probes)
+        .await
+        .into_iter()
+        .filter_map(|(capability, supported)| Some((capability, supported?)))
+        .collect()
+}
+
+/// Whether an error says the method is unknown or disabled, as worded by common node clients and
+/// RPC providers
+fn is_unsupported(message: &str) -> bool {
+    let message = message.to_lowercase();
+    let refusals = [
+        "not found",
+        "does not exist",
+        "not available",
+        "not supported",
+        "unsupported",
+        "not whitelisted",
+        "not allowed",
+        "not enabled",
+    ];
+    message.contains("-32601")
+        || ((message.contains("method") || message.contains("namespace"))
+            && refusals.iter().any(|refusal| message.contains(refusal)))
+}
@@ -0,0 +1,218 @@
+use crate::columns::Column;
+use crate::thresholds::{Limits, ThresholdMetric, LAG_BLOCK_TIMES};
+use ratatui::style::{Color, Modifier, Style};
+use serde_derive::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Conditional formatting of a table cell, e.g. `{ "metric": "block_age", "above": 30, "color":
+/// "yellow" }`. The cell of the metric's column is styled while its value crosses the limits.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ColorRule {
+    pub metric: ThresholdMetric,
+    #[serde(flatten)]
+    pub limits: Limits,
+    /// Network labels the rule applies to; all networks if empty
+    #[serde(default)]
+    pub networks: Vec<String>,
+    /// A color name such as `yellow` or `light-green`, or a `#rrggbb` hex code
+    #[serde(default)]
+    pub color: Option<CellColor>,
+    #[serde(default)]
+    pub bold: bool,
+    /// Shown before the cell's value, e.g. `⚠`
+    #[serde(default)]
+    pub symbol: Option<String>,
+}
+
+impl ColorRule {
+    /// The rules applied unless configured: lagging networks in red with `⚠`, and TPS far off its
+    /// usual level at this hour with an arrow
+    pub fn defaults() -> Vec<ColorRule> {
+        let rule = |metric, limits, color: Color, bold, symbol: &str| ColorRule {
+            metric,
+            limits,
+            networks: Vec::new(),
+            color: Some(CellColor(color)),
+            bold,
+            symbol: Some(symbol.to_string()),
+        };
+        let above = |limit| Limits { above: Some(limit), below: None };
+        let below = |limit| Limits { above: None, below: Some(limit) };
+        vec![
+            rule(ThresholdMetric::Lag, above(LAG_BLOCK_TIMES), Color::Red, true, "⚠"),
+            rule(ThresholdMetric::TpsBaseline, above(2.0), Color::Yellow, false, "▲"),
+            rule(ThresholdMetric::TpsBaseline, below(0.5), Color::Yellow, false, "▼"),
+        ]
+    }
+
+    pub fn validate(&self) -> eyre::Result<()> {
+        self.limits.validate(&self.metric)
+    }
+
+    fn applies_to(&self, network: &str, cell: CellColumn) -> bool {
+        let column = match (&self.metric, cell) {
+            (ThresholdMetric::Custom(label), CellColumn::Custom(custom)) => label == custom,
+            (ThresholdMetric::Custom(_), _) | (_, CellColumn::Custom(_)) => false,
+            (metric, CellColumn::Fixed(column)) => metric_column(metric) == Some(column),
+        };
+        column && (self.networks.is_empty() || self.networks.iter().any(|n| n == network))
+    }
+
+    fn style(&self) -> Style {
+        let style = match self.color {
+            Some(CellColor(color)) => Style::default().fg(color),
+            None => Style::default(),
+        };
+        if self.bold {
+            style.add_modifier(Modifier::BOLD)
+        } else {
+            style
+        }
+    }
+}
+
+/// Column showing the metric, `None` for custom metrics which have columns of their own
+fn metric_column(metric: &ThresholdMetric) -> Option<Column> {
+    match metric {
+        ThresholdMetric::Tps => Some(Column::Tps),
+        ThresholdMetric::Gps => Some(Column::Gas),
+        ThresholdMetric::Dps => Some(Column::Data),
+        ThresholdMetric::Da => Some(Column::Da),
+        ThresholdMetric::BlockAge | ThresholdMetric::Lag => Some(Column::LastBlock),
+        ThresholdMetric::TpsBaseline => Some(Column::Tps),
+        ThresholdMetric::FailedPolls => Some(Column::Status),
+        ThresholdMetric::Custom(_) => None,
+    }
+}
+
+/// Column of a table cell, one of the fixed ones or a custom metric's by label
+#[derive(Clone, Copy)]
+pub enum CellColumn<'a> {
+    Fixed(Column),
+    Custom(&'a str),
+}
+
+/// Formatting the matching rules apply to a network's cell
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellFormat {
+    /// Applied on top of the cell's style
+    pub style: Style,
+    pub symbol: Option<String>,
+}
+
+/// Formatting the matching rules apply to a network's cell, later rules taking precedence.
+/// `value` reads a metric of the network.
+pub fn cell_format(
+    rules: &[ColorRule],
+    network: &str,
+    cell: CellColumn,
+    value: impl Fn(&ThresholdMetric) -> Option<f64>,
+) -> Option<CellFormat> {
+    rules
+        .iter()
+        .filter(|rule| rule.applies_to(network, cell))
+        .filter(|rule| value(&rule.metric).is_some_and(|value| rule.limits.is_crossed(value)))
+        .map(|rule| CellFormat { style: rule.style(), symbol: rule.symbol.clone() })
+        .reduce(|format, rule| CellFormat {
+            style: format.style.patch(rule.style),
+            symbol: rule.symbol.or(format.symbol),
+        })
+}
+
+/// A color parsed from its name or hex code
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CellColor(Color);
+
+impl TryFrom<String> for CellColor {
+    type Error = eyre::Report;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Color::from_str(&name).map(CellColor).map_err(|_| eyre::eyre!("unknown color `{name}`"))
+    }
+}
+
+impl From<CellColor> for String {
+    fn from(color: CellColor) -> Self {
+        color.0.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(json: &str) -> ColorRule {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn format(
+        rules: &[ColorRule],
+        network: &str,
+        cell: CellColumn,
+        value: f64,
+    ) -> Option<CellFormat> {
+        cell_format(rules, network, cell, |_| Some(value))
+    }
+
+    #[test]
+    fn styles_the_cell_of_the_metric_while_crossed() {
+        let rules = [rule(r#"{ "metric": "block_age", "above": 30, "color": "yellow" }"#)];
+        let last_block = CellColumn::Fixed(Column::LastBlock);
+        let style = Style::default().fg(Color::Yellow);
+        assert_eq!(format(&rules, "base", last_block, 31.0).map(|f| f.style), Some(style));
+        assert_eq!(format(&rules, "base", last_block, 30.0), None);
+        assert_eq!(format(&rules, "base", CellColumn::Fixed(Column::Tps), 31.0), None);
+        // a metric the network lacks leaves the cell alone
+        assert_eq!(cell_format(&rules, "base", last_block, |_| None), None);
+    }
+
+    #[test]
+    fn applies_to_the_listed_networks_and_custom_columns() {
+        let rules =
+            [rule(r#"{ "metric": { "custom": "queue" }, "above": 1, "networks": ["base"] }"#)];
+        assert!(format(&rules, "base", CellColumn::Custom("queue"), 2.0).is_some());
+        assert!(format(&rules, "base", CellColumn::Custom("other"), 2.0).is_none());
+        assert!(format(&rules, "zora", CellColumn::Custom("queue"), 2.0).is_none());
+    }
+
+    #[test]
+    fn later_rules_take_precedence() {
+        let rules = [
+            rule(r##"{ "metric": "tps", "above": 1, "color": "red", "symbol": "!" }"##),
+            rule(r##"{ "metric": "tps", "above": 2, "color": "#00ff00", "bold": true }"##),
+        ];
+        let tps = CellColumn::Fixed(Column::Tps);
+        let format = format(&rules, "base", tps, 3.0).unwrap();
+        let style = Style::default().fg(Color::Rgb(0, 255, 0)).add_modifier(Modifier::BOLD);
+        assert_eq!(format.style, style);
+        // a rule without a symbol keeps the one of the rules before it
+        assert_eq!(format.symbol.as_deref(), Some("!"));
+    }
+
+    #[test]
+    fn defaults_flag_lagging_networks_and_tps_off_its_baseline() {
+        let rules = ColorRule::defaults();
+        let symbol = |cell, value| format(&rules, "base", cell, value).and_then(|f| f.symbol);
+        let last_block = CellColumn::Fixed(Column::LastBlock);
+        let tps = CellColumn::Fixed(Column::Tps);
+        assert_eq!(symbol(last_block, LAG_BLOCK_TIMES + 1.0).as_deref(), Some("⚠"));
+        assert_eq!(symbol(last_block, 1.0), None);
+        assert_eq!(symbol(tps, 2.5).as_deref(), Some("▲"));
+        assert_eq!(symbol(tps, 0.4).as_deref(), Some("▼"));
+        assert_eq!(symbol(tps, 1.0), None);
+        // dumped with the effective configuration to be copied into the settings
+        let json = serde_json::to_string(&rules).unwrap();
+        let parsed = serde_json::from_str::<Vec<ColorRule>>(&json).unwrap();
+        assert_eq!(parsed[0].color.map(|color| color.0), Some(Color::Red));
+    }
+
+    #[test]
+    fn rejects_rules_without_limits_or_with_unknown_colors() {
+        assert!(rule(r#"{ "metric": "tps" }"#).validate().is_err());
+        assert!(serde_json::from_str::<ColorRule>(
+            r#"{ "metric": "tps", "above": 1, "color": "nope" }"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::units::Units;
+use ratatui::layout::Constraint;
+use serde_derive::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Columns of the networks table, in display order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    Network,
+    Block,
+    BlockTime,
+    LastBlock,
+    Tps,
+    Gas,
+    Data,
+    Da,
+    BaseFee,
+    PriorityFee,
+    Value,
+    /// Share of total tx/gas/data
+    Share,
+    /// Most frequent MEV-Boost builder and its share of the blocks
+    Builder,
+    /// Age of the state verified on L1
+    Verification,
+    Updated,
+    Status,
+}
+
+impl Column {
+    pub const ALL: [Column; 16] = [
+        Column::Network,
+        Column::Block,
+        Column::BlockTime,
+        Column::LastBlock,
+        Column::Tps,
+        Column::Gas,
+        Column::Data,
+        Column::Da,
+        Column::BaseFee,
+        Column::PriorityFee,
+        Column::Value,
+        Column::Share,
+        Column::Builder,
+        Column::Verification,
+        Column::Updated,
+        Column::Status,
+    ];
+
+    /// Columns the table can be sorted by, in the order `s` cycles through them
+    pub const SORTABLE: [Column; 5] =
+        [Column::Network, Column::Block, Column::Tps, Column::Gas, Column::Data];
+
+    pub fn title(self, units: Units) -> &'static str {
+        match self {
+            Column::Network => "Network",
+            Column::Block => "Block",
+            Column::BlockTime => "Block time",
+            Column::LastBlock => "Last block",
+            Column::Tps => "TPS",
+            Column::Gas => units.gas.label(),
+            Column::Data => units.data.label(),
+            Column::Da => units.data.da_label(),
+            Column::BaseFee => "Base fee",
+            Column::PriorityFee => "Priority fee",
+            Column::Value => "Value/s",
+            Column::Share => "Share",
+            Column::Builder => "Top builder",
+            Column::Verification => "Verify lag",
+            Column::Updated => "Updated",
+            Column::Status => "Status",
+        }
+    }
+
+    /// Name of the column in console commands
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Network => "network",
+            Column::Block => "block",
+            Column::BlockTime => "block-time",
+            Column::LastBlock => "last-block",
+            Column::Tps => "tps",
+            Column::Gas => "gas",
+            Column::Data => "data",
+            Column::Da => "da",
+            Column::BaseFee => "base-fee",
+            Column::PriorityFee => "priority-fee",
+            Column::Value => "value",
+            Column::Share => "share",
+            Column::Builder => "builder",
+            Column::Verification => "verify-lag",
+            Column::Updated => "updated",
+            Column::Status => "status",
+        }
+    }
+
+    pub fn constraint(self, longest_name: u16) -> Constraint {
+        match self {
+            Column::Network => Constraint::Length(longest_name + 2),
+            Column::Block => Constraint::Min(0),
+            Column::BlockTime | Column::LastBlock => Constraint::Min(10),
+            Column::Tps | Column::Gas | Column::Data | Column::Da => Constraint::Min(5),
+            Column::BaseFee | Column::PriorityFee | Column::Value => Constraint::Min(12),
+            Column::Share => Constraint::Min(11),
+            Column::Builder => Constraint::Min(16),
+            Column::Verification => Constraint::Min(10),
+            Column::Updated => Constraint::Min(7),
+            Column::Status => Constraint::Min(10),
+        }
+    }
+
+    /// Position of the column's value in a row's cells
+    pub fn index(self) -> usize {
+        Column::ALL.iter().position(|&column| column == self).unwrap_or_default()
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Column::ALL.into_iter().find(|column| column.name() == name).ok_or_else(|| {
+            let names = Column::ALL.map(Column::name).join(", ");
+            format!("unknown column `{name}`, one of {names}")
+        })
+    }
+}
+
+/// Order of the networks table. Mainnets always come before testnets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sort {
+    pub column: Column,
+    pub descending: bool,
+    /// Keeps the current order instead of sorting as metrics arrive, new networks being added
+    /// at the end
+    pub frozen: bool,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Self { column: Column::Tps, descending: true, frozen: false }
+    }
+}
+
+impl Sort {
+    /// Sorts by the next sortable column, numbers descending and names ascending
+    pub fn next_column(self) -> Self {
+        let i = Column::SORTABLE.iter().position(|&column| column == self.column);
+        let column = Column::SORTABLE[i.map_or(0, |i| (i + 1) % Column::SORTABLE.len())];
+        Self { column, descending: column != Column::Network, frozen: false }
+    }
+}
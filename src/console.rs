@@ -0,0 +1,125 @@
+use crate::columns::Column;
+use std::str::FromStr;
+
+pub const HELP: &str = "window <seconds>[,<seconds>...] | add <url> [label] | pause/resume <network> | remove <network> | hide/show <column> | widths reset | note <network> [text] | mark [label] | export csv [path] | config dump [path] | help | quit";
+
+/// A command entered at the `:` prompt
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConsoleCommand {
+    /// Sets the metrics windows of every network, the first being the main window
+    Window(Vec<u64>),
+    /// Starts polling a new network
+    Add {
+        url: String,
+        label: Option<String>,
+    },
+    /// Stops polling a network until it is resumed
+    Pause(String),
+    Resume(String),
+    /// Stops polling a network and drops it from the table
+    Remove(String),
+    /// Hides or shows a column of the table
+    Hide(Column),
+    Show(Column),
+    /// Restores the widths of the columns resized with Shift+←/→
+    ResetWidths,
+    /// Sets or, without text, clears the note of a network. The network label may contain
+    /// spaces, so the label and text are split by matching against the known labels.
+    Note(String),
+    /// Drops a timestamped marker on the history charts and event timeline
+    Mark(String),
+    /// Writes the table to a CSV file
+    ExportCsv {
+        path: Option<String>,
+    },
+    /// Writes the effective configuration to a JSON file
+    DumpConfig {
+        path: Option<String>,
+    },
+    Help,
+    Quit,
+}
+
+impl FromStr for ConsoleCommand {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut args = input.split_whitespace();
+        let command = args.next().ok_or_else(|| HELP.to_string())?;
+        let command = match command {
+            "window" | "w" => {
+                let windows = args
+                    .flat_map(|arg| arg.split(','))
+                    .filter(|seconds| !seconds.is_empty())
+                    .map(|seconds| match seconds.parse() {
+                        Ok(seconds) if seconds > 0 => Ok(seconds),
+                        _ => Err(format!("invalid window `{seconds}`")),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if windows.is_empty() {
+                    return Err("usage: window <seconds>[,<seconds>...]".to_string());
+                }
+                ConsoleCommand::Window(windows)
+            }
+            "add" => {
+                let url = args.next().ok_or("usage: add <url> [label]")?.to_string();
+                let label = args.collect::<Vec<_>>().join(" ");
+                ConsoleCommand::Add { url, label: (!label.is_empty()).then_some(label) }
+            }
+            "pause" | "resume" => {
+                let network = args.collect::<Vec<_>>().join(" ");
+                if network.is_empty() {
+                    return Err(format!("usage: {command} <network>"));
+                }
+                if command == "pause" {
+                    ConsoleCommand::Pause(network)
+                } else {
+                    ConsoleCommand::Resume(network)
+                }
+            }
+            "remove" | "rm" => {
+                let network = args.collect::<Vec<_>>().join(" ");
+                if network.is_empty() {
+                    return Err(format!("usage: {command} <network>"));
+                }
+                ConsoleCommand::Remove(network)
+            }
+            "hide" | "show" => {
+                let column = args.next().ok_or_else(|| format!("usage: {command} <column>"))?;
+                let column = column.parse()?;
+                if command == "hide" {
+                    ConsoleCommand::Hide(column)
+                } else {
+                    ConsoleCommand::Show(column)
+                }
+            }
+            "widths" => match args.next() {
+                Some("reset") => ConsoleCommand::ResetWidths,
+                _ => return Err("usage: widths reset".to_string()),
+            },
+            "note" => {
+                let args = args.collect::<Vec<_>>().join(" ");
+                if args.is_empty() {
+                    return Err("usage: note <network> [text]".to_string());
+                }
+                ConsoleCommand::Note(args)
+            }
+            "mark" | "m" => {
+                let label = args.collect::<Vec<_>>().join(" ");
+                ConsoleCommand::Mark(if label.is_empty() { "marker".to_string() } else { label })
+            }
+            "export" => match args.next() {
+                Some("csv") => ConsoleCommand::ExportCsv { path: args.next().map(String::from) },
+                _ => return Err("usage: export csv [path]".to_string()),
+            },
+            "config" => match args.next() {
+                Some("dump") => ConsoleCommand::DumpConfig { path: args.next().map(String::from) },
+                _ => return Err("usage: config dump [path]".to_string()),
+            },
+            "help" | "h" => ConsoleCommand::Help,
+            "quit" | "q" => ConsoleCommand::Quit,
+            _ => return Err(format!("unknown command `{command}`, {HELP}")),
+        };
+        Ok(command)
+    }
+}
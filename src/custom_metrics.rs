@@ -0,0 +1,108 @@
+use crate::rpc_pool::MeteredProvider;
+use crate::types::CustomValue;
+use alloy_provider::Provider;
+use futures::future::join_all;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// How often a custom metric is read unless configured
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A number read from a chain-specific JSON-RPC method and shown as a column of its own, e.g.
+/// `{ "label": "Unsafe L2", "method": "optimism_syncStatus", "pointer": "/unsafe_l2/number" }`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CustomMetric {
+    /// Title of the column
+    pub label: String,
+    pub method: String,
+    #[serde(default)]
+    pub params: Vec<Value>,
+    /// JSON pointer to the number in the result, the result itself unless set
+    #[serde(default)]
+    pub pointer: String,
+    /// Seconds between reads, 30 unless configured
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+}
+
+impl CustomMetric {
+    /// Checks that the pointer is a JSON pointer, so a typo fails at startup rather than on
+    /// every read
+    pub fn validate(&self) -> eyre::Result<()> {
+        let pointer = &self.pointer;
+        let escapes_valid = pointer
+            .split('~')
+            .skip(1)
+            .all(|escaped| escaped.starts_with('0') || escaped.starts_with('1'));
+        if !(pointer.is_empty() || pointer.starts_with('/')) || !escapes_valid {
+            eyre::bail!(
+                "invalid pointer `{pointer}` of custom metric {}, expected e.g. `/number`",
+                self.label
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Reads the custom metrics of a network, each on its own interval
+pub struct CustomMetrics {
+    metrics: Vec<CustomMetric>,
+    /// When each metric was last read and its value, if the read succeeded
+    values: Vec<Option<(Instant, Option<f64>)>>,
+}
+
+impl CustomMetrics {
+    pub fn new(metrics: &[CustomMetric]) -> Self {
+        Self { metrics: metrics.to_vec(), values: vec![None; metrics.len()] }
+    }
+
+    /// Reads the metrics due at once, returning an error message per failed read. Each read is
+    /// paced by the host's rate limit and fails after the request timeout.
+    pub async fn update(&mut self, provider: &MeteredProvider) -> Vec<String> {
+        let due = self.metrics.iter().zip(&mut self.values).filter(|(metric, value)| {
+            let interval = metric.interval_secs.map_or(DEFAULT_INTERVAL, Duration::from_secs);
+            value.is_none_or(|(at, _)| at.elapsed() >= interval)
+        });
+        let reads = due.map(|(metric, value)| async move {
+            let read = read(provider, metric).await;
+            *value = Some((Instant::now(), read.as_ref().ok().copied()));
+            read.err().map(|err| format!("failed to read {}: {err}", metric.label))
+        });
+        join_all(reads).await.into_iter().flatten().collect()
+    }
+
+    pub fn values(&self) -> Vec<CustomValue> {
+        self.metrics
+            .iter()
+            .zip(&self.values)
+            .map(|(metric, value)| CustomValue {
+                label: metric.label.clone(),
+                value: value.and_then(|(_, value)| value),
+            })
+            .collect()
+    }
+}
+
+async fn read(provider: &MeteredProvider, metric: &CustomMetric) -> eyre::Result<f64> {
+    let (method, params) = (metric.method.clone(), metric.params.clone());
+    let result: Value =
+        provider.call(|provider| provider.raw_request(method.into(), params)).await?;
+    let value = result
+        .pointer(&metric.pointer)
+        .ok_or_else(|| eyre::eyre!("no `{}` in the result", metric.pointer))?;
+    number(value).ok_or_else(|| eyre::eyre!("`{value}` is not a number"))
+}
+
+/// Reads a JSON number, or a string holding a decimal or `0x` prefixed hex number as is common in
+/// JSON-RPC results
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(string) => match string.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16).ok().map(|number| number as f64),
+            None => string.parse().ok(),
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,75 @@
+use crate::block_metrics::{BlockInfo, BlockMetricsBuffer};
+use crate::types::{BlockMessage, NetworkHealth, NetworkInfo};
+use chrono::Utc;
+use serde_derive::Deserialize;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio::time;
+use tracing::debug;
+
+/// A couple of minutes of blocks of a few networks, in the block cache format
+const RECORDINGS: &str = include_str!("../assets/demo.json");
+
+#[derive(Deserialize)]
+struct Recording {
+    network: NetworkInfo,
+    /// Consecutive blocks, oldest first
+    blocks: Vec<BlockInfo>,
+}
+
+/// Replays the bundled recordings in a loop as if their networks were polled, so the UI can be
+/// tried without network access. Blocks are renumbered and moved to the current time, the first
+/// pass filling the metrics windows right away.
+pub fn replay(windows: &[u64], tx: &Sender<BlockMessage>) -> eyre::Result<()> {
+    let recordings: Vec<Recording> = serde_json::from_str(RECORDINGS)?;
+    for recording in recordings {
+        let (windows, tx) = (windows.to_vec(), tx.clone());
+        tokio::spawn(async move {
+            if let Err(err) = play(recording, &windows, tx).await {
+                debug!("demo replay stopped: {err}");
+            }
+        });
+    }
+    Ok(())
+}
+
+async fn play(recording: Recording, windows: &[u64], tx: Sender<BlockMessage>) -> eyre::Result<()> {
+    let Recording { network, blocks } = recording;
+    let (Some(first), Some(last)) = (blocks.first(), blocks.last()) else {
+        return Ok(());
+    };
+    let (number, start) = (first.number(), first.timestamp());
+    // passes are a block time apart, keeping the cadence of the recording across loops
+    let recorded = last.timestamp() - start;
+    let span = recorded + (recorded / (blocks.len() as u64 - 1).max(1)).max(1);
+    let label = network.label.clone();
+    tx.send(BlockMessage::AddNetwork(network)).await?;
+
+    let mut buffer = BlockMetricsBuffer::new(label.clone(), windows);
+    let offset = (Utc::now().timestamp() as u64).saturating_sub(span);
+    for pass in 0u64.. {
+        for (i, block) in blocks.iter().enumerate() {
+            let timestamp = offset + pass * span + block.timestamp() - start;
+            let now = Utc::now().timestamp() as u64;
+            if timestamp > now {
+                time::sleep(Duration::from_secs(timestamp - now)).await;
+            }
+            let bn = number + pass * blocks.len() as u64 + i as u64;
+            let block = block.replayed(bn, timestamp);
+            tx.send(BlockMessage::Block(block.record(&label))).await?;
+            buffer.add_block_info(block);
+            // the first pass lies in the past and is reported once complete
+            if pass == 0 && i + 1 < blocks.len() {
+                continue;
+            }
+            tx.send(BlockMessage::UpdateNetwork(Box::new(buffer.get_metrics()))).await?;
+            let health = NetworkHealth {
+                network: label.clone(),
+                last_success: Some(Utc::now()),
+                ..Default::default()
+            };
+            tx.send(BlockMessage::Health(health)).await?;
+        }
+    }
+    Ok(())
+}
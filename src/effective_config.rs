@@ -0,0 +1,76 @@
+use crate::alert_sinks::AlertSink;
+use crate::color_rules::ColorRule;
+use crate::indices::IndexConfig;
+use crate::networks::Network;
+use crate::settings::TimeZone;
+use crate::terminal::Palette;
+use crate::thresholds::Threshold;
+use chrono::Local;
+use serde_derive::Serialize;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::net::IpAddr;
+use std::path::Path;
+
+/// The configuration the tool runs with, after defaults and command line arguments are applied
+/// and including networks added at runtime
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct EffectiveConfig {
+    /// `tui`, `serve`, `headless`, `watch`, `demo`, `replay` or `attach`
+    pub mode: String,
+    pub log_level: String,
+    pub log_dir: String,
+    pub palette: Palette,
+    /// Whether the UI is drawn inline instead of on the alternate screen
+    pub passive: bool,
+    /// Port the Prometheus metrics are served on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics_port: Option<u16>,
+    /// Address the Prometheus metrics are served on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics_address: Option<IpAddr>,
+    /// SQLite file the blocks are recorded to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record: Option<String>,
+    /// Unix socket the session is shared on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share: Option<String>,
+    /// Address the feed is served on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen: Option<String>,
+    /// Remote collectors watched or federated
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub collectors: Vec<String>,
+    /// Metrics windows in seconds
+    pub windows: Vec<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub indices: Vec<IndexConfig>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub color_rules: Vec<ColorRule>,
+    /// Time zone block timestamps are shown in
+    pub time_zone: TimeZone,
+    /// Mean time in milliseconds a UI frame may take before charts are paused
+    pub frame_budget_ms: u64,
+    pub networks: Vec<Network>,
+    pub alert_sinks: Vec<AlertSink>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub thresholds: Vec<Threshold>,
+}
+
+impl EffectiveConfig {
+    /// Writes the configuration as JSON, under `exports/` unless a path is given, returning its
+    /// path
+    pub fn write(&self, path: Option<String>) -> eyre::Result<String> {
+        let path = path.unwrap_or_else(|| {
+            format!("exports/config-{}.json", Local::now().format("%Y%m%d-%H%M%S"))
+        });
+        if let Some(dir) = Path::new(&path).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = BufWriter::new(File::create(&path)?);
+        serde_json::to_writer_pretty(&mut file, self)?;
+        writeln!(file)?;
+        file.flush()?;
+        Ok(path)
+    }
+}
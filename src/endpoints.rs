@@ -0,0 +1,185 @@
+use crate::capabilities::{self, Capabilities, Capability};
+use crate::networks::{url_authority, AcceptEncoding, Network};
+use crate::rpc_pool::{MeteredProvider, RpcPool};
+use crate::types::EndpointStats;
+use alloy_provider::Provider;
+use futures::future::join_all;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// A candidate must score this much better than the active endpoint to replace it
+const HYSTERESIS: f64 = 0.25;
+/// Minimum time between two switches
+const MIN_DWELL: Duration = Duration::from_secs(30);
+/// How often inactive endpoints are probed
+const PROBE_INTERVAL: Duration = Duration::from_secs(10);
+
+pub struct Endpoint {
+    pub url: String,
+    pub provider: MeteredProvider,
+    capabilities: Capabilities,
+}
+
+impl Endpoint {
+    /// Whether the endpoint supports the capability, as far as probed
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.capabilities.get(&capability) != Some(&false)
+    }
+
+    pub fn latency(&self) -> Option<Duration> {
+        self.provider.stats().latency.map(Duration::from_secs_f64)
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.provider.budget().is_some_and(|budget| budget.remaining() == 0)
+    }
+
+    /// Lower is better, failing endpoints are penalized well beyond their latency
+    fn score(&self) -> f64 {
+        let stats = self.provider.stats();
+        stats.latency.unwrap_or(1.0) * (1.0 + 10.0 * stats.error_rate)
+    }
+}
+
+/// Keeps latency and error rate statistics for each endpoint of a network and dynamically
+/// prefers the best performing one
+pub struct EndpointSelector {
+    endpoints: Vec<Endpoint>,
+    active: usize,
+    last_switch: Option<Instant>,
+    last_probe: Instant,
+    accept_encoding: AcceptEncoding,
+}
+
+impl EndpointSelector {
+    pub fn new(network: &Network, rpc_pool: &RpcPool) -> eyre::Result<Self> {
+        let endpoints = network
+            .endpoints()
+            .map(|url| {
+                Ok(Endpoint {
+                    url: url.to_string(),
+                    provider: rpc_pool.endpoint(url)?,
+                    capabilities: Capabilities::new(),
+                })
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+        Ok(Self {
+            endpoints,
+            active: 0,
+            last_switch: None,
+            last_probe: Instant::now(),
+            accept_encoding: network.accept_encoding,
+        })
+    }
+
+    pub fn active(&self) -> &Endpoint {
+        &self.endpoints[self.active]
+    }
+
+    /// Host of the active endpoint
+    pub fn active_host(&self) -> &str {
+        url_authority(&self.active().url)
+    }
+
+    /// Request statistics of every endpoint, identified by host as URLs may contain API keys
+    pub fn stats(&self) -> Vec<EndpointStats> {
+        self.endpoints
+            .iter()
+            .enumerate()
+            .map(|(i, endpoint)| {
+                let stats = endpoint.provider.stats();
+                EndpointStats {
+                    host: url_authority(&endpoint.url).to_string(),
+                    active: i == self.active,
+                    latency: stats.histogram,
+                    errors: stats.errors,
+                    compressed: self.accept_encoding == AcceptEncoding::Gzip,
+                    transfer: stats.transfer,
+                    capabilities: endpoint.capabilities.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Measures the latest block number request on every inactive endpoint with budget left at
+    /// once, at most once every [`PROBE_INTERVAL`]. A slow endpoint holds up the probe no longer
+    /// than the request timeout.
+    pub async fn probe(&mut self) {
+        if self.endpoints.len() < 2 || self.last_probe.elapsed() < PROBE_INTERVAL {
+            return;
+        }
+        self.last_probe = Instant::now();
+        let probes = self
+            .endpoints
+            .iter()
+            .enumerate()
+            .filter(|(i, endpoint)| *i != self.active && !endpoint.is_exhausted())
+            .map(|(_, endpoint)| endpoint.provider.call(|provider| provider.get_block_number()));
+        // the outcomes are recorded in the stats of each endpoint
+        join_all(probes).await;
+        self.reselect();
+    }
+
+    /// Probes the optional RPC methods of every endpoint with budget left, all at once. The
+    /// probe doesn't borrow the selector, so it can run off the poll loop, and its outcome is
+    /// applied with [`Self::set_capabilities`].
+    pub fn probe_capabilities(&self) -> impl Future<Output = Vec<Capabilities>> + Send + 'static {
+        let providers = self
+            .endpoints
+            .iter()
+            .map(|endpoint| (!endpoint.is_exhausted()).then(|| endpoint.provider.clone()))
+            .collect::<Vec<_>>();
+        async move {
+            join_all(providers.iter().map(|provider| async move {
+                match provider {
+                    Some(provider) => capabilities::probe_all(provider, &Capability::ALL).await,
+                    None => Capabilities::new(),
+                }
+            }))
+            .await
+        }
+    }
+
+    /// Sets the capabilities probed of each endpoint, returning those each endpoint lacks by host
+    pub fn set_capabilities(
+        &mut self,
+        probed: Vec<Capabilities>,
+    ) -> Vec<(String, Vec<Capability>)> {
+        let mut lacking = Vec::new();
+        for (endpoint, capabilities) in self.endpoints.iter_mut().zip(probed) {
+            endpoint.capabilities.extend(capabilities);
+            let unsupported = Capability::ALL
+                .into_iter()
+                .filter(|&capability| !endpoint.supports(capability))
+                .collect::<Vec<_>>();
+            if !unsupported.is_empty() {
+                lacking.push((url_authority(&endpoint.url).to_string(), unsupported));
+            }
+        }
+        lacking
+    }
+
+    /// Switches to the best endpoint, right away when the active one ran out of budget
+    pub fn reselect(&mut self) {
+        let exhausted = self.active().is_exhausted();
+        if !exhausted && self.last_switch.is_some_and(|at| at.elapsed() < MIN_DWELL) {
+            return;
+        }
+        let Some((best, endpoint)) = self
+            .endpoints
+            .iter()
+            .enumerate()
+            .filter(|(_, endpoint)| endpoint.latency().is_some() && !endpoint.is_exhausted())
+            .min_by(|(_, a), (_, b)| a.score().total_cmp(&b.score()))
+        else {
+            return;
+        };
+        let better = endpoint.score() < self.active().score() * (1.0 - HYSTERESIS);
+        if best != self.active && (exhausted || better) {
+            info!("switching endpoint from {} to {}", self.active().url, endpoint.url);
+            self.active = best;
+            self.last_switch = Some(Instant::now());
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+
+/// A small arithmetic expression over named variables, e.g. `gps / 4` or `(tps * 21000) + 1`.
+///
+/// Supports `+ - * /`, unary minus, parentheses, numeric literals and identifiers, which are
+/// resolved at evaluation time.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Expr {
+    source: String,
+    node: Node,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Number(f64),
+    Var(String),
+    Neg(Box<Node>),
+    Binary(Box<Node>, Op, Box<Node>),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Expr {
+    pub fn parse(source: &str) -> eyre::Result<Self> {
+        let mut parser = Parser { chars: source.chars().collect(), pos: 0 };
+        let node = parser.expr()?;
+        parser.skip_whitespace();
+        if parser.pos < parser.chars.len() {
+            eyre::bail!(
+                "unexpected '{}' at {} in `{source}`",
+                parser.chars[parser.pos],
+                parser.pos
+            );
+        }
+        Ok(Self { source: source.to_string(), node })
+    }
+
+    /// Evaluates the expression, returning `None` if it references an unknown variable or
+    /// divides by zero
+    pub fn eval(&self, vars: &impl Fn(&str) -> Option<f64>) -> Option<f64> {
+        self.node.eval(vars)
+    }
+
+    /// Names of the variables the expression references
+    pub fn vars(&self) -> Vec<&str> {
+        let mut vars = Vec::new();
+        self.node.vars(&mut vars);
+        vars
+    }
+}
+
+impl TryFrom<String> for Expr {
+    type Error = eyre::Report;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::parse(&source)
+    }
+}
+
+impl From<Expr> for String {
+    fn from(expr: Expr) -> Self {
+        expr.source
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Node {
+    fn eval(&self, vars: &impl Fn(&str) -> Option<f64>) -> Option<f64> {
+        match self {
+            Node::Number(n) => Some(*n),
+            Node::Var(name) => vars(name),
+            Node::Neg(node) => node.eval(vars).map(|v| -v),
+            Node::Binary(lhs, op, rhs) => {
+                let (lhs, rhs) = (lhs.eval(vars)?, rhs.eval(vars)?);
+                match op {
+                    Op::Add => Some(lhs + rhs),
+                    Op::Sub => Some(lhs - rhs),
+                    Op::Mul => Some(lhs * rhs),
+                    Op::Div => (rhs != 0.0).then(|| lhs / rhs),
+                }
+            }
+        }
+    }
+
+    fn vars<'a>(&'a self, vars: &mut Vec<&'a str>) {
+        match self {
+            Node::Number(_) => {}
+            Node::Var(name) => vars.push(name),
+            Node::Neg(node) => node.vars(vars),
+            Node::Binary(lhs, _, rhs) => {
+                lhs.vars(vars);
+                rhs.vars(vars);
+            }
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn expr(&mut self) -> eyre::Result<Node> {
+        let mut node = self.term()?;
+        while let Some(op) = self.eat_op(&['+', '-']) {
+            node = Node::Binary(Box::new(node), op, Box::new(self.term()?));
+        }
+        Ok(node)
+    }
+
+    fn term(&mut self) -> eyre::Result<Node> {
+        let mut node = self.factor()?;
+        while let Some(op) = self.eat_op(&['*', '/']) {
+            node = Node::Binary(Box::new(node), op, Box::new(self.factor()?));
+        }
+        Ok(node)
+    }
+
+    fn factor(&mut self) -> eyre::Result<Node> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                Ok(Node::Neg(Box::new(self.factor()?)))
+            }
+            Some('(') => {
+                self.pos += 1;
+                let node = self.expr()?;
+                self.skip_whitespace();
+                if self.peek() != Some(')') {
+                    eyre::bail!("expected ')' at {}", self.pos);
+                }
+                self.pos += 1;
+                Ok(node)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let literal = self.take_while(|c| c.is_ascii_digit() || c == '.' || c == '_');
+                Ok(Node::Number(literal.replace('_', "").parse()?))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                Ok(Node::Var(self.take_while(|c| c.is_alphanumeric() || c == '_')))
+            }
+            Some(c) => eyre::bail!("unexpected '{c}' at {}", self.pos),
+            None => eyre::bail!("unexpected end of expression"),
+        }
+    }
+
+    fn eat_op(&mut self, ops: &[char]) -> Option<Op> {
+        self.skip_whitespace();
+        let op = match self.peek().filter(|c| ops.contains(c))? {
+            '+' => Op::Add,
+            '-' => Op::Sub,
+            '*' => Op::Mul,
+            _ => Op::Div,
+        };
+        self.pos += 1;
+        Some(op)
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(&predicate) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str) -> Option<f64> {
+        let vars = |name: &str| match name {
+            "tps" => Some(10.0),
+            "gps" => Some(400.0),
+            "zero" => Some(0.0),
+            _ => None,
+        };
+        Expr::parse(source).unwrap().eval(&vars)
+    }
+
+    #[test]
+    fn follows_precedence_and_parentheses() {
+        assert_eq!(eval("1 + 2 * 3"), Some(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Some(9.0));
+        assert_eq!(eval("10 - 4 - 3"), Some(3.0));
+        assert_eq!(eval("-(2 + 3) * -2"), Some(10.0));
+        assert_eq!(eval("1_000 / 8"), Some(125.0));
+    }
+
+    #[test]
+    fn resolves_variables() {
+        assert_eq!(eval("gps / 4"), Some(100.0));
+        assert_eq!(eval("(tps * 21000) + 1"), Some(210_001.0));
+        assert_eq!(eval("unknown * 2"), None);
+        assert_eq!(Expr::parse("tps + gps * tps").unwrap().vars(), ["tps", "gps", "tps"]);
+    }
+
+    #[test]
+    fn division_by_zero_has_no_value() {
+        assert_eq!(eval("tps / zero"), None);
+        assert_eq!(eval("1 / (tps - 10)"), None);
+        assert_eq!(eval("zero / tps"), Some(0.0));
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for source in ["", "1 +", "(1 + 2", "1 + 2)", "tps $ 2", "1..2", "2 tps"] {
+            assert!(Expr::parse(source).is_err(), "`{source}` should not parse");
+        }
+    }
+
+    #[test]
+    fn round_trips_through_serde() {
+        let expr: Expr = serde_json::from_str("\"gps / 4\"").unwrap();
+        assert_eq!(expr.to_string(), "gps / 4");
+        assert_eq!(serde_json::to_string(&expr).unwrap(), "\"gps / 4\"");
+        assert!(serde_json::from_str::<Expr>("\"gps /\"").is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use crate::block_metrics::median_priority_fee;
+use crate::types::{EventKind, FeeKind, GasToken};
+use alloy_rpc_types::Block;
+use std::collections::VecDeque;
+
+/// Number of preceding blocks whose median fee is used as the baseline
+const BASELINE_BLOCKS: usize = 20;
+/// A fee at least this many times the baseline is considered a spike
+const SPIKE_RATIO: f64 = 2.0;
+
+/// Detects sudden base fee and priority fee spikes relative to the median of recent blocks.
+///
+/// Spikes are edge triggered: a sustained spike is only reported once, when fees first cross
+/// the threshold.
+#[derive(Default)]
+pub struct FeeSpikeDetector {
+    token: GasToken,
+    last_block: Option<u64>,
+    base_fee: FeeSeries,
+    priority_fee: FeeSeries,
+}
+
+impl FeeSpikeDetector {
+    pub fn new(token: GasToken) -> Self {
+        Self { token, ..Default::default() }
+    }
+
+    /// Observes a block, returning any spikes it starts. Blocks must be observed in order;
+    /// already observed blocks are ignored.
+    pub fn observe(&mut self, block: &Block) -> Vec<EventKind> {
+        let Some(bn) = block.header.number else {
+            return vec![];
+        };
+        if self.last_block.is_some_and(|last| bn <= last) {
+            return vec![];
+        }
+        self.last_block = Some(bn);
+
+        let priority_fee = median_priority_fee(block);
+
+        [
+            (FeeKind::Base, self.base_fee.observe(block.header.base_fee_per_gas)),
+            (FeeKind::Priority, self.priority_fee.observe(priority_fee)),
+        ]
+        .into_iter()
+        .filter_map(|(fee, spike)| {
+            let token = self.token.clone();
+            spike.map(|(from, to)| EventKind::FeeSpike { fee, from, to, token })
+        })
+        .collect()
+    }
+}
+
+#[derive(Default)]
+struct FeeSeries {
+    recent: VecDeque<u128>,
+    spiking: bool,
+}
+
+impl FeeSeries {
+    /// Records a fee, returning the baseline and the fee if it starts a spike
+    fn observe(&mut self, fee: Option<u128>) -> Option<(u128, u128)> {
+        let fee = fee?;
+        let baseline = self.baseline();
+        if self.recent.len() == BASELINE_BLOCKS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(fee);
+
+        let baseline = baseline.filter(|&baseline| baseline > 0)?;
+        let spiking = fee as f64 >= baseline as f64 * SPIKE_RATIO;
+        let started = spiking && !self.spiking;
+        self.spiking = spiking;
+        started.then_some((baseline, fee))
+    }
+
+    fn baseline(&self) -> Option<u128> {
+        if self.recent.len() < BASELINE_BLOCKS {
+            return None;
+        }
+        let mut fees = self.recent.iter().copied().collect::<Vec<_>>();
+        fees.sort_unstable();
+        Some(fees[fees.len() / 2])
+    }
+}
@@ -0,0 +1,179 @@
+use crate::alert_sinks::AlertRouter;
+use crate::alerts::Alert;
+use crate::networks::{url_authority, Network};
+use crate::signals::{self, Signal, FLUSH_TIMEOUT};
+use crate::types::{BlockMessage, NetworkInfo, NetworkMetrics};
+use futures::{SinkExt, StreamExt};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio::time;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{accept_async, connect_async};
+use tracing::{info, warn};
+
+/// Number of messages buffered per watcher before a slow one starts skipping
+const FEED_CAPACITY: usize = 256;
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Serves the messages of the local streamers and of any federated collectors to any number of
+/// watchers over WebSocket, one JSON encoded [`BlockMessage`] per text frame. Watchers are first
+/// sent every known network. On a shutdown signal the queued messages are served and their alerts
+/// delivered before returning.
+pub async fn serve(
+    listen: &str,
+    networks: &[Network],
+    alert_router: AlertRouter,
+    mut rx: Receiver<BlockMessage>,
+    mut signals: Receiver<Signal>,
+) -> eyre::Result<()> {
+    let listener = TcpListener::bind(listen).await?;
+    let (feed, _) = broadcast::channel(FEED_CAPACITY);
+    let local = networks.iter().map(|n| n.label.clone()).collect::<Vec<_>>();
+    let announcements: Arc<Mutex<Vec<NetworkInfo>>> =
+        Arc::new(Mutex::new(networks.iter().map(NetworkInfo::from).collect()));
+
+    let (feed_tx, known) = (feed.clone(), announcements.clone());
+    let mut accepting: JoinHandle<eyre::Result<()>> = tokio::spawn(async move {
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let announcements = known.lock().unwrap().clone();
+            let feed = feed.subscribe();
+            tokio::spawn(async move {
+                info!("watcher {peer} connected");
+                match forward(stream, announcements, feed).await {
+                    Ok(()) => info!("watcher {peer} disconnected"),
+                    Err(err) => info!("watcher {peer} disconnected: {err}"),
+                }
+            });
+        }
+    });
+
+    let mut collector =
+        Collector { local, announcements, latest: BTreeMap::new(), alert_router, feed: feed_tx };
+    loop {
+        tokio::select! {
+            accepted = &mut accepting => return accepted?,
+            message = rx.recv() => match message {
+                Some(message) => collector.handle(message),
+                None => {
+                    accepting.abort();
+                    return Ok(());
+                }
+            },
+            Some(signal) = signals.recv() => match signal {
+                Signal::Shutdown => {
+                    // the streamers have stopped, what they sent last is still queued
+                    while let Ok(message) = rx.try_recv() {
+                        collector.handle(message);
+                    }
+                    accepting.abort();
+                    collector.alert_router.flush(FLUSH_TIMEOUT).await;
+                    return Ok(());
+                }
+                Signal::Reload => {
+                    if let Err(err) = collector.alert_router.reload() {
+                        warn!("keeping the alert sinks: {err}");
+                    }
+                }
+                Signal::DumpMetrics => {
+                    signals::log_metrics(collector.latest.values().map(|m| &**m));
+                }
+            }
+        }
+    }
+}
+
+/// State of the collector serving the feed
+struct Collector {
+    /// Labels of the networks polled by this collector rather than federated
+    local: Vec<String>,
+    announcements: Arc<Mutex<Vec<NetworkInfo>>>,
+    /// The newest metrics of every network, for metric dumps
+    latest: BTreeMap<String, Box<NetworkMetrics>>,
+    alert_router: AlertRouter,
+    feed: broadcast::Sender<BlockMessage>,
+}
+
+impl Collector {
+    /// Delivers the alerts of the local networks and serves the message to the watchers
+    fn handle(&mut self, message: BlockMessage) {
+        match &message {
+            // federated collectors deliver their own alerts
+            BlockMessage::Event(event) if self.local.contains(&event.network) => {
+                self.alert_router.route(&Alert::from_event(event));
+            }
+            BlockMessage::UpdateNetwork(metrics) => {
+                self.latest.insert(metrics.network.clone(), metrics.clone());
+            }
+            BlockMessage::AddNetwork(network) => {
+                let mut known = self.announcements.lock().unwrap();
+                if known.iter().all(|n| n.label != network.label) {
+                    known.push(network.clone());
+                }
+            }
+            BlockMessage::RemoveNetwork(label)
+            | BlockMessage::RenameNetwork { from: label, .. } => {
+                self.announcements.lock().unwrap().retain(|n| n.label != *label);
+                self.latest.remove(label);
+            }
+            _ => {}
+        }
+        // no watchers connected is not an error
+        let _ = self.feed.send(message);
+    }
+}
+
+async fn forward(
+    stream: TcpStream,
+    announcements: Vec<NetworkInfo>,
+    mut feed: broadcast::Receiver<BlockMessage>,
+) -> eyre::Result<()> {
+    let mut ws = accept_async(stream).await?;
+    for message in announcements.into_iter().map(BlockMessage::AddNetwork) {
+        ws.send(Message::Text(serde_json::to_string(&message)?)).await?;
+    }
+    loop {
+        match feed.recv().await {
+            Ok(message) => ws.send(Message::Text(serde_json::to_string(&message)?)).await?,
+            // metrics are periodically resent, skipping some is harmless
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return Ok(()),
+        }
+    }
+}
+
+/// Forwards the messages of a remote collector to the provided channel, reconnecting whenever
+/// the connection drops. Networks are attributed to the collector unless it federates them
+/// from another one.
+pub async fn watch(url: String, tx: Sender<BlockMessage>) -> eyre::Result<()> {
+    let source = url_authority(&url).to_string();
+    loop {
+        match connect_async(url.as_str()).await {
+            Ok((mut ws, _)) => {
+                info!("connected to collector {url}");
+                while let Some(Ok(message)) = ws.next().await {
+                    let Message::Text(text) = message else { continue };
+                    let mut message = match serde_json::from_str(&text) {
+                        Ok(message) => message,
+                        Err(err) => {
+                            warn!("skipping malformed message from {url}: {err}");
+                            continue;
+                        }
+                    };
+                    if let BlockMessage::AddNetwork(network) = &mut message {
+                        network.source.get_or_insert_with(|| source.clone());
+                    }
+                    tx.send(message).await?;
+                }
+                warn!("lost connection to collector {url}");
+            }
+            Err(err) => warn!("failed to connect to collector {url}: {err}"),
+        }
+        time::sleep(RECONNECT_INTERVAL).await;
+    }
+}
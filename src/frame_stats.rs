@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Number of frames the mean frame time is taken over
+const FRAMES: usize = 40;
+/// Minimum time the widgets stay degraded, as leaving them out makes frames cheap right away
+const MIN_DEGRADED: Duration = Duration::from_secs(10);
+
+/// Time spent on one frame of the UI
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameTimes {
+    /// Waiting for the app state while messages are applied to it
+    pub lock_wait: Duration,
+    /// Building the widgets and writing the changed cells to the terminal
+    pub draw: Duration,
+    /// Applying the messages received since the previous frame
+    pub update: Duration,
+}
+
+impl FrameTimes {
+    pub fn total(&self) -> Duration {
+        self.lock_wait + self.draw + self.update
+    }
+}
+
+/// Frame times of the render loop measured against a budget. Expensive widgets are degraded
+/// while the mean frame time exceeds the budget, and restored once it falls below half of it after
+/// at least [`MIN_DEGRADED`]. Each switch starts over with [`FRAMES`] new frames.
+#[derive(Debug, Default)]
+pub struct FrameStats {
+    /// Mean frame time allowed, zero never degrading
+    budget: Duration,
+    frames: VecDeque<FrameTimes>,
+    /// Time spent applying messages since the last frame
+    pending_update: Duration,
+    /// Since when the expensive widgets have been left out
+    degraded_since: Option<Instant>,
+}
+
+impl FrameStats {
+    pub fn new(budget: Duration) -> Self {
+        Self { budget, ..Default::default() }
+    }
+
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Whether expensive widgets should be left out to keep up with the budget
+    pub fn degraded(&self) -> bool {
+        self.degraded_since.is_some()
+    }
+
+    /// Adds time spent applying messages, counted towards the next frame
+    pub fn add_update(&mut self, elapsed: Duration) {
+        self.pending_update += elapsed;
+    }
+
+    /// Records a drawn frame and degrades or restores the expensive widgets
+    pub fn record(&mut self, lock_wait: Duration, draw: Duration) {
+        self.record_at(lock_wait, draw, Instant::now());
+    }
+
+    fn record_at(&mut self, lock_wait: Duration, draw: Duration, now: Instant) {
+        let update = std::mem::take(&mut self.pending_update);
+        self.frames.push_back(FrameTimes { lock_wait, draw, update });
+        if self.frames.len() > FRAMES {
+            self.frames.pop_front();
+        }
+        if self.budget.is_zero() || self.frames.len() < FRAMES {
+            return;
+        }
+        let mean = self.mean().total();
+        match self.degraded_since {
+            None if mean > self.budget => {
+                info!(
+                    "mean frame time {mean:?} is over the {:?} budget, pausing charts",
+                    self.budget
+                );
+                self.degraded_since = Some(now);
+                self.frames.clear();
+            }
+            Some(since) if mean < self.budget / 2 && now - since >= MIN_DEGRADED => {
+                info!("mean frame time {mean:?} is back within budget, resuming charts");
+                self.degraded_since = None;
+                self.frames.clear();
+            }
+            _ => {}
+        }
+    }
+
+    /// Mean time of each phase over the recent frames
+    pub fn mean(&self) -> FrameTimes {
+        let frames = self.frames.len().max(1) as u32;
+        let sum = self.frames.iter().fold(FrameTimes::default(), |sum, frame| FrameTimes {
+            lock_wait: sum.lock_wait + frame.lock_wait,
+            draw: sum.draw + frame.draw,
+            update: sum.update + frame.update,
+        });
+        FrameTimes {
+            lock_wait: sum.lock_wait / frames,
+            draw: sum.draw / frames,
+            update: sum.update / frames,
+        }
+    }
+
+    /// Longest of the recent frames
+    pub fn max(&self) -> Duration {
+        self.frames.iter().map(FrameTimes::total).max().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUDGET: Duration = Duration::from_millis(20);
+
+    /// Records a full window of frames drawn in `draw` at `at`
+    fn frames(stats: &mut FrameStats, draw: Duration, at: Instant) {
+        for _ in 0..FRAMES {
+            stats.record_at(Duration::ZERO, draw, at);
+        }
+    }
+
+    #[test]
+    fn degrades_once_a_full_window_is_over_budget() {
+        let mut stats = FrameStats::new(BUDGET);
+        let now = Instant::now();
+        for _ in 1..FRAMES {
+            stats.record_at(Duration::ZERO, BUDGET * 2, now);
+        }
+        assert!(!stats.degraded());
+        stats.record_at(Duration::ZERO, BUDGET * 2, now);
+        assert!(stats.degraded());
+    }
+
+    #[test]
+    fn stays_degraded_for_a_minimum_time() {
+        let mut stats = FrameStats::new(BUDGET);
+        let now = Instant::now();
+        frames(&mut stats, BUDGET * 2, now);
+        assert!(stats.degraded());
+        // cheap frames right after degrading don't restore the widgets yet
+        frames(&mut stats, Duration::ZERO, now + MIN_DEGRADED / 2);
+        assert!(stats.degraded());
+        frames(&mut stats, Duration::ZERO, now + MIN_DEGRADED);
+        assert!(!stats.degraded());
+    }
+
+    #[test]
+    fn restores_only_below_half_the_budget() {
+        let mut stats = FrameStats::new(BUDGET);
+        let now = Instant::now();
+        frames(&mut stats, BUDGET * 2, now);
+        frames(&mut stats, BUDGET * 3 / 4, now + MIN_DEGRADED * 2);
+        assert!(stats.degraded());
+        frames(&mut stats, BUDGET / 4, now + MIN_DEGRADED * 2);
+        assert!(!stats.degraded());
+        // within budget but above half of it keeps the widgets
+        frames(&mut stats, BUDGET * 3 / 4, now + MIN_DEGRADED * 3);
+        assert!(!stats.degraded());
+    }
+
+    #[test]
+    fn never_degrades_without_a_budget() {
+        let mut stats = FrameStats::new(Duration::ZERO);
+        frames(&mut stats, Duration::from_secs(1), Instant::now());
+        assert!(!stats.degraded());
+    }
+}
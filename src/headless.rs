@@ -0,0 +1,149 @@
+use crate::alert_sinks::AlertRouter;
+use crate::alerts::Alert;
+use crate::signals::{self, Signal, FLUSH_TIMEOUT};
+use crate::types::{BlockMessage, NetworkMetrics};
+use crate::units::csv_field;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde_derive::Serialize;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc::Receiver;
+use tokio::time;
+use tracing::warn;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum OutputFormat {
+    /// One JSON object per line with all metrics of a network
+    Json,
+    /// The main rates of each network, with a header line
+    Csv,
+}
+
+/// A metrics update with the time it was written
+#[derive(Serialize)]
+struct Record<'a> {
+    at: DateTime<Utc>,
+    #[serde(flatten)]
+    metrics: &'a NetworkMetrics,
+}
+
+/// Writes the latest metrics of every network updated since the previous write each `interval`,
+/// to the file at `output` or stdout. Alerts are delivered by `alert_router` as there is no UI
+/// to list them. On a shutdown signal the queued messages are handled and the pending records and
+/// alerts flushed before returning.
+pub async fn run(
+    format: OutputFormat,
+    output: Option<String>,
+    interval: Duration,
+    mut alert_router: AlertRouter,
+    mut rx: Receiver<BlockMessage>,
+    mut signals: Receiver<Signal>,
+) -> eyre::Result<()> {
+    let (mut out, new_file): (Box<dyn Write + Send>, bool) = match &output {
+        Some(path) => {
+            if let Some(dir) = Path::new(path).parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let new_file = fs::metadata(path).map_or(true, |metadata| metadata.len() == 0);
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            (Box::new(BufWriter::new(file)), new_file)
+        }
+        None => (Box::new(io::stdout()), true),
+    };
+    // appending to an existing CSV file keeps its header
+    if matches!(format, OutputFormat::Csv) && new_file {
+        writeln!(out, "time,network,block,tps,gas_per_second,bytes_per_second,base_fees")?;
+    }
+
+    let mut updated = BTreeMap::new();
+    // the newest metrics of every network, for metric dumps
+    let mut latest = BTreeMap::new();
+    let mut ticks = time::interval(interval);
+    loop {
+        tokio::select! {
+            message = rx.recv() => match message {
+                Some(message) => handle(message, &mut latest, &mut updated, &mut alert_router),
+                None => return Ok(()),
+            },
+            _ = ticks.tick() => {
+                let at = Utc::now();
+                for metrics in std::mem::take(&mut updated).values() {
+                    write_record(&mut out, format, at, metrics)?;
+                }
+                out.flush()?;
+            }
+            Some(signal) = signals.recv() => match signal {
+                Signal::Shutdown => {
+                    // the streamers have stopped, what they sent last is still queued
+                    while let Ok(message) = rx.try_recv() {
+                        handle(message, &mut latest, &mut updated, &mut alert_router);
+                    }
+                    let at = Utc::now();
+                    for metrics in updated.values() {
+                        write_record(&mut out, format, at, metrics)?;
+                    }
+                    out.flush()?;
+                    alert_router.flush(FLUSH_TIMEOUT).await;
+                    return Ok(());
+                }
+                Signal::Reload => {
+                    if let Err(err) = alert_router.reload() {
+                        warn!("keeping the alert sinks: {err}");
+                    }
+                }
+                Signal::DumpMetrics => signals::log_metrics(latest.values().map(|m| &**m)),
+            }
+        }
+    }
+}
+
+/// Keeps the metrics of the message to be written and delivers its alerts
+fn handle(
+    message: BlockMessage,
+    latest: &mut BTreeMap<String, Box<NetworkMetrics>>,
+    updated: &mut BTreeMap<String, Box<NetworkMetrics>>,
+    alert_router: &mut AlertRouter,
+) {
+    match message {
+        BlockMessage::UpdateNetwork(metrics) => {
+            latest.insert(metrics.network.clone(), metrics.clone());
+            updated.insert(metrics.network.clone(), metrics);
+        }
+        BlockMessage::RemoveNetwork(label) | BlockMessage::RenameNetwork { from: label, .. } => {
+            latest.remove(&label);
+            updated.remove(&label);
+        }
+        BlockMessage::Event(event) => alert_router.route(&Alert::from_event(&event)),
+        _ => {}
+    }
+}
+
+fn write_record(
+    out: &mut impl Write,
+    format: OutputFormat,
+    at: DateTime<Utc>,
+    metrics: &NetworkMetrics,
+) -> eyre::Result<()> {
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer(&mut *out, &Record { at, metrics })?;
+            writeln!(out)?;
+        }
+        OutputFormat::Csv => writeln!(
+            out,
+            "{},{},{},{:.2},{:.0},{:.0},{:.0}",
+            at.to_rfc3339(),
+            csv_field(&metrics.network),
+            metrics.block,
+            metrics.tps,
+            metrics.gps,
+            metrics.dps,
+            metrics.base_fees,
+        )?,
+    }
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use std::collections::VecDeque;
+
+/// Fixed-capacity ring buffer of metric samples, oldest first
+#[derive(Clone, Debug)]
+pub struct History {
+    samples: VecDeque<f64>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self { samples: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    pub fn push(&mut self, sample: f64) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Returns the most recent `n` samples as integers for use in a sparkline. Sparklines are
+    /// scaled relative to their maximum, so samples are only multiplied to keep two decimals.
+    pub fn sparkline(&self, n: usize) -> Vec<u64> {
+        let skip = self.samples.len().saturating_sub(n);
+        self.samples.iter().skip(skip).map(|&sample| (sample * 100.0) as u64).collect()
+    }
+}
@@ -0,0 +1,185 @@
+use crate::networks::url_authority;
+use crate::rpc_pool::{MeteredProvider, RpcPool};
+use crate::types::InboxQueue;
+use alloy_primitives::{address, keccak256, Address, Bytes, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types::{BlockNumberOrTag, Filter, Log};
+use chrono::Utc;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+/// L1 blocks searched for the oldest pending message on the first check, about a day
+const LOOKBACK_BLOCKS: u64 = 7200;
+/// Newest L1 blocks of the previous check searched again, in case a reorg replaced them
+const RESCANNED_BLOCKS: u64 = 12;
+/// `L1Block` predeploy of OP Stack chains, holding the L1 origin of the latest L2 block
+const L1_BLOCK: Address = address!("4200000000000000000000000000000000000015");
+
+/// L1 contracts through which messages can be force-included, bypassing the sequencer
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InboxContracts {
+    /// Delayed inbox of Arbitrum and Orbit chains
+    Arbitrum { bridge: Address, sequencer_inbox: Address },
+    /// Deposits through the `OptimismPortal` of OP Stack chains
+    OpStack { portal: Address },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InboxConfig {
+    /// RPC endpoint of the L1 the inbox contracts are deployed on
+    pub l1_http: String,
+    #[serde(flatten)]
+    pub contracts: InboxContracts,
+}
+
+/// Watches the messages queued in a network's L1 inbox that the sequencer hasn't included yet.
+/// Only the L1 blocks produced since the previous check are searched for new messages.
+pub struct InboxMonitor {
+    l1: MeteredProvider,
+    l1_host: String,
+    contracts: InboxContracts,
+    /// Newest L1 block searched for messages
+    scanned_to: Option<u64>,
+    /// Messages found that may still be queued: the L1 block of each by message index on
+    /// Arbitrum, the number of deposits by L1 block on OP Stack
+    messages: BTreeMap<u64, u64>,
+    /// The L1 block of the oldest queued message and its timestamp
+    oldest: Option<(u64, u64)>,
+}
+
+impl InboxMonitor {
+    pub fn new(config: &InboxConfig, rpc_pool: &RpcPool) -> eyre::Result<Self> {
+        Ok(Self {
+            l1: rpc_pool.endpoint(&config.l1_http)?,
+            l1_host: url_authority(&config.l1_http).to_string(),
+            contracts: config.contracts.clone(),
+            scanned_to: None,
+            messages: BTreeMap::new(),
+            oldest: None,
+        })
+    }
+
+    /// Provider of the L1 the inbox contracts are read on
+    pub fn l1(&self) -> &MeteredProvider {
+        &self.l1
+    }
+
+    pub fn l1_host(&self) -> &str {
+        &self.l1_host
+    }
+
+    /// Returns the queue depth and the age of the oldest queued message. `l2` is the network's
+    /// provider, needed to find the L1 origin of OP Stack chains.
+    pub async fn check(&mut self, l2: &MeteredProvider) -> eyre::Result<InboxQueue> {
+        match self.contracts {
+            InboxContracts::Arbitrum { bridge, sequencer_inbox } => {
+                let delivered = call_u64(&self.l1, bridge, "delayedMessageCount()", &[]).await?;
+                let read =
+                    call_u64(&self.l1, sequencer_inbox, "totalDelayedMessagesRead()", &[]).await?;
+                let head = self.l1.call(|l1| l1.get_block_number()).await?;
+                let filter = Filter::new().address(bridge).event_signature(keccak256(
+                    "MessageDelivered(uint256,bytes32,address,uint8,address,bytes32,uint256,uint64)",
+                ));
+                let (from, logs) =
+                    self.scan(filter, head.saturating_sub(LOOKBACK_BLOCKS), head).await?;
+                self.messages.retain(|_, block| *block < from);
+                for log in logs {
+                    let index = log.topics().get(1).and_then(|index| {
+                        u64::try_from(U256::from_be_slice(index.as_slice())).ok()
+                    });
+                    if let (Some(index), Some(block)) = (index, log.block_number) {
+                        self.messages.insert(index, block);
+                    }
+                }
+                // messages the sequencer inbox has read are no longer queued
+                self.messages = self.messages.split_off(&read);
+                let depth = delivered.saturating_sub(read);
+                // the oldest queued message is the first one the sequencer inbox hasn't read
+                let oldest = self.messages.get(&read).copied().filter(|_| depth > 0);
+                Ok(InboxQueue { depth, oldest_age: self.age(oldest).await? })
+            }
+            InboxContracts::OpStack { portal } => {
+                // deposits are included once the L1 origin of the network passes their L1 block
+                let origin = call_u64(l2, L1_BLOCK, "number()", &[]).await?;
+                let head = self.l1.call(|l1| l1.get_block_number()).await?;
+                let filter = Filter::new().address(portal).event_signature(keccak256(
+                    "TransactionDeposited(address,address,uint256,bytes)",
+                ));
+                let (from, logs) = self.scan(filter, origin + 1, head).await?;
+                self.messages.split_off(&from);
+                for block in logs.iter().filter_map(|log| log.block_number) {
+                    *self.messages.entry(block).or_default() += 1;
+                }
+                self.messages = self.messages.split_off(&(origin + 1));
+                let depth = self.messages.values().sum();
+                let oldest = self.messages.keys().next().copied();
+                Ok(InboxQueue { depth, oldest_age: self.age(oldest).await? })
+            }
+        }
+    }
+
+    /// Fetches the logs matching `filter` from the L1 blocks after the previous scan, or from
+    /// `first` on the first one, up to `head`. The newest blocks of the previous scan are searched
+    /// again in case a reorg replaced them. Returns the first block searched with the logs.
+    async fn scan(
+        &mut self,
+        filter: Filter,
+        first: u64,
+        head: u64,
+    ) -> eyre::Result<(u64, Vec<Log>)> {
+        let from = match self.scanned_to {
+            Some(scanned_to) => scanned_to.saturating_sub(RESCANNED_BLOCKS) + 1,
+            None => first,
+        };
+        if from > head {
+            return Ok((from, Vec::new()));
+        }
+        let filter = filter.from_block(from).to_block(head);
+        let logs = self.l1.call(|l1| l1.get_logs(&filter)).await?;
+        self.scanned_to = Some(head);
+        Ok((from, logs))
+    }
+
+    /// Seconds since the given L1 block was produced
+    async fn age(&mut self, block: Option<u64>) -> eyre::Result<Option<u64>> {
+        let Some(number) = block else {
+            return Ok(None);
+        };
+        let timestamp = match self.oldest {
+            Some((oldest, timestamp)) if oldest == number => timestamp,
+            _ => {
+                let tag = BlockNumberOrTag::Number(number);
+                let block = self.l1.call(|l1| l1.get_block_by_number(tag, false)).await?;
+                let Some(block) = block else {
+                    return Ok(None);
+                };
+                self.oldest = Some((number, block.header.timestamp));
+                block.header.timestamp
+            }
+        };
+        Ok(Some((Utc::now().timestamp() as u64).saturating_sub(timestamp)))
+    }
+}
+
+/// Calls a view function taking integer arguments and returning an integer that fits in 64 bits
+pub async fn call_u64(
+    provider: &MeteredProvider,
+    to: Address,
+    signature: &str,
+    args: &[u64],
+) -> eyre::Result<u64> {
+    let mut data = keccak256(signature)[..4].to_vec();
+    for arg in args {
+        data.extend_from_slice(&U256::from(*arg).to_be_bytes::<32>());
+    }
+    let data = Bytes::from(data);
+    let params = (json!({ "to": to, "data": data }), "latest");
+    let output: Bytes =
+        provider.call(|provider| provider.raw_request("eth_call".into(), params)).await?;
+    if output.len() != 32 {
+        eyre::bail!("unexpected `{signature}` output of {} bytes", output.len());
+    }
+    Ok(U256::from_be_slice(&output).try_into()?)
+}
@@ -0,0 +1,299 @@
+use crate::networks::Network;
+use crate::types::{BlockMessage, GasToken, NetworkInfo, NetworkMetrics, WindowMetrics};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+
+/// A weighted sum of the metrics of several networks, e.g. the throughput of the OP Stack
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IndexConfig {
+    pub label: String,
+    /// Weight of each network by label
+    #[serde(default)]
+    pub networks: BTreeMap<String, f64>,
+    /// Includes the mainnets built on this stack with a weight of 1, unless weighted in
+    /// `networks`
+    #[serde(default)]
+    pub stack: Option<String>,
+}
+
+impl IndexConfig {
+    pub fn validate(&self) -> eyre::Result<()> {
+        if self.networks.is_empty() && self.stack.is_none() {
+            eyre::bail!("index `{}` needs networks or a stack", self.label);
+        }
+        if let Some((network, _)) = self.networks.iter().find(|(_, weight)| !weight.is_finite()) {
+            eyre::bail!("index `{}` has an invalid weight for {network}", self.label);
+        }
+        Ok(())
+    }
+
+    /// Checks that the index doesn't share a label with a network and only weights networks that
+    /// exist
+    pub fn validate_labels(&self, networks: &[Network]) -> eyre::Result<()> {
+        if networks.iter().any(|network| network.label == self.label) {
+            eyre::bail!("index `{}` has the label of a network", self.label);
+        }
+        if let Some(label) =
+            self.networks.keys().find(|label| networks.iter().all(|n| n.label != **label))
+        {
+            eyre::bail!("index `{}` weights unknown network `{label}`", self.label);
+        }
+        Ok(())
+    }
+
+    fn weight(&self, network: &NetworkInfo) -> Option<f64> {
+        match self.networks.get(&network.label) {
+            Some(weight) => Some(*weight),
+            None if !network.testnet && network.stack.is_some() && network.stack == self.stack => {
+                Some(1.0)
+            }
+            None => None,
+        }
+    }
+}
+
+/// Networks and their latest metrics, by label
+#[derive(Default)]
+struct Members {
+    networks: HashMap<String, NetworkInfo>,
+    latest: HashMap<String, NetworkMetrics>,
+}
+
+/// Computes the configured indices from the metrics read from `rx`, announcing each index as a
+/// network and sending its metrics after those of its networks. Messages are passed on to the
+/// returned receiver, so indices are displayed and exported like any other network.
+pub fn aggregate(
+    indices: Vec<IndexConfig>,
+    networks: &[Network],
+    mut rx: Receiver<BlockMessage>,
+) -> Receiver<BlockMessage> {
+    if indices.is_empty() {
+        return rx;
+    }
+    let mut members = Members {
+        networks: networks.iter().map(|n| (n.label.clone(), NetworkInfo::from(n))).collect(),
+        ..Default::default()
+    };
+    let (tx, aggregated) = channel(8);
+    tokio::spawn(async move {
+        for index in &indices {
+            if announce(index, &tx).await.is_err() {
+                return;
+            }
+        }
+        while let Some(message) = rx.recv().await {
+            let updated = match &message {
+                BlockMessage::AddNetwork(network) => {
+                    members.networks.insert(network.label.clone(), network.clone());
+                    None
+                }
+                // the indices it was part of are recomputed without it
+                BlockMessage::RemoveNetwork(label)
+                | BlockMessage::RenameNetwork { from: label, .. } => {
+                    members.latest.remove(label);
+                    members.networks.remove(label)
+                }
+                BlockMessage::UpdateNetwork(metrics) => {
+                    members.latest.insert(metrics.network.clone(), (**metrics).clone());
+                    members.networks.get(&metrics.network).cloned()
+                }
+                _ => None,
+            };
+            if tx.send(message).await.is_err() {
+                return;
+            }
+            let Some(network) = updated else { continue };
+            for index in indices.iter().filter(|index| index.weight(&network).is_some()) {
+                let metrics = members.index_metrics(index);
+                if tx.send(BlockMessage::UpdateNetwork(Box::new(metrics))).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    aggregated
+}
+
+async fn announce(index: &IndexConfig, tx: &Sender<BlockMessage>) -> eyre::Result<()> {
+    let info = NetworkInfo {
+        name: index.label.clone(),
+        label: index.label.clone(),
+        adjusted: false,
+        stack: None,
+        group: None,
+        testnet: false,
+        source: None,
+        gas_token: Default::default(),
+        explorer: None,
+        time_zone: None,
+        index: true,
+    };
+    tx.send(BlockMessage::AddNetwork(info)).await?;
+    Ok(())
+}
+
+impl Members {
+    /// Weighted sums of the rates of the index's networks that reported metrics. Fees and value
+    /// are only summed while every network pays in ETH, the index's gas token.
+    fn index_metrics(&self, index: &IndexConfig) -> NetworkMetrics {
+        let mut metrics = NetworkMetrics { network: index.label.clone(), ..Default::default() };
+        let weighted = self
+            .latest
+            .iter()
+            .filter_map(|(label, latest)| {
+                let network = self.networks.get(label)?;
+                Some((index.weight(network)?, network, latest))
+            })
+            .collect::<Vec<_>>();
+        let eth = GasToken::default();
+        let same_token = weighted.iter().all(|(_, network, _)| network.gas_token == eth);
+        for (i, &(weight, _, latest)) in weighted.iter().enumerate() {
+            metrics.tps += weight * latest.tps;
+            metrics.gps += weight * latest.gps;
+            metrics.dps += weight * latest.dps;
+            metrics.da += weight * latest.da;
+            if same_token {
+                metrics.base_fees += weight * latest.base_fees;
+                if let Some(value) = latest.value {
+                    metrics.value = Some(metrics.value.unwrap_or_default() + weight * value);
+                }
+            }
+            if i == 0 {
+                metrics.windows = latest
+                    .windows
+                    .iter()
+                    .map(|window| WindowMetrics { seconds: window.seconds, ..Default::default() })
+                    .collect();
+            }
+            // windows are summed position by position, networks configured alike
+            for (sum, window) in metrics.windows.iter_mut().zip(&latest.windows) {
+                sum.tps += weight * window.tps;
+                sum.gps += weight * window.gps;
+                sum.dps += weight * window.dps;
+                sum.da += weight * window.da;
+            }
+        }
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(label: &str, stack: Option<&str>) -> NetworkInfo {
+        NetworkInfo {
+            name: label.to_string(),
+            label: label.to_string(),
+            adjusted: false,
+            stack: stack.map(str::to_string),
+            group: None,
+            testnet: false,
+            source: None,
+            gas_token: GasToken::default(),
+            explorer: None,
+            time_zone: None,
+            index: false,
+        }
+    }
+
+    fn metrics(label: &str, tps: f64, base_fees: f64) -> NetworkMetrics {
+        NetworkMetrics {
+            network: label.to_string(),
+            tps,
+            base_fees,
+            value: Some(1.0),
+            windows: vec![WindowMetrics { seconds: 60, tps, ..Default::default() }],
+            ..Default::default()
+        }
+    }
+
+    fn index(networks: &[(&str, f64)], stack: Option<&str>) -> IndexConfig {
+        IndexConfig {
+            label: "index".to_string(),
+            networks: networks.iter().map(|(label, weight)| (label.to_string(), *weight)).collect(),
+            stack: stack.map(str::to_string),
+        }
+    }
+
+    fn members(networks: &[(NetworkInfo, NetworkMetrics)]) -> Members {
+        Members {
+            networks: networks.iter().map(|(n, _)| (n.label.clone(), n.clone())).collect(),
+            latest: networks.iter().map(|(n, m)| (n.label.clone(), m.clone())).collect(),
+        }
+    }
+
+    #[test]
+    fn weights_listed_networks_and_mainnets_of_the_stack() {
+        let index = index(&[("base", 2.0)], Some("OP Stack"));
+        assert_eq!(index.weight(&network("base", Some("OP Stack"))), Some(2.0));
+        assert_eq!(index.weight(&network("zora", Some("OP Stack"))), Some(1.0));
+        assert_eq!(index.weight(&network("arbitrum", Some("Arbitrum"))), None);
+        let testnet = NetworkInfo { testnet: true, ..network("sepolia", Some("OP Stack")) };
+        assert_eq!(index.weight(&testnet), None);
+    }
+
+    #[test]
+    fn sums_weighted_metrics() {
+        let members = members(&[
+            (network("base", None), metrics("base", 10.0, 100.0)),
+            (network("zora", None), metrics("zora", 4.0, 50.0)),
+            (network("arbitrum", None), metrics("arbitrum", 7.0, 70.0)),
+        ]);
+        let metrics = members.index_metrics(&index(&[("base", 1.0), ("zora", 0.5)], None));
+        assert_eq!(metrics.network, "index");
+        assert_eq!(metrics.tps, 12.0);
+        assert_eq!(metrics.base_fees, 125.0);
+        assert_eq!(metrics.value, Some(1.5));
+        assert_eq!(metrics.windows[0].tps, 12.0);
+    }
+
+    #[test]
+    fn skips_fees_of_mixed_gas_tokens() {
+        let degen = GasToken { symbol: "DEGEN".to_string(), decimals: 18 };
+        let members = members(&[
+            (network("base", None), metrics("base", 10.0, 100.0)),
+            (
+                NetworkInfo { gas_token: degen, ..network("degen", None) },
+                metrics("degen", 2.0, 9.0),
+            ),
+        ]);
+        let metrics = members.index_metrics(&index(&[("base", 1.0), ("degen", 1.0)], None));
+        assert_eq!(metrics.tps, 12.0);
+        assert_eq!(metrics.base_fees, 0.0);
+        assert_eq!(metrics.value, None);
+    }
+
+    #[test]
+    fn rejects_labels_of_networks() {
+        let networks = [Network { label: "base".to_string(), ..Default::default() }];
+        assert!(index(&[("base", 1.0)], None).validate_labels(&networks).is_ok());
+        assert!(index(&[("bsae", 1.0)], None).validate_labels(&networks).is_err());
+        let clashing = IndexConfig { label: "base".to_string(), ..index(&[], Some("OP Stack")) };
+        assert!(clashing.validate_labels(&networks).is_err());
+    }
+
+    #[tokio::test]
+    async fn recomputes_when_a_member_is_removed() {
+        let networks = ["base", "zora"]
+            .map(|label| Network { label: label.to_string(), ..Default::default() });
+        let (tx, rx) = channel(16);
+        let mut rx = aggregate(vec![index(&[("base", 1.0), ("zora", 1.0)], None)], &networks, rx);
+        for (label, tps) in [("base", 10.0), ("zora", 4.0)] {
+            let update = BlockMessage::UpdateNetwork(Box::new(metrics(label, tps, 0.0)));
+            tx.send(update).await.unwrap();
+        }
+        tx.send(BlockMessage::RemoveNetwork("zora".to_string())).await.unwrap();
+        drop(tx);
+        let mut index_tps = Vec::new();
+        while let Some(message) = rx.recv().await {
+            if let BlockMessage::UpdateNetwork(metrics) = message {
+                if metrics.network == "index" {
+                    index_tps.push(metrics.tps);
+                }
+            }
+        }
+        assert_eq!(index_tps, [10.0, 14.0, 10.0]);
+    }
+}
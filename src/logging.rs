@@ -0,0 +1,78 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter};
+
+/// Number of log lines kept for the in-TUI tail view
+const TAIL_LINES: usize = 500;
+/// Number of daily log files kept
+const MAX_LOG_FILES: usize = 7;
+
+/// The most recent log lines, shared with the TUI
+#[derive(Clone, Default)]
+pub struct LogTail(Arc<Mutex<VecDeque<String>>>);
+
+impl LogTail {
+    /// Returns up to the last `n` lines, oldest first
+    pub fn last(&self, n: usize) -> Vec<String> {
+        let lines = self.0.lock().unwrap();
+        lines.iter().skip(lines.len().saturating_sub(n)).cloned().collect()
+    }
+
+    fn push(&self, line: String) {
+        let mut lines = self.0.lock().unwrap();
+        if lines.len() == TAIL_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
+
+impl io::Write for LogTail {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // the formatter writes one event per call
+        let line = String::from_utf8_lossy(buf);
+        self.push(line.trim_end().to_string());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogTail {
+    type Writer = LogTail;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// Writes logs at `level` and above to daily rotated files in `dir` and to the returned tail.
+/// `RUST_LOG` takes precedence over `level`. The guard must be held until exit so buffered lines
+/// are flushed.
+pub fn init(dir: &str, level: &str) -> eyre::Result<(WorkerGuard, LogTail)> {
+    fs::create_dir_all(dir)?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("rollup-tui")
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)?;
+    let (file, guard) = tracing_appender::non_blocking(appender);
+    let tail = LogTail::default();
+    let filter = EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new(level))?;
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(file).with_ansi(false))
+        .with(fmt::layer().with_writer(tail.clone()).with_ansi(false).with_target(false))
+        .try_init()?;
+    Ok((guard, tail))
+}
@@ -1,26 +1,332 @@
-use crate::block_streamer::BlockStreamer;
-use crate::networks::read_networks;
+use crate::address_book::AddressBook;
+use crate::alert_sinks::AlertRouter;
+use crate::effective_config::EffectiveConfig;
+use crate::headless::OutputFormat;
+use crate::networks::{read_networks, Network};
+use crate::rpc_pool::RpcPool;
+use crate::settings::Settings;
+use crate::signals::Signal;
+use crate::supervisor::{watch_config, Supervisor, SupervisorHandle};
+use crate::terminal::Palette;
+use crate::thresholds::Threshold;
 use crate::tui::tui;
+use crate::types::BlockMessage;
+use clap::{Parser, Subcommand};
+use std::net::IpAddr;
+use std::path::Path;
+use std::time::Duration;
 use tokio::spawn;
-use tokio::sync::mpsc::channel;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tracing::{error, info};
 
+mod address_book;
+mod alert_sinks;
+mod alerts;
+mod background;
+mod baseline;
 mod block_metrics;
 mod block_streamer;
+mod bridges;
+mod budget;
+mod builders;
+mod capabilities;
+mod color_rules;
+mod columns;
+mod console;
+mod custom_metrics;
+mod demo;
+mod effective_config;
+mod endpoints;
+mod expr;
+mod fee_spikes;
+mod feed;
+mod frame_stats;
+mod headless;
+mod history;
+mod inbox;
+mod indices;
+mod logging;
+mod mints;
+mod mirror;
+#[cfg(any(test, feature = "test-support"))]
+#[cfg_attr(not(test), allow(dead_code))]
+mod mock_rpc;
 mod networks;
+mod notes;
+mod prometheus;
+mod recording;
+mod rpc_cache;
+mod rpc_pool;
+mod selectors;
+mod settings;
+mod setup;
+mod signals;
+#[cfg(test)]
+mod streamer_tests;
+mod supervisor;
+mod terminal;
+mod thresholds;
+mod timeseries;
+mod tps_anomalies;
 mod tui;
 mod types;
+mod ui_state;
+mod units;
+mod verification;
+mod zk_batches;
+
+const NETWORKS_PATH: &str = "config/networks.json";
+
+#[derive(Parser)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Minimum level of the log file, e.g. `debug` or `rollups=trace`
+    #[arg(long, global = true, default_value = "info")]
+    log_level: String,
+    #[arg(long, global = true, default_value = "logs")]
+    log_dir: String,
+    /// Colors of the UI, `basic` uses the 16 standard colors of legacy consoles
+    #[arg(long, global = true, value_enum, default_value = "auto")]
+    palette: Palette,
+    /// Draw the UI inline, without the alternate screen and mouse capture, for nested tmux or
+    /// screen sessions
+    #[arg(long, global = true)]
+    passive: bool,
+    /// Serve the metrics of every network as Prometheus gauges on this port, under `/metrics`
+    #[arg(long, global = true)]
+    metrics_port: Option<u16>,
+    /// Address the Prometheus metrics are served on, e.g. `0.0.0.0` for all interfaces
+    #[arg(long, global = true, default_value = "127.0.0.1")]
+    metrics_address: IpAddr,
+    /// Replay a bundled recording of a few networks instead of polling RPCs, to try the UI
+    /// without network access
+    #[arg(long)]
+    demo: bool,
+    /// Record every block to this SQLite file, appending to an existing recording, for the
+    /// `replay` command
+    #[arg(long, global = true)]
+    record: Option<String>,
+    /// Share the session on a unix socket, `cache/session.sock` unless given, for other
+    /// terminals to mirror with `attach`
+    #[arg(long, global = true, num_args = 0..=1, default_missing_value = mirror::DEFAULT_SOCKET)]
+    share: Option<String>,
+    /// Write the effective configuration, with defaults and arguments applied, to this file
+    #[arg(long, global = true)]
+    dump_config: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Poll the configured networks without the UI and serve metrics to watchers over WebSocket
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:9100")]
+        listen: String,
+        /// Remote collectors whose networks are federated into the served feed
+        #[arg(long)]
+        upstream: Vec<String>,
+    },
+    /// Poll the configured networks without the UI and write their metrics to stdout or a file
+    Headless {
+        #[arg(long, value_enum, default_value = "json")]
+        format: OutputFormat,
+        /// File the metrics are appended to instead of stdout
+        #[arg(long)]
+        output: Option<String>,
+        /// Seconds between writes, each writing the networks updated since the previous one
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+    },
+    /// Display metrics from remote collectors instead of polling RPCs, e.g. ws://host:9100
+    Watch {
+        #[arg(required = true)]
+        urls: Vec<String>,
+    },
+    /// Mirror a session shared with `--share` read-only, e.g. from another tmux window
+    Attach {
+        #[arg(default_value = mirror::DEFAULT_SOCKET)]
+        socket: String,
+    },
+    /// Replay the blocks recorded with `--record` into the UI
+    Replay {
+        file: String,
+        /// How many times faster than recorded the blocks are replayed
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+    },
+}
 
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
-    let networks = read_networks("config/networks.json")?;
+    let args = Args::parse();
+    let (_log_guard, log_tail) = logging::init(&args.log_dir, &args.log_level)?;
+    let address_book = AddressBook::read("config/addressbook.json")?;
+    let alert_router = AlertRouter::read("config/alerts.json")?;
+    let settings = Settings::read("config/settings.json")?;
     let (tx, rx) = channel(8);
-    for network in &networks {
-        let mut streamer = BlockStreamer::new(network.clone(), tx.clone()).await?;
-        spawn(async move {
-            let _ = streamer.start().await;
-        });
+    let mut config = EffectiveConfig {
+        log_level: args.log_level.clone(),
+        log_dir: args.log_dir.clone(),
+        palette: args.palette,
+        passive: args.passive,
+        metrics_port: args.metrics_port,
+        metrics_address: args.metrics_port.map(|_| args.metrics_address),
+        record: args.record.clone(),
+        share: args.share.clone(),
+        windows: settings.windows.clone(),
+        indices: settings.indices.clone(),
+        color_rules: settings.color_rules.clone(),
+        time_zone: settings.time_zone,
+        frame_budget_ms: settings.frame_budget_ms,
+        ..Default::default()
+    };
+
+    if args.demo {
+        if args.command.is_some() {
+            eyre::bail!("--demo only replays into the UI and takes no command");
+        }
+        config.mode = "demo".to_string();
+        dump_config(&config, args.dump_config.clone())?;
+        demo::replay(&settings.windows, &tx)?;
+        let rx = pipeline(rx, &settings, &[], &[], &args).await?;
+        // the demo's made up alerts are kept off the configured sinks
+        tui(config, address_book, AlertRouter::default(), log_tail, None, rx).await?;
+        return Ok(());
     }
 
-    tui(networks, rx).await?;
+    if let Some(Command::Watch { urls }) = &args.command {
+        let urls = urls.clone();
+        config.mode = "watch".to_string();
+        config.collectors = urls.clone();
+        dump_config(&config, args.dump_config.clone())?;
+        watch_all(urls, &tx);
+        let rx = pipeline(rx, &settings, &[], &[], &args).await?;
+        // alerts are delivered by the collector
+        tui(config, address_book, AlertRouter::default(), log_tail, None, rx).await?;
+        return Ok(());
+    }
+
+    if let Some(Command::Attach { socket }) = &args.command {
+        config.mode = "attach".to_string();
+        dump_config(&config, args.dump_config.clone())?;
+        mirror::attach(socket, tx).await?;
+        // the shared session already ran its messages through the pipeline and delivers the alerts
+        tui(config, address_book, AlertRouter::default(), log_tail, None, rx).await?;
+        return Ok(());
+    }
+
+    if let Some(Command::Replay { file, speed }) = &args.command {
+        config.mode = "replay".to_string();
+        dump_config(&config, args.dump_config.clone())?;
+        recording::replay(file, *speed, &settings.windows, tx)?;
+        let rx = pipeline(rx, &settings, &[], alert_router.thresholds(), &args).await?;
+        // alerts were delivered while recording
+        tui(config, address_book, AlertRouter::default(), log_tail, None, rx).await?;
+        return Ok(());
+    }
+
+    // first run, without the UI there is no terminal to set up in and the default networks are
+    // monitored
+    let headless = matches!(args.command, Some(Command::Serve { .. } | Command::Headless { .. }));
+    if !headless && !Path::new(NETWORKS_PATH).exists() && !setup::run(NETWORKS_PATH, args.passive)?
+    {
+        return Ok(());
+    }
+    let networks = read_networks(NETWORKS_PATH)?;
+    for index in &settings.indices {
+        index.validate_labels(&networks)?;
+    }
+    config.networks = networks.clone();
+    config.alert_sinks = alert_router.sinks().to_vec();
+    config.thresholds = alert_router.thresholds().to_vec();
+    match &args.command {
+        Some(Command::Serve { listen, upstream }) => {
+            config.mode = "serve".to_string();
+            config.listen = Some(listen.clone());
+            config.collectors = upstream.clone();
+        }
+        Some(Command::Headless { .. }) => config.mode = "headless".to_string(),
+        _ => config.mode = "tui".to_string(),
+    }
+    dump_config(&config, args.dump_config.clone())?;
+    let rx = pipeline(rx, &settings, &networks, alert_router.thresholds(), &args).await?;
+    // watchers of a collector may record its blocks
+    let record_blocks =
+        args.record.is_some() || matches!(args.command, Some(Command::Serve { .. }));
+    let mut supervisor = Supervisor::new(RpcPool::new(&networks)?, settings.windows, tx.clone())
+        .with_block_records(record_blocks)
+        .with_address_book(address_book.clone());
+    supervisor.start(&networks).await?;
+    let (control_tx, control_rx) = channel(8);
+    let handle = SupervisorHandle { control: control_tx.clone(), networks: supervisor.networks() };
+    spawn(supervisor.run(control_rx));
+    spawn(watch_config(NETWORKS_PATH, control_tx));
+
+    match args.command {
+        Some(Command::Serve { listen, upstream }) => {
+            watch_all(upstream, &tx);
+            let signals = supervised_signals(handle)?;
+            feed::serve(&listen, &networks, alert_router, rx, signals).await?
+        }
+        Some(Command::Headless { format, output, interval }) => {
+            let interval = Duration::from_secs(interval.max(1));
+            let signals = supervised_signals(handle)?;
+            headless::run(format, output, interval, alert_router, rx, signals).await?
+        }
+        _ => tui(config, address_book, alert_router, log_tail, Some(handle), rx).await?,
+    }
+    Ok(())
+}
+
+/// Records the blocks of the streamers or collectors if enabled, adds the configured indices and
+/// the threshold alerts to their messages, exports them to Prometheus and shares them with other
+/// terminals if enabled
+async fn pipeline(
+    rx: Receiver<BlockMessage>,
+    settings: &Settings,
+    networks: &[Network],
+    thresholds: &[Threshold],
+    args: &Args,
+) -> eyre::Result<Receiver<BlockMessage>> {
+    let rx = match &args.record {
+        Some(path) => recording::record(path, networks, rx)?,
+        None => rx,
+    };
+    let rx = indices::aggregate(settings.indices.clone(), networks, rx);
+    let rx = thresholds::evaluate(thresholds.to_vec(), rx);
+    let rx = match args.metrics_port {
+        Some(port) => prometheus::serve(args.metrics_address, port, rx).await?,
+        None => rx,
+    };
+    match &args.share {
+        Some(path) => mirror::share(path, rx).await,
+        None => Ok(rx),
+    }
+}
+
+/// Signals of the modes without the UI, reloading the network config and stopping the streamers
+/// before they are handled
+fn supervised_signals(supervisor: SupervisorHandle) -> eyre::Result<Receiver<Signal>> {
+    let signals = signals::listen()?;
+    let signals = signals::reload_networks(NETWORKS_PATH, supervisor.control.clone(), signals);
+    Ok(signals::stop_streamers(supervisor, signals))
+}
+
+fn dump_config(config: &EffectiveConfig, path: Option<String>) -> eyre::Result<()> {
+    if path.is_some() {
+        let path = config.write(path)?;
+        info!("wrote effective configuration to {path}");
+    }
     Ok(())
 }
+
+fn watch_all(urls: Vec<String>, tx: &Sender<BlockMessage>) {
+    for url in urls {
+        let tx = tx.clone();
+        spawn(async move {
+            if let Err(err) = feed::watch(url.clone(), tx).await {
+                error!("stopped watching {url}: {err}");
+            }
+        });
+    }
+}
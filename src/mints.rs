@@ -0,0 +1,31 @@
+use alloy_primitives::{b256, B256};
+use alloy_rpc_types::{Filter, Log};
+
+/// `Transfer(address,address,uint256)`, shared by ERC-20 and ERC-721
+const TRANSFER: B256 = b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+/// `TransferSingle(address,address,address,uint256,uint256)`
+const TRANSFER_SINGLE: B256 =
+    b256!("c3d58168c5ae7397731d063d5bbf3d657854427343f4c083240f7aacaa2d0f62");
+/// `TransferBatch(address,address,address,uint256[],uint256[])`
+const TRANSFER_BATCH: B256 =
+    b256!("4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb");
+
+/// Log filters for ERC-721 and ERC-1155 transfers from the zero address within a block range.
+/// ERC-721 and ERC-1155 index the sender in different topics, so they need separate filters.
+pub fn mint_filters(from_block: u64, to_block: u64) -> [Filter; 2] {
+    let range = Filter::new().from_block(from_block).to_block(to_block);
+    [
+        range.clone().event_signature(TRANSFER).topic1(B256::ZERO),
+        range.event_signature(vec![TRANSFER_SINGLE, TRANSFER_BATCH]).topic2(B256::ZERO),
+    ]
+}
+
+/// Whether a log returned by [`mint_filters`] is an NFT mint. ERC-20 transfers share the
+/// `Transfer` signature but don't index the amount, so they have one less topic.
+pub fn is_nft_mint(log: &Log) -> bool {
+    match log.topics().first() {
+        Some(&topic) if topic == TRANSFER => log.topics().len() == 4,
+        Some(&topic) => topic == TRANSFER_SINGLE || topic == TRANSFER_BATCH,
+        None => false,
+    }
+}
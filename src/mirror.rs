@@ -0,0 +1,203 @@
+use crate::types::BlockMessage;
+use tokio::sync::mpsc::{Receiver, Sender};
+
+/// Default path of the socket a session is shared on
+pub const DEFAULT_SOCKET: &str = "cache/session.sock";
+
+#[cfg(unix)]
+mod unix {
+    use crate::types::{BlockMessage, NetworkEvent, NetworkHealth, NetworkInfo, NetworkMetrics};
+    use eyre::WrapErr;
+    use std::collections::{HashMap, VecDeque};
+    use std::fs;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+    use tokio::sync::broadcast::{self, error::RecvError};
+    use tokio::sync::mpsc::{channel, Receiver, Sender};
+    use tracing::{debug, info, warn};
+
+    /// Number of messages buffered per attached terminal before a slow one starts skipping
+    const MIRROR_CAPACITY: usize = 256;
+    /// Newest events sent to a terminal attaching, so its events and alerts views aren't empty
+    const REPLAYED_EVENTS: usize = 100;
+
+    /// What a terminal attaching starts from, later messages being forwarded as they come
+    #[derive(Default)]
+    struct Snapshot {
+        networks: Vec<NetworkInfo>,
+        metrics: HashMap<String, Box<NetworkMetrics>>,
+        health: HashMap<String, NetworkHealth>,
+        events: VecDeque<NetworkEvent>,
+    }
+
+    impl Snapshot {
+        fn observe(&mut self, message: &BlockMessage) {
+            match message {
+                BlockMessage::AddNetwork(network) => {
+                    self.networks.retain(|known| known.label != network.label);
+                    self.networks.push(network.clone());
+                }
+                BlockMessage::RemoveNetwork(label)
+                | BlockMessage::RenameNetwork { from: label, .. } => {
+                    self.networks.retain(|known| known.label != *label);
+                    self.metrics.remove(label);
+                    self.health.remove(label);
+                }
+                BlockMessage::UpdateNetwork(metrics) => {
+                    self.metrics.insert(metrics.network.clone(), metrics.clone());
+                }
+                BlockMessage::Health(health) => {
+                    self.health.insert(health.network.clone(), health.clone());
+                }
+                BlockMessage::Event(event) => {
+                    if self.events.len() == REPLAYED_EVENTS {
+                        self.events.pop_front();
+                    }
+                    self.events.push_back(event.clone());
+                }
+                BlockMessage::Block(_) | BlockMessage::Log(_) => {}
+            }
+        }
+
+        fn messages(&self) -> Vec<BlockMessage> {
+            let networks = self.networks.iter().cloned().map(BlockMessage::AddNetwork);
+            let metrics = self.metrics.values().cloned().map(BlockMessage::UpdateNetwork);
+            let health = self.health.values().cloned().map(BlockMessage::Health);
+            let events = self.events.iter().cloned().map(BlockMessage::Event);
+            networks.chain(metrics).chain(health).chain(events).collect()
+        }
+    }
+
+    pub async fn share(
+        path: &str,
+        mut rx: Receiver<BlockMessage>,
+    ) -> eyre::Result<Receiver<BlockMessage>> {
+        // a socket left behind by an instance that didn't exit cleanly is replaced
+        if UnixStream::connect(path).await.is_ok() {
+            eyre::bail!("another instance is already sharing its session on {path}");
+        }
+        let _ = fs::remove_file(path);
+        if let Some(dir) = Path::new(path).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        info!("sharing the session on {path}");
+        let snapshot = Arc::new(Mutex::new(Snapshot::default()));
+        let (mirror, _) = broadcast::channel(MIRROR_CAPACITY);
+        let (tx, forwarded) = channel(8);
+
+        let (observed, mirror_tx) = (snapshot.clone(), mirror.clone());
+        tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                {
+                    // terminals attaching in between get either the message or a snapshot with it
+                    let mut observed = observed.lock().unwrap();
+                    observed.observe(&message);
+                    // no terminals attached is not an error
+                    let _ = mirror_tx.send(message.clone());
+                }
+                if tx.send(message).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(err) => {
+                        warn!("failed to accept terminal attaching: {err}");
+                        continue;
+                    }
+                };
+                let (messages, feed) = {
+                    let snapshot = snapshot.lock().unwrap();
+                    (snapshot.messages(), mirror.subscribe())
+                };
+                tokio::spawn(async move {
+                    info!("terminal attached");
+                    match forward(stream, messages, feed).await {
+                        Ok(()) => info!("terminal detached"),
+                        Err(err) => debug!("terminal detached: {err}"),
+                    }
+                });
+            }
+        });
+        Ok(forwarded)
+    }
+
+    /// Writes one JSON encoded [`BlockMessage`] per line, the snapshot first
+    async fn forward(
+        mut stream: UnixStream,
+        snapshot: Vec<BlockMessage>,
+        mut feed: broadcast::Receiver<BlockMessage>,
+    ) -> eyre::Result<()> {
+        for message in snapshot {
+            write(&mut stream, &message).await?;
+        }
+        loop {
+            match feed.recv().await {
+                Ok(message) => write(&mut stream, &message).await?,
+                // metrics are resent with every poll, skipping some is harmless
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+
+    async fn write(stream: &mut UnixStream, message: &BlockMessage) -> eyre::Result<()> {
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+        stream.write_all(&line).await?;
+        Ok(())
+    }
+
+    pub async fn attach(path: &str, tx: Sender<BlockMessage>) -> eyre::Result<()> {
+        let stream = UnixStream::connect(path)
+            .await
+            .wrap_err_with(|| format!("no session is shared on {path}"))?;
+        let path = path.to_string();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stream).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                match serde_json::from_str(&line) {
+                    Ok(message) => {
+                        if tx.send(message).await.is_err() {
+                            return;
+                        }
+                    }
+                    Err(err) => warn!("skipping malformed message from {path}: {err}"),
+                }
+            }
+            warn!("the session shared on {path} ended");
+        });
+        Ok(())
+    }
+}
+
+/// Shares the session on a unix socket at `path`, so other terminals can mirror it read-only
+/// with `attach`. Messages are read from `rx` and passed on to the returned receiver unchanged.
+pub async fn share(path: &str, rx: Receiver<BlockMessage>) -> eyre::Result<Receiver<BlockMessage>> {
+    #[cfg(unix)]
+    return unix::share(path, rx).await;
+    #[cfg(not(unix))]
+    {
+        let _ = (path, rx);
+        eyre::bail!("sharing a session needs unix sockets");
+    }
+}
+
+/// Mirrors the session shared on the unix socket at `path` to `tx`, starting from its current
+/// state
+pub async fn attach(path: &str, tx: Sender<BlockMessage>) -> eyre::Result<()> {
+    #[cfg(unix)]
+    return unix::attach(path, tx).await;
+    #[cfg(not(unix))]
+    {
+        let _ = (path, tx);
+        eyre::bail!("attaching to a session needs unix sockets");
+    }
+}
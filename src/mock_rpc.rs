@@ -0,0 +1,319 @@
+use alloy_primitives::{keccak256, Address, Bloom, B256};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio::time;
+
+/// Gas a transaction of the scripted blocks uses, that of a plain transfer
+const GAS_PER_TX: u64 = 21_000;
+/// Age of the genesis block, leaving room for the blocks mined at the start of a script
+const GENESIS_AGE: u64 = 3600;
+
+/// A change to the scripted chain
+#[derive(Clone, Debug)]
+pub enum Step {
+    /// Produces blocks with `txs` transactions each, the newest stamped now and the others a block
+    /// time apart. Several blocks at once are delivered as a burst.
+    Mine { blocks: u64, txs: usize },
+    /// Moves the blocks `seconds` into the past, as if the chain had produced none for as long
+    Stall { seconds: u64 },
+    /// Replaces the newest `depth` blocks with a fork one block longer
+    Reorg { depth: u64 },
+    /// Waits before applying the next step
+    Wait(Duration),
+}
+
+#[derive(Clone, Debug)]
+struct MockBlock {
+    number: u64,
+    hash: B256,
+    parent_hash: B256,
+    timestamp: u64,
+    txs: usize,
+}
+
+impl MockBlock {
+    fn to_json(&self) -> Value {
+        let hashes = (0..self.txs)
+            .map(|i| keccak256([self.hash.as_slice(), &i.to_be_bytes()].concat()))
+            .collect::<Vec<_>>();
+        json!({
+            "hash": self.hash,
+            "parentHash": self.parent_hash,
+            "sha3Uncles": B256::ZERO,
+            "miner": Address::ZERO,
+            "stateRoot": B256::ZERO,
+            "transactionsRoot": B256::ZERO,
+            "receiptsRoot": B256::ZERO,
+            "logsBloom": Bloom::ZERO,
+            "difficulty": "0x0",
+            "number": quantity(self.number),
+            "gasLimit": quantity(30_000_000),
+            "gasUsed": quantity(self.txs as u64 * GAS_PER_TX),
+            "timestamp": quantity(self.timestamp),
+            "extraData": "0x",
+            "mixHash": B256::ZERO,
+            "nonce": "0x0000000000000000",
+            "baseFeePerGas": quantity(1_000_000_000),
+            "size": quantity(600 + self.txs as u64 * 110),
+            "uncles": [],
+            "transactions": hashes,
+        })
+    }
+}
+
+/// The canonical chain served, and the requests made so far
+struct Chain {
+    blocks: Vec<MockBlock>,
+    /// Seconds between the blocks of a burst
+    block_time: u64,
+    /// Number of reorgs, keeping the hashes of each fork apart
+    forks: u64,
+    requests: HashMap<String, usize>,
+}
+
+impl Chain {
+    fn new(block_time: u64) -> Self {
+        let timestamp = (chrono::Utc::now().timestamp() as u64).saturating_sub(GENESIS_AGE);
+        let genesis = MockBlock {
+            number: 0,
+            hash: keccak256("0:0"),
+            parent_hash: B256::ZERO,
+            timestamp,
+            txs: 0,
+        };
+        Self { blocks: vec![genesis], block_time, forks: 0, requests: HashMap::new() }
+    }
+
+    fn mine(&mut self, blocks: u64, txs: usize) {
+        let now = chrono::Utc::now().timestamp() as u64;
+        for i in (0..blocks).rev() {
+            let parent = self.head();
+            let (number, parent_hash, parent_timestamp) =
+                (parent.number + 1, parent.hash, parent.timestamp);
+            let hash = keccak256(format!("{}:{number}", self.forks));
+            let timestamp = now.saturating_sub(i * self.block_time).max(parent_timestamp);
+            self.blocks.push(MockBlock { number, hash, parent_hash, timestamp, txs });
+        }
+    }
+
+    fn apply(&mut self, step: &Step) {
+        match *step {
+            Step::Mine { blocks, txs } => self.mine(blocks, txs),
+            Step::Stall { seconds } => {
+                for block in &mut self.blocks {
+                    block.timestamp = block.timestamp.saturating_sub(seconds);
+                }
+            }
+            Step::Reorg { depth } => {
+                let txs = self.blocks.last().map_or(0, |block| block.txs);
+                let kept = self.blocks.len().saturating_sub(depth as usize).max(1);
+                self.blocks.truncate(kept);
+                self.forks += 1;
+                self.mine(depth + 1, txs);
+            }
+            Step::Wait(_) => {}
+        }
+    }
+
+    fn head(&self) -> &MockBlock {
+        self.blocks.last().expect("the chain starts with a genesis block")
+    }
+
+    fn call(&self, method: &str, params: &Value) -> Result<Value, String> {
+        match method {
+            "eth_chainId" => Ok(quantity(1337)),
+            "eth_blockNumber" => Ok(quantity(self.head().number)),
+            "eth_gasPrice" => Ok(quantity(1_000_000_000)),
+            "eth_getLogs" => Ok(json!([])),
+            "eth_getBlockByNumber" => {
+                let block = match params[0].as_str() {
+                    Some("latest" | "safe" | "finalized" | "pending") => Some(self.head()),
+                    Some("earliest") => self.blocks.first(),
+                    Some(number) => {
+                        let number = number.strip_prefix("0x").unwrap_or(number);
+                        let number = u64::from_str_radix(number, 16)
+                            .map_err(|_| format!("invalid block number {number}"))?;
+                        self.blocks.get(number as usize)
+                    }
+                    None => return Err("missing block number".to_string()),
+                };
+                Ok(block.map_or(Value::Null, MockBlock::to_json))
+            }
+            "eth_getBlockByHash" => {
+                let hash = params[0].as_str().unwrap_or_default();
+                let block = self.blocks.iter().find(|block| block.hash.to_string() == hash);
+                Ok(block.map_or(Value::Null, MockBlock::to_json))
+            }
+            _ => Err(format!("the method {method} does not exist/is not available")),
+        }
+    }
+}
+
+/// An in-process JSON-RPC server over HTTP serving a scripted chain, starting from a genesis
+/// block, to test the streamer end to end. Stalls, reorgs and bursts are scripted with [`Step`]s.
+pub struct MockRpc {
+    /// URL the server listens on, to configure as a network's `http`
+    pub url: String,
+    chain: Arc<Mutex<Chain>>,
+    server: JoinHandle<()>,
+}
+
+impl MockRpc {
+    /// Starts serving on a free local port, bursts of blocks being `block_time` seconds apart
+    pub async fn start(block_time: u64) -> eyre::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let chain = Arc::new(Mutex::new(Chain::new(block_time)));
+        let served = chain.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let chain = served.clone();
+                tokio::spawn(async move {
+                    // clients closing their connection is expected
+                    let _ = serve(stream, chain).await;
+                });
+            }
+        });
+        Ok(Self { url, chain, server })
+    }
+
+    /// Applies a step to the chain at once, ignoring waits
+    pub fn apply(&self, step: &Step) {
+        self.chain.lock().unwrap().apply(step);
+    }
+
+    /// Applies the steps in order in the background, waiting where the script says so
+    pub fn play(&self, script: Vec<Step>) -> JoinHandle<()> {
+        let chain = self.chain.clone();
+        tokio::spawn(async move {
+            for step in script {
+                match step {
+                    Step::Wait(duration) => time::sleep(duration).await,
+                    step => chain.lock().unwrap().apply(&step),
+                }
+            }
+        })
+    }
+
+    /// Number of the newest block
+    pub fn head(&self) -> u64 {
+        self.chain.lock().unwrap().head().number
+    }
+
+    /// Number of requests made for the method so far
+    pub fn requests(&self, method: &str) -> usize {
+        self.chain.lock().unwrap().requests.get(method).copied().unwrap_or_default()
+    }
+}
+
+impl Drop for MockRpc {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+/// Answers the JSON-RPC requests, single or batched, of one keep-alive connection
+async fn serve(stream: TcpStream, chain: Arc<Mutex<Chain>>) -> eyre::Result<()> {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(());
+        }
+        let mut length = 0;
+        loop {
+            line.clear();
+            reader.read_line(&mut line).await?;
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    length = value.trim().parse()?;
+                }
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).await?;
+        let response = match serde_json::from_slice(&body)? {
+            Value::Array(calls) => calls.iter().map(|call| respond(&chain, call)).collect(),
+            call => respond(&chain, &call),
+        };
+        let body = serde_json::to_vec(&response)?;
+        let head = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n",
+            body.len()
+        );
+        let stream = reader.get_mut();
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(&body).await?;
+    }
+}
+
+fn respond(chain: &Mutex<Chain>, call: &Value) -> Value {
+    let method = call["method"].as_str().unwrap_or_default();
+    let mut chain = chain.lock().unwrap();
+    *chain.requests.entry(method.to_string()).or_default() += 1;
+    match chain.call(method, &call["params"]) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": call["id"], "result": result }),
+        Err(message) => json!({
+            "jsonrpc": "2.0",
+            "id": call["id"],
+            "error": { "code": -32601, "message": message },
+        }),
+    }
+}
+
+fn quantity(value: u64) -> Value {
+    Value::String(format!("{value:#x}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn call(url: &str, method: &str, params: Value) -> Value {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = reqwest::Client::new().post(url).json(&body).send().await.unwrap();
+        response.json().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn serves_the_scripted_chain() {
+        let rpc = MockRpc::start(2).await.unwrap();
+        rpc.apply(&Step::Mine { blocks: 3, txs: 4 });
+        let head = call(&rpc.url, "eth_blockNumber", json!([])).await;
+        assert_eq!(head["result"], "0x3");
+        let block = call(&rpc.url, "eth_getBlockByNumber", json!(["0x3", false])).await;
+        assert_eq!(block["result"]["transactions"].as_array().unwrap().len(), 4);
+        let parent = call(&rpc.url, "eth_getBlockByNumber", json!(["0x2", false])).await;
+        assert_eq!(block["result"]["parentHash"], parent["result"]["hash"]);
+        assert_eq!(rpc.requests("eth_getBlockByNumber"), 2);
+    }
+
+    #[tokio::test]
+    async fn reorgs_replace_the_newest_blocks() {
+        let rpc = MockRpc::start(2).await.unwrap();
+        rpc.apply(&Step::Mine { blocks: 5, txs: 1 });
+        let before = call(&rpc.url, "eth_getBlockByNumber", json!(["0x4", false])).await;
+        rpc.apply(&Step::Reorg { depth: 2 });
+        assert_eq!(rpc.head(), 6);
+        let after = call(&rpc.url, "eth_getBlockByNumber", json!(["0x4", false])).await;
+        assert_ne!(before["result"]["hash"], after["result"]["hash"]);
+        let by_hash = call(&rpc.url, "eth_getBlockByHash", json!([after["result"]["hash"], false]));
+        assert_eq!(by_hash.await["result"]["number"], "0x4");
+    }
+
+    #[tokio::test]
+    async fn unknown_methods_fail() {
+        let rpc = MockRpc::start(2).await.unwrap();
+        let response = call(&rpc.url, "debug_traceBlock", json!([])).await;
+        assert_eq!(response["error"]["code"], -32601);
+    }
+}
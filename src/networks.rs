@@ -1,17 +1,238 @@
-use serde_derive::Deserialize;
+use crate::bridges::BridgeContract;
+use crate::custom_metrics::CustomMetric;
+use crate::expr::Expr;
+use crate::inbox::InboxConfig;
+use crate::settings::TimeZone;
+use crate::types::{GasToken, NetworkInfo, NetworkMetrics};
+use crate::verification::VerifierConfig;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, ErrorKind};
 
-#[derive(Clone, Debug, Deserialize)]
+/// Networks compiled into the binary, all monitored unless there is a local config
+pub const DEFAULT_NETWORKS: &str = include_str!("../config/default-networks.json");
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Network {
     pub name: String,
     pub label: String,
     pub http: String,
+    /// Additional RPC endpoints, the best performing endpoint is used
+    #[serde(default)]
+    pub alternate_http: Vec<String>,
+    /// WebSocket endpoint to subscribe to new heads on, blocks are still fetched over HTTP
+    #[serde(default)]
+    pub ws: Option<String>,
+    /// Rollup stack the network is built on, e.g. `OP Stack`, used to cluster shares of total
+    #[serde(default)]
+    pub stack: Option<String>,
+    /// Group the network is listed under when the table is grouped, e.g. `Superchain`, its
+    /// stack unless configured
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Testnets are set apart in the table and left out of totals
+    #[serde(default)]
+    pub testnet: bool,
+    #[serde(default)]
+    pub overrides: MetricOverrides,
+    /// Fetch full transaction objects instead of hashes, enabling per-transaction metrics at
+    /// the cost of larger responses
+    #[serde(default)]
+    pub full_transactions: bool,
+    /// Bridge contracts whose deposit and withdrawal events are counted
+    #[serde(default)]
+    pub bridges: Vec<BridgeContract>,
+    /// Scan logs for ERC-721/1155 mints
+    #[serde(default)]
+    pub track_mints: bool,
+    /// Attribute Ethereum blocks to MEV-Boost builders by their extra data and fee recipient
+    #[serde(default)]
+    pub track_builders: bool,
+    /// Track how fresh the network's data on L1 is from the node's `safe` head, the newest block
+    /// derived from batches posted to L1
+    #[serde(default)]
+    pub track_batches: bool,
+    /// Track how far the L1 commits, proofs and executions of a ZK Stack chain trail its newest
+    /// batch through the `zks_` RPC methods
+    #[serde(default)]
+    pub track_zk_batches: bool,
+    /// Z-score of the TPS against its rolling session mean beyond which a spike or drop is
+    /// flagged, 3 unless configured
+    #[serde(default)]
+    pub tps_z_score: Option<f64>,
+    /// Token fees are paid in, ETH unless configured
+    #[serde(default)]
+    pub gas_token: GasToken,
+    /// Block explorer transactions are opened in, e.g. `https://basescan.org`, linking to
+    /// `<explorer>/tx/<hash>`
+    #[serde(default)]
+    pub explorer: Option<String>,
+    /// Time zone the network's block timestamps are shown in, overriding the settings
+    #[serde(default)]
+    pub time_zone: Option<TimeZone>,
+    /// Numbers read from chain-specific JSON-RPC methods, each shown as a column
+    #[serde(default)]
+    pub custom_metrics: Vec<CustomMetric>,
+    /// L1 rollup contract of a ZK rollup to read the verified batch or block from
+    #[serde(default)]
+    pub verifier: Option<VerifierConfig>,
+    /// L1 inbox to watch for messages awaiting force-inclusion
+    #[serde(default)]
+    pub inbox: Option<InboxConfig>,
+    /// Compute rates up to the newest block rather than the current time, steadying the readings
+    /// of endpoints that deliver several blocks at once
+    #[serde(default)]
+    pub smooth_bursts: bool,
+    /// Fraction of transaction data posted to L1 after batch compression, estimating the DA
+    /// usage of chains without blobs. 0.4 unless configured.
+    #[serde(default)]
+    pub da_ratio: Option<f64>,
+    /// Encoding RPC responses are requested in, gzip unless configured. `identity` saves
+    /// decompressing on constrained CPUs at the cost of bandwidth.
+    #[serde(default)]
+    pub accept_encoding: AcceptEncoding,
+    /// Requests per second allowed by the RPC host, shared by all networks on that host
+    #[serde(default)]
+    pub requests_per_second: Option<u32>,
+    /// Requests per hour allowed by each endpoint, e.g. a free tier quota. Polling slows down
+    /// and skips backfill to stay within it.
+    #[serde(default)]
+    pub requests_per_hour: Option<u32>,
+    /// Milliseconds between polls, 750 unless configured. Adaptive polling starts from it.
+    #[serde(default)]
+    pub poll_interval_ms: Option<u64>,
+    /// Poll about once per block time as learned from the blocks, backing off exponentially
+    /// after failed or rate limited polls
+    #[serde(default)]
+    pub adaptive_polling: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AcceptEncoding {
+    #[default]
+    Gzip,
+    Identity,
 }
 
+impl Network {
+    /// All RPC endpoints, the primary one first
+    pub fn endpoints(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.http.as_str()).chain(self.alternate_http.iter().map(String::as_str))
+    }
+
+    /// Endpoints of the L1 the inbox and verifier contracts are read on
+    pub fn l1_endpoints(&self) -> impl Iterator<Item = &str> {
+        let inbox = self.inbox.as_ref().map(|inbox| inbox.l1_http.as_str());
+        let verifier = self.verifier.as_ref().map(|verifier| verifier.l1_http.as_str());
+        inbox.into_iter().chain(verifier)
+    }
+}
+
+impl From<&Network> for NetworkInfo {
+    fn from(network: &Network) -> Self {
+        Self {
+            name: network.name.clone(),
+            label: network.label.clone(),
+            adjusted: !network.overrides.is_empty(),
+            stack: network.stack.clone(),
+            group: network.group.clone(),
+            testnet: network.testnet,
+            source: None,
+            gas_token: network.gas_token.clone(),
+            explorer: network.explorer.clone(),
+            time_zone: network.time_zone,
+            index: false,
+        }
+    }
+}
+
+/// Optional per-network formulas applied to the raw metrics before they are reported, so chains
+/// with different gas or data semantics can be normalized for comparison, e.g.
+/// `{ "gps": "gps / 4" }`. Expressions may reference `tps`, `gps` and `dps`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct MetricOverrides {
+    pub tps: Option<Expr>,
+    pub gps: Option<Expr>,
+    pub dps: Option<Expr>,
+}
+
+impl MetricOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.tps.is_none() && self.gps.is_none() && self.dps.is_none()
+    }
+
+    /// Applies the formulas to the rates of the main window and of every other window
+    pub fn apply(&self, metrics: &mut NetworkMetrics) {
+        self.apply_to(&mut metrics.tps, &mut metrics.gps, &mut metrics.dps);
+        for window in &mut metrics.windows {
+            self.apply_to(&mut window.tps, &mut window.gps, &mut window.dps);
+        }
+    }
+
+    fn apply_to(&self, tps: &mut f64, gps: &mut f64, dps: &mut f64) {
+        let (raw_tps, raw_gps, raw_dps) = (*tps, *gps, *dps);
+        let vars = |name: &str| match name {
+            "tps" => Some(raw_tps),
+            "gps" => Some(raw_gps),
+            "dps" => Some(raw_dps),
+            _ => None,
+        };
+        let targets = [(&self.tps, tps), (&self.gps, gps), (&self.dps, dps)];
+        for (expr, target) in targets {
+            if let Some(expr) = expr {
+                // a division by zero, e.g. by an idle network's tps, counts as no activity
+                *target = expr.eval(&vars).unwrap_or(0.0);
+            }
+        }
+    }
+
+    fn validate(&self) -> eyre::Result<()> {
+        for expr in [&self.tps, &self.gps, &self.dps].into_iter().flatten() {
+            if expr.vars().iter().any(|name| !matches!(*name, "tps" | "gps" | "dps")) {
+                eyre::bail!("unknown variable in override `{expr}` (expected tps, gps or dps)");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the networks of the local config at `file_path`, or the default networks if there is
+/// none. A local entry with the name of a default network only needs the fields it changes, e.g.
+/// another RPC endpoint, the others being taken from the default.
 pub fn read_networks(file_path: &str) -> eyre::Result<Vec<Network>> {
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
-    let networks: Vec<Network> = serde_json::from_reader(reader)?;
+    let defaults: Vec<Value> = serde_json::from_str(DEFAULT_NETWORKS)?;
+    let networks: Vec<Value> = match File::open(file_path) {
+        Ok(file) => serde_json::from_reader(BufReader::new(file))?,
+        Err(err) if err.kind() == ErrorKind::NotFound => defaults.clone(),
+        Err(err) => return Err(err.into()),
+    };
+    let networks = networks
+        .into_iter()
+        .map(|entry| {
+            let default = defaults.iter().find(|network| network["name"] == entry["name"]);
+            match (default, entry) {
+                (Some(Value::Object(default)), Value::Object(fields)) => {
+                    let mut network = default.clone();
+                    network.extend(fields);
+                    serde_json::from_value(Value::Object(network))
+                }
+                (_, entry) => serde_json::from_value(entry),
+            }
+        })
+        .collect::<Result<Vec<Network>, _>>()?;
+    for network in &networks {
+        network.overrides.validate()?;
+        for metric in &network.custom_metrics {
+            metric.validate()?;
+        }
+    }
     Ok(networks)
 }
+
+/// Host and port of a url, e.g. `ws://10.0.0.1:9100/` becomes `10.0.0.1:9100`
+pub fn url_authority(url: &str) -> &str {
+    let address = url.split_once("://").map_or(url, |(_, rest)| rest);
+    address.split('/').next().unwrap_or(address)
+}
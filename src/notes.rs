@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind};
+use std::path::Path;
+
+const PATH: &str = "config/notes.json";
+
+/// Free-text notes attached to networks, keyed by network label
+#[derive(Debug, Default)]
+pub struct Notes {
+    notes: BTreeMap<String, String>,
+    /// Whether the notes file could be read, so unreadable notes are never overwritten
+    loaded: bool,
+}
+
+impl Notes {
+    /// Reads the saved notes, a missing file meaning no notes
+    pub fn read() -> eyre::Result<Self> {
+        let notes = match File::open(PATH) {
+            Ok(file) => serde_json::from_reader(BufReader::new(file))?,
+            Err(err) if err.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self { notes, loaded: true })
+    }
+
+    pub fn get(&self, network: &str) -> Option<&str> {
+        self.notes.get(network).map(String::as_str)
+    }
+
+    /// Sets the note of a network, an empty note removing it, and saves the notes
+    pub fn set(&mut self, network: &str, note: &str) -> eyre::Result<()> {
+        if !self.loaded {
+            eyre::bail!("{PATH} couldn't be read, not overwriting it");
+        }
+        if note.is_empty() {
+            self.notes.remove(network);
+        } else {
+            self.notes.insert(network.to_string(), note.to_string());
+        }
+        self.save()
+    }
+
+    /// Moves the note of a renamed network to its new label, saving the notes if it had one
+    pub fn rename(&mut self, from: &str, to: &str) -> eyre::Result<()> {
+        if !self.loaded {
+            eyre::bail!("{PATH} couldn't be read, not overwriting it");
+        }
+        let Some(note) = self.notes.remove(from) else {
+            return Ok(());
+        };
+        self.notes.insert(to.to_string(), note);
+        self.save()
+    }
+
+    fn save(&self) -> eyre::Result<()> {
+        if let Some(dir) = Path::new(PATH).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        serde_json::to_writer_pretty(BufWriter::new(File::create(PATH)?), &self.notes)?;
+        Ok(())
+    }
+}
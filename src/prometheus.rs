@@ -0,0 +1,169 @@
+use crate::types::{BlockMessage, EndpointStats, NetworkMetrics, LATENCY_BUCKETS_MS};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{channel, Receiver};
+use tracing::{debug, info};
+
+/// Largest request read, scrapers only send a request line and a few headers
+const MAX_REQUEST: usize = 8192;
+
+/// A gauge exported per network: its name, help text and value
+type Gauge = (&'static str, &'static str, fn(&NetworkMetrics) -> f64);
+
+const GAUGES: &[Gauge] = &[
+    ("rollup_tps", "Transactions per second over the main window", |m| m.tps),
+    ("rollup_gas_per_second", "Gas used per second over the main window", |m| m.gps),
+    ("rollup_bytes_per_second", "Transaction bytes per second over the main window", |m| m.dps),
+    ("rollup_da_bytes_per_second", "Estimated data availability bytes per second", |m| m.da),
+    ("rollup_block_height", "Newest block polled", |m| m.block as f64),
+];
+
+/// Latest metrics and endpoint statistics, by network
+#[derive(Default)]
+struct Snapshot {
+    metrics: BTreeMap<String, NetworkMetrics>,
+    endpoints: BTreeMap<String, Vec<EndpointStats>>,
+}
+
+type Latest = Arc<Mutex<Snapshot>>;
+
+/// Serves the latest metrics of every network as Prometheus gauges on `port` of `address`, under
+/// `/metrics`, along with the latency histogram of each RPC endpoint.
+/// Messages are read from `rx` and passed on to the returned receiver unchanged, so the exporter
+/// runs alongside the UI or any other consumer.
+pub async fn serve(
+    address: IpAddr,
+    port: u16,
+    mut rx: Receiver<BlockMessage>,
+) -> eyre::Result<Receiver<BlockMessage>> {
+    let listener = TcpListener::bind((address, port)).await?;
+    info!("serving Prometheus metrics on {}", listener.local_addr()?);
+    let latest = Latest::default();
+    let (tx, forwarded) = channel(8);
+
+    let updated = latest.clone();
+    tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            match &message {
+                BlockMessage::UpdateNetwork(metrics) => {
+                    let mut updated = updated.lock().unwrap();
+                    updated.metrics.insert(metrics.network.clone(), (**metrics).clone());
+                }
+                BlockMessage::Health(health) => {
+                    let mut updated = updated.lock().unwrap();
+                    updated.endpoints.insert(health.network.clone(), health.endpoints.clone());
+                }
+                BlockMessage::RemoveNetwork(label)
+                | BlockMessage::RenameNetwork { from: label, .. } => {
+                    let mut updated = updated.lock().unwrap();
+                    updated.metrics.remove(label);
+                    updated.endpoints.remove(label);
+                }
+                _ => {}
+            }
+            if tx.send(message).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    debug!("failed to accept metrics scrape: {err}");
+                    continue;
+                }
+            };
+            let latest = latest.clone();
+            tokio::spawn(async move {
+                if let Err(err) = respond(stream, &latest).await {
+                    debug!("metrics scrape from {peer} failed: {err}");
+                }
+            });
+        }
+    });
+    Ok(forwarded)
+}
+
+async fn respond(mut stream: TcpStream, latest: &Latest) -> eyre::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+    let request_line = request.split(|b| *b == b'\n').next().unwrap_or_default();
+    let (status, body) = if request_line.starts_with(b"GET /metrics ") {
+        ("200 OK", render(&latest.lock().unwrap()))
+    } else {
+        ("404 Not Found", "Metrics are served under /metrics\n".to_string())
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// The text exposition format, one metric family after the other
+fn render(latest: &Snapshot) -> String {
+    let mut out = String::new();
+    for (name, help, value) in GAUGES {
+        let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} gauge");
+        for (network, metrics) in &latest.metrics {
+            let _ = writeln!(out, "{name}{{network=\"{}\"}} {}", escape(network), value(metrics));
+        }
+    }
+
+    let name = "rollup_rpc_latency_seconds";
+    let _ = writeln!(out, "# HELP {name} Latency of successful RPC requests per endpoint");
+    let _ = writeln!(out, "# TYPE {name} histogram");
+    for (network, endpoint) in endpoints(latest) {
+        let labels =
+            format!("network=\"{}\",endpoint=\"{}\"", escape(network), escape(&endpoint.host));
+        let histogram = &endpoint.latency;
+        let mut cumulative = 0;
+        for (i, bound) in LATENCY_BUCKETS_MS.iter().enumerate() {
+            cumulative += histogram.buckets.get(i).copied().unwrap_or_default();
+            let le = *bound as f64 / 1000.0;
+            let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{le}\"}} {cumulative}");
+        }
+        let _ = writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {}", histogram.count);
+        let _ = writeln!(out, "{name}_sum{{{labels}}} {}", histogram.sum_seconds);
+        let _ = writeln!(out, "{name}_count{{{labels}}} {}", histogram.count);
+    }
+
+    let name = "rollup_rpc_errors_total";
+    let _ = writeln!(out, "# HELP {name} Failed RPC requests per endpoint\n# TYPE {name} counter");
+    for (network, endpoint) in endpoints(latest) {
+        let (network, host) = (escape(network), escape(&endpoint.host));
+        let _ = writeln!(
+            out,
+            "{name}{{network=\"{network}\",endpoint=\"{host}\"}} {}",
+            endpoint.errors
+        );
+    }
+    out
+}
+
+fn endpoints(latest: &Snapshot) -> impl Iterator<Item = (&String, &EndpointStats)> {
+    latest
+        .endpoints
+        .iter()
+        .flat_map(|(network, endpoints)| endpoints.iter().map(move |e| (network, e)))
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,182 @@
+use crate::block_metrics::{BlockInfo, BlockMetricsBuffer};
+use crate::networks::Network;
+use crate::types::{BlockMessage, BlockRecord, NetworkInfo};
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tracing::{info, warn};
+
+/// Longest pause between replayed blocks, gaps between recording sessions being skipped
+const MAX_GAP: Duration = Duration::from_secs(60);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS networks (label TEXT PRIMARY KEY, info TEXT NOT NULL);
+    CREATE TABLE IF NOT EXISTS blocks (
+        network TEXT NOT NULL,
+        number INTEGER NOT NULL,
+        timestamp INTEGER NOT NULL,
+        txs INTEGER NOT NULL,
+        gas INTEGER NOT NULL,
+        size INTEGER,
+        PRIMARY KEY (network, number)
+    );
+    CREATE INDEX IF NOT EXISTS blocks_timestamp ON blocks (timestamp);
+";
+
+enum Row {
+    Network(NetworkInfo),
+    Block(BlockRecord),
+}
+
+/// Writes the networks and every block read from `rx` to the SQLite file at `path`, appending to
+/// an existing recording. Blocks replaced by a reorg are overwritten. Messages are passed on to the
+/// returned receiver unchanged.
+pub fn record(
+    path: &str,
+    networks: &[Network],
+    mut rx: Receiver<BlockMessage>,
+) -> eyre::Result<Receiver<BlockMessage>> {
+    let mut conn = Connection::open(path)?;
+    conn.execute_batch(SCHEMA)?;
+    info!("recording blocks to {path}");
+    let (rows, written) = mpsc::channel();
+    for network in networks {
+        let _ = rows.send(Row::Network(network.into()));
+    }
+    // SQLite blocks, the writes are batched on a thread of their own
+    let path = path.to_string();
+    thread::spawn(move || {
+        if let Err(err) = write(&mut conn, written) {
+            warn!("stopped recording to {path}: {err}");
+        }
+    });
+
+    let (tx, forwarded) = channel(8);
+    tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            let row = match &message {
+                BlockMessage::AddNetwork(network) if !network.index => {
+                    Some(Row::Network(network.clone()))
+                }
+                BlockMessage::Block(block) => Some(Row::Block(block.clone())),
+                _ => None,
+            };
+            // a failed recording doesn't stop the UI
+            if let Some(row) = row {
+                let _ = rows.send(row);
+            }
+            if tx.send(message).await.is_err() {
+                return;
+            }
+        }
+    });
+    Ok(forwarded)
+}
+
+/// Writes the rows received, those arriving together in a single transaction
+fn write(conn: &mut Connection, rows: mpsc::Receiver<Row>) -> eyre::Result<()> {
+    while let Ok(row) = rows.recv() {
+        let batch = conn.transaction()?;
+        for row in std::iter::once(row).chain(rows.try_iter()) {
+            match row {
+                Row::Network(network) => {
+                    batch.execute(
+                        "INSERT OR REPLACE INTO networks (label, info) VALUES (?1, ?2)",
+                        params![network.label, serde_json::to_string(&network)?],
+                    )?;
+                }
+                Row::Block(block) => {
+                    batch.execute(
+                        "INSERT OR REPLACE INTO blocks (network, number, timestamp, txs, gas, size)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        params![
+                            block.network,
+                            block.number,
+                            block.timestamp,
+                            block.txs,
+                            block.gas,
+                            block.size
+                        ],
+                    )?;
+                }
+            }
+        }
+        batch.commit()?;
+    }
+    Ok(())
+}
+
+/// Feeds the blocks recorded in the SQLite file at `path` to `tx` in the order they were
+/// produced, `speed` times faster than they were. The metrics are computed as of each block's
+/// timestamp, as if the networks were polled at the time.
+pub fn replay(
+    path: &str,
+    speed: f64,
+    windows: &[u64],
+    tx: Sender<BlockMessage>,
+) -> eyre::Result<()> {
+    if !(speed > 0.0 && speed.is_finite()) {
+        eyre::bail!("replay speed must be positive, got {speed}");
+    }
+    let conn = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let networks = conn
+        .prepare("SELECT info FROM networks ORDER BY label")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .map(|info| Ok(serde_json::from_str::<NetworkInfo>(&info?)?))
+        .collect::<eyre::Result<Vec<_>>>()?;
+    let (windows, path) = (windows.to_vec(), path.to_string());
+    thread::spawn(move || match play(&conn, networks, speed, &windows, &tx) {
+        Ok(blocks) => info!("replayed {blocks} blocks from {path}"),
+        Err(err) => warn!("replay of {path} stopped: {err}"),
+    });
+    Ok(())
+}
+
+fn play(
+    conn: &Connection,
+    networks: Vec<NetworkInfo>,
+    speed: f64,
+    windows: &[u64],
+    tx: &Sender<BlockMessage>,
+) -> eyre::Result<usize> {
+    let mut buffers = HashMap::new();
+    for network in networks {
+        buffers
+            .insert(network.label.clone(), BlockMetricsBuffer::new(network.label.clone(), windows));
+        tx.blocking_send(BlockMessage::AddNetwork(network))?;
+    }
+    let mut statement = conn.prepare(
+        "SELECT network, number, timestamp, txs, gas, size FROM blocks
+         ORDER BY timestamp, network, number",
+    )?;
+    let records = statement.query_map([], |row| {
+        Ok(BlockRecord {
+            network: row.get(0)?,
+            number: row.get(1)?,
+            timestamp: row.get(2)?,
+            txs: row.get(3)?,
+            gas: row.get(4)?,
+            size: row.get(5)?,
+        })
+    })?;
+
+    let (mut previous, mut replayed) = (None, 0);
+    for record in records {
+        let record = record?;
+        let Some(buffer) = buffers.get_mut(&record.network) else { continue };
+        if let Some(previous) = previous.filter(|&previous| record.timestamp > previous) {
+            let gap = Duration::from_secs(record.timestamp - previous).min(MAX_GAP);
+            thread::sleep(gap.div_f64(speed));
+        }
+        previous = Some(record.timestamp);
+        buffer.set_clock(record.timestamp);
+        buffer.add_block_info(BlockInfo::recorded(&record));
+        tx.blocking_send(BlockMessage::UpdateNetwork(Box::new(buffer.get_metrics())))?;
+        tx.blocking_send(BlockMessage::Block(record))?;
+        replayed += 1;
+    }
+    Ok(replayed)
+}
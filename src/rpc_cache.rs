@@ -0,0 +1,72 @@
+use alloy_rpc_types::Block;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex as AsyncMutex;
+
+/// How long a fetched head is shared before it is requested again
+const HEAD_TTL: Duration = Duration::from_millis(250);
+/// Number of most recent blocks kept per network
+const CACHED_BLOCKS: usize = 64;
+
+type BlockSlot = Arc<AsyncMutex<Option<Block>>>;
+
+/// Per-network cache that coalesces requests for the head and for blocks by number, so
+/// subsystems needing the same values in the same tick don't multiply RPC costs: the poll, its
+/// block fetches and the verifier's head. Concurrent requests for the same value wait for the one
+/// in flight. Requests for anything else, such as logs, the safe head or blocks by hash during a
+/// reorg, aren't cached. Clones share the cache.
+#[derive(Clone, Default)]
+pub struct RpcCache {
+    head: Arc<AsyncMutex<Option<(Instant, u64)>>>,
+    blocks: Arc<Mutex<BTreeMap<u64, BlockSlot>>>,
+}
+
+impl RpcCache {
+    /// Returns the latest block number, calling `fetch` unless a recent one is cached
+    pub async fn head<E, F>(&self, fetch: impl FnOnce() -> F) -> Result<u64, E>
+    where
+        F: Future<Output = Result<u64, E>>,
+    {
+        let mut head = self.head.lock().await;
+        if let Some((at, number)) = *head {
+            if at.elapsed() < HEAD_TTL {
+                return Ok(number);
+            }
+        }
+        let number = fetch().await?;
+        *head = Some((Instant::now(), number));
+        Ok(number)
+    }
+
+    /// Returns the block, calling `fetch` unless it is cached. Missing blocks aren't cached since
+    /// they may become available later.
+    pub async fn block<E, F>(
+        &self,
+        number: u64,
+        fetch: impl FnOnce() -> F,
+    ) -> Result<Option<Block>, E>
+    where
+        F: Future<Output = Result<Option<Block>, E>>,
+    {
+        let slot = {
+            let mut blocks = self.blocks.lock().unwrap();
+            let slot = blocks.entry(number).or_default().clone();
+            while blocks.len() > CACHED_BLOCKS {
+                blocks.pop_first();
+            }
+            slot
+        };
+        let mut block = slot.lock().await;
+        if block.is_none() {
+            *block = fetch().await?;
+        }
+        Ok(block.clone())
+    }
+
+    /// Drops the cached blocks from `number` on, after a reorg replaced them
+    pub fn invalidate_from(&self, number: u64) {
+        self.blocks.lock().unwrap().split_off(&number);
+    }
+}
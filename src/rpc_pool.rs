@@ -0,0 +1,317 @@
+use crate::budget::RequestBudget;
+use crate::networks::{url_authority, AcceptEncoding, Network};
+use crate::types::{LatencyHistogram, TransferStats};
+use alloy_provider::{ProviderBuilder, ReqwestProvider};
+use alloy_rpc_client::RpcClient;
+use alloy_rpc_types::{Block, BlockTransactionsKind};
+use alloy_transport_http::Http;
+use flate2::read::GzDecoder;
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use serde_derive::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::io::Read;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{self, Duration, Instant};
+
+/// Request rate per RPC host unless a network on that host configures one, low enough for the
+/// free tiers of public endpoints. A network polled every 750ms needs about 3.
+const DEFAULT_REQUESTS_PER_SECOND: u32 = 5;
+/// A request taking longer than this is abandoned and counts as failed
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Weight of the newest sample in the latency and error rate averages
+const SMOOTHING: f64 = 0.2;
+
+/// Paces the requests sent to one RPC host
+#[derive(Debug)]
+pub struct HostLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl HostLimiter {
+    fn new(requests_per_second: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / requests_per_second.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Waits until the next request to the host is allowed
+    pub async fn acquire(&self) {
+        let at = {
+            let mut next = self.next.lock().await;
+            let at = (*next).max(Instant::now());
+            *next = at + self.interval;
+            at
+        };
+        time::sleep_until(at).await;
+    }
+}
+
+/// Waits until the host allows the next request and charges it to the endpoint budget, if any
+async fn acquire(limiter: &HostLimiter, budget: Option<&RequestBudget>) {
+    limiter.acquire().await;
+    if let Some(budget) = budget {
+        budget.spend(1);
+    }
+}
+
+/// Outcomes of the requests sent to an endpoint
+#[derive(Clone, Debug, Default)]
+pub struct RequestStats {
+    /// Smoothed request latency in seconds, `None` until measured
+    pub latency: Option<f64>,
+    /// Smoothed fraction of failed requests
+    pub error_rate: f64,
+    pub histogram: LatencyHistogram,
+    pub errors: u64,
+    /// Sizes of the block responses, measured when they are requested gzip compressed
+    pub transfer: Option<TransferStats>,
+}
+
+impl RequestStats {
+    fn record(&mut self, elapsed: Duration, ok: bool) {
+        let smooth = |average: f64, sample: f64| average + SMOOTHING * (sample - average);
+        if ok {
+            let latency = elapsed.as_secs_f64();
+            self.latency = Some(self.latency.map_or(latency, |average| smooth(average, latency)));
+            self.histogram.record(latency);
+        } else {
+            self.errors += 1;
+        }
+        self.error_rate = smooth(self.error_rate, if ok { 0.0 } else { 1.0 });
+    }
+
+    fn record_transfer(&mut self, gzip: bool, received: usize, decompressed: usize) {
+        let transfer = self.transfer.get_or_insert_with(TransferStats::default);
+        transfer.gzip = gzip;
+        transfer.compressed_bytes += received as u64;
+        transfer.uncompressed_bytes += decompressed as u64;
+    }
+}
+
+/// Response to a JSON-RPC request
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<serde_json::Value>,
+}
+
+/// The provider of an endpoint, through which every request waits for its host's rate limit, is
+/// charged to the endpoint budget, fails after [`REQUEST_TIMEOUT`] and is recorded in the stats.
+/// Clones share the stats.
+#[derive(Clone)]
+pub struct MeteredProvider {
+    provider: ReqwestProvider,
+    url: String,
+    encoding: AcceptEncoding,
+    /// Client receiving responses as sent, to measure their size on the wire
+    transfer_client: reqwest::Client,
+    limiter: Arc<HostLimiter>,
+    budget: Option<Arc<RequestBudget>>,
+    stats: Arc<std::sync::Mutex<RequestStats>>,
+}
+
+impl MeteredProvider {
+    /// Sends the request made by `request` on the provider
+    pub async fn call<'a, T, E, F>(
+        &'a self,
+        request: impl FnOnce(&'a ReqwestProvider) -> F,
+    ) -> eyre::Result<T>
+    where
+        F: Future<Output = Result<T, E>>,
+        E: Display,
+    {
+        acquire(&self.limiter, self.budget.as_deref()).await;
+        let started = Instant::now();
+        let result = time::timeout(REQUEST_TIMEOUT, request(&self.provider)).await;
+        let (result, answered) = match result {
+            Ok(Ok(value)) => (Ok(value), true),
+            // the node answering with an error, e.g. for an unknown method, is still healthy
+            Ok(Err(err)) => {
+                let message = err.to_string();
+                let answered = message.contains("error response");
+                (Err(eyre::eyre!(message)), answered)
+            }
+            Err(_) => (Err(eyre::eyre!("no response within {REQUEST_TIMEOUT:?}")), false),
+        };
+        self.stats.lock().unwrap().record(started.elapsed(), answered);
+        result
+    }
+
+    /// Fetches a block by number like [`alloy_provider::Provider::get_block`], counting the bytes
+    /// of the response as received and decompressed in the stats
+    pub async fn get_block_counted(
+        &self,
+        number: u64,
+        kind: BlockTransactionsKind,
+    ) -> eyre::Result<Option<Block>> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_getBlockByNumber",
+            "params": [format!("{number:#x}"), kind == BlockTransactionsKind::Full],
+        });
+        self.call(|_| self.post_counted(&body)).await
+    }
+
+    async fn post_counted(&self, body: &serde_json::Value) -> eyre::Result<Option<Block>> {
+        let encoding = match self.encoding {
+            AcceptEncoding::Gzip => "gzip",
+            AcceptEncoding::Identity => "identity",
+        };
+        let response = self
+            .transfer_client
+            .post(&self.url)
+            .header(header::ACCEPT_ENCODING, encoding)
+            .json(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        let gzip =
+            response.headers().get(header::CONTENT_ENCODING).is_some_and(|value| value == "gzip");
+        let received = response.bytes().await?;
+        let body = if gzip {
+            let mut body = Vec::new();
+            GzDecoder::new(&received[..]).read_to_end(&mut body)?;
+            body
+        } else {
+            received.to_vec()
+        };
+        // an endpoint asked for uncompressed responses says nothing about its gzip support
+        if self.encoding == AcceptEncoding::Gzip {
+            self.stats.lock().unwrap().record_transfer(gzip, received.len(), body.len());
+        }
+        let response: RpcResponse<Block> = serde_json::from_slice(&body)?;
+        match response.error {
+            Some(error) => eyre::bail!("server returned an error response: {error}"),
+            None => Ok(response.result),
+        }
+    }
+
+    pub fn budget(&self) -> Option<&Arc<RequestBudget>> {
+        self.budget.as_ref()
+    }
+
+    pub fn stats(&self) -> RequestStats {
+        self.stats.lock().unwrap().clone()
+    }
+}
+
+/// Providers and rate limiters shared by networks, so per-host provider limits are respected
+/// across all streamers rather than per streamer. Networks with the same endpoint share a
+/// provider and thereby its connection pool.
+#[derive(Default)]
+pub struct RpcPool {
+    providers: HashMap<String, (ReqwestProvider, AcceptEncoding)>,
+    limiters: HashMap<String, Arc<HostLimiter>>,
+    /// Hourly request budgets by endpoint url
+    budgets: HashMap<String, Arc<RequestBudget>>,
+    transfer_client: reqwest::Client,
+}
+
+impl RpcPool {
+    pub fn new(networks: &[Network]) -> eyre::Result<Self> {
+        let mut rates: HashMap<&str, u32> = HashMap::new();
+        for network in networks {
+            for url in network.endpoints() {
+                let rate = rates.entry(url_authority(url)).or_insert(u32::MAX);
+                // the most conservative limit configured for a host wins
+                *rate = (*rate).min(network.requests_per_second.unwrap_or(u32::MAX));
+            }
+            for url in network.l1_endpoints() {
+                rates.entry(url_authority(url)).or_insert(u32::MAX);
+            }
+        }
+        let limiters = rates
+            .into_iter()
+            .map(|(host, rate)| {
+                let rate = if rate == u32::MAX { DEFAULT_REQUESTS_PER_SECOND } else { rate };
+                (host.to_string(), Arc::new(HostLimiter::new(rate)))
+            })
+            .collect();
+
+        let mut pool = Self {
+            providers: HashMap::new(),
+            limiters,
+            budgets: HashMap::new(),
+            transfer_client: reqwest::Client::builder().no_gzip().build()?,
+        };
+        for network in networks {
+            pool.add(network)?;
+        }
+        Ok(pool)
+    }
+
+    /// Adds the endpoints of a network started at runtime, and of the L1 it reads contracts
+    /// on. Hosts already in the pool keep their rate limit and endpoints their budget and
+    /// encoding.
+    pub fn add(&mut self, network: &Network) -> eyre::Result<()> {
+        for url in network.endpoints() {
+            self.add_provider(url, network.accept_encoding)?;
+            self.limiters.entry(url_authority(url).to_string()).or_insert_with(|| {
+                let rate = network.requests_per_second.unwrap_or(DEFAULT_REQUESTS_PER_SECOND);
+                Arc::new(HostLimiter::new(rate))
+            });
+            if let Some(per_hour) = network.requests_per_hour {
+                self.budgets
+                    .entry(url.to_string())
+                    .or_insert_with(|| Arc::new(RequestBudget::new(per_hour)));
+            }
+        }
+        for url in network.l1_endpoints() {
+            self.add_provider(url, AcceptEncoding::default())?;
+            self.limiters
+                .entry(url_authority(url).to_string())
+                .or_insert_with(|| Arc::new(HostLimiter::new(DEFAULT_REQUESTS_PER_SECOND)));
+        }
+        Ok(())
+    }
+
+    fn add_provider(&mut self, url: &str, encoding: AcceptEncoding) -> eyre::Result<()> {
+        if !self.providers.contains_key(url) {
+            let http = Http::with_client(client(encoding)?, url.parse()?);
+            let provider = ProviderBuilder::new().on_client(RpcClient::new(http, false));
+            self.providers.insert(url.to_string(), (provider, encoding));
+        }
+        Ok(())
+    }
+
+    /// Returns the shared provider of an endpoint, metered with stats of its own
+    pub fn endpoint(&self, url: &str) -> eyre::Result<MeteredProvider> {
+        let provider = self.providers.get(url);
+        let limiter = self.limiters.get(url_authority(url));
+        match (provider, limiter) {
+            (Some((provider, encoding)), Some(limiter)) => Ok(MeteredProvider {
+                provider: provider.clone(),
+                url: url.to_string(),
+                encoding: *encoding,
+                transfer_client: self.transfer_client.clone(),
+                limiter: limiter.clone(),
+                budget: self.budgets.get(url).cloned(),
+                stats: Default::default(),
+            }),
+            _ => eyre::bail!("no endpoint for `{url}` in the rpc pool"),
+        }
+    }
+}
+
+/// HTTP client requesting responses in the given encoding, gzip responses being decompressed
+fn client(encoding: AcceptEncoding) -> eyre::Result<reqwest::Client> {
+    let builder = reqwest::Client::builder();
+    let builder = match encoding {
+        AcceptEncoding::Gzip => builder,
+        AcceptEncoding::Identity => {
+            let headers = HeaderMap::from_iter([(
+                header::ACCEPT_ENCODING,
+                HeaderValue::from_static("identity"),
+            )]);
+            builder.no_gzip().default_headers(headers)
+        }
+    };
+    Ok(builder.build()?)
+}
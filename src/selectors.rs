@@ -0,0 +1,33 @@
+use alloy_primitives::Selector;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Function signatures of common selectors, compiled into the binary
+const BUNDLED_SELECTORS: &str = include_str!("../assets/selectors.json");
+
+fn selectors() -> &'static HashMap<Selector, String> {
+    static SELECTORS: OnceLock<HashMap<Selector, String>> = OnceLock::new();
+    SELECTORS.get_or_init(|| {
+        let entries: HashMap<String, String> =
+            serde_json::from_str(BUNDLED_SELECTORS).expect("bundled selectors are valid json");
+        entries
+            .into_iter()
+            .filter_map(|(selector, signature)| {
+                Some((Selector::from_str(&selector).ok()?, signature))
+            })
+            .collect()
+    })
+}
+
+/// Describes a call by its function name, e.g. `transfer`, falling back to the selector's hex
+/// form. Calls without calldata are native transfers.
+pub fn describe_call(selector: Option<&Selector>) -> String {
+    let Some(selector) = selector else {
+        return "native transfer".to_string();
+    };
+    match selectors().get(selector) {
+        Some(signature) => signature.split('(').next().unwrap_or(signature).to_string(),
+        None => selector.to_string(),
+    }
+}
@@ -0,0 +1,93 @@
+use crate::block_metrics::WINDOW_SECONDS;
+use crate::color_rules::ColorRule;
+use crate::indices::IndexConfig;
+use chrono::{DateTime, Local};
+use serde_derive::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, ErrorKind};
+
+/// Settings that apply to all networks
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Settings {
+    /// Metrics windows in seconds, cycled through in the table. The first is the main window,
+    /// which alerts, baselines and per-transaction metrics use.
+    #[serde(default = "default_windows")]
+    pub windows: Vec<u64>,
+    /// Composite indices of several networks, displayed and exported as networks
+    #[serde(default)]
+    pub indices: Vec<IndexConfig>,
+    /// Conditional formatting of table cells, the built-in rules unless set
+    #[serde(default = "ColorRule::defaults")]
+    pub color_rules: Vec<ColorRule>,
+    /// Time zone block timestamps are shown in, unless a network configures its own
+    #[serde(default)]
+    pub time_zone: TimeZone,
+    /// Mean time in milliseconds a UI frame may take before charts are paused and the UI is
+    /// refreshed less often, 0 never degrading
+    #[serde(default = "default_frame_budget_ms")]
+    pub frame_budget_ms: u64,
+}
+
+fn default_frame_budget_ms() -> u64 {
+    40
+}
+
+/// Time zone block timestamps are shown in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeZone {
+    #[default]
+    Local,
+    Utc,
+}
+
+impl TimeZone {
+    /// Formats a unix timestamp as the time of day, suffixed with `Z` in UTC
+    pub fn time_of_day(self, timestamp: u64) -> String {
+        let Some(at) = DateTime::from_timestamp(timestamp as i64, 0) else {
+            return "-".to_string();
+        };
+        match self {
+            TimeZone::Local => at.with_timezone(&Local).format("%H:%M:%S").to_string(),
+            TimeZone::Utc => at.format("%H:%M:%SZ").to_string(),
+        }
+    }
+}
+
+fn default_windows() -> Vec<u64> {
+    vec![WINDOW_SECONDS, 5 * 60, 15 * 60]
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            windows: default_windows(),
+            indices: Vec::new(),
+            color_rules: ColorRule::defaults(),
+            time_zone: TimeZone::default(),
+            frame_budget_ms: default_frame_budget_ms(),
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings, returning the defaults if the file doesn't exist
+    pub fn read(file_path: &str) -> eyre::Result<Self> {
+        let file = match File::open(file_path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+        let settings: Self = serde_json::from_reader(BufReader::new(file))?;
+        if settings.windows.is_empty() || settings.windows.contains(&0) {
+            eyre::bail!("windows must be a non-empty list of seconds");
+        }
+        for index in &settings.indices {
+            index.validate()?;
+        }
+        for rule in &settings.color_rules {
+            rule.validate()?;
+        }
+        Ok(settings)
+    }
+}
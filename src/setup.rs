@@ -0,0 +1,217 @@
+use crate::networks::DEFAULT_NETWORKS;
+use crate::terminal::{self, Screen};
+use crossterm::event::{self, Event, KeyCode};
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Layout, Margin},
+    terminal::{Frame, Terminal},
+    text::{Line, Text},
+    widgets::{Block, Borders, HighlightSpacing, List, ListState, Paragraph},
+};
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Alchemy subdomains of the default networks Alchemy serves, by network name
+const ALCHEMY_NETWORKS: &[(&str, &str)] = &[
+    ("base-mainnet", "base-mainnet"),
+    ("blast-mainnet", "blast-mainnet"),
+    ("arbitrum-one-mainnet", "arb-mainnet"),
+    ("optimism-mainnet", "opt-mainnet"),
+    ("scroll-mainnet", "scroll-mainnet"),
+    ("zksync-era", "zksync-mainnet"),
+    ("linea-mainnet", "linea-mainnet"),
+    ("arbitrum-nova-mainnet", "arbnova-mainnet"),
+    ("zora-mainnet", "zora-mainnet"),
+];
+const PICK_INFO_TEXT: &str =
+    "(↑↓) move | (Space) toggle | (a) toggle all | (Enter) continue | (s) skip | (q) quit";
+const KEY_INFO_TEXT: &str = "(Enter) write config | (Esc) back";
+
+/// How the setup was left
+enum Outcome {
+    Done,
+    Skipped,
+    Quit,
+}
+
+enum Step {
+    Pick,
+    ApiKey,
+}
+
+struct Setup {
+    step: Step,
+    networks: Vec<Value>,
+    selected: Vec<bool>,
+    state: ListState,
+    api_key: String,
+}
+
+impl Setup {
+    fn new() -> eyre::Result<Self> {
+        let networks: Vec<Value> = serde_json::from_str(DEFAULT_NETWORKS)?;
+        // mainnets are preselected
+        let selected = networks.iter().map(|network| network["testnet"] != true).collect();
+        Ok(Self {
+            step: Step::Pick,
+            networks,
+            selected,
+            state: ListState::default().with_selected(Some(0)),
+            api_key: String::new(),
+        })
+    }
+
+    /// The picked networks by name, their other fields taken from the defaults. Networks Alchemy
+    /// serves are served by it if an API key was given, keeping the public endpoint as an
+    /// alternate.
+    fn config(&self) -> Vec<Value> {
+        let api_key = self.api_key.trim();
+        self.picked()
+            .map(|network| {
+                let name = &network["name"];
+                match alchemy_subdomain(network).filter(|_| !api_key.is_empty()) {
+                    Some(subdomain) => json!({
+                        "name": name,
+                        "http": format!("https://{subdomain}.g.alchemy.com/v2/{api_key}"),
+                        "alternate_http": [network["http"]],
+                    }),
+                    None => json!({ "name": name }),
+                }
+            })
+            .collect()
+    }
+
+    fn picked(&self) -> impl Iterator<Item = &Value> {
+        self.networks
+            .iter()
+            .zip(&self.selected)
+            .filter(|(_, selected)| **selected)
+            .map(|(network, _)| network)
+    }
+}
+
+fn alchemy_subdomain(network: &Value) -> Option<&'static str> {
+    ALCHEMY_NETWORKS
+        .iter()
+        .find(|(name, _)| network["name"] == *name)
+        .map(|(_, subdomain)| *subdomain)
+}
+
+/// Walks through picking networks and an optional API key, and writes the network config to
+/// `path`. Returns whether to go on, monitoring all default networks if the setup was skipped.
+pub fn run(path: &str, passive: bool) -> eyre::Result<bool> {
+    let mut setup = Setup::new()?;
+    let mut screen = Screen::enter(passive)?;
+    let res = run_setup(&mut screen.terminal, &mut setup);
+    screen.leave()?;
+
+    match res? {
+        Outcome::Done => {}
+        Outcome::Skipped => return Ok(true),
+        Outcome::Quit => return Ok(false),
+    }
+    if let Some(dir) = Path::new(path).parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut file, &setup.config())?;
+    writeln!(file)?;
+    file.flush()?;
+    println!("Wrote {} networks to {path}", setup.config().len());
+    Ok(true)
+}
+
+fn run_setup<B: Backend>(terminal: &mut Terminal<B>, setup: &mut Setup) -> io::Result<Outcome> {
+    loop {
+        terminal.draw(|f| ui(f, setup))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if !terminal::is_handled(&key) {
+            continue;
+        }
+        match (&setup.step, key.code) {
+            (Step::Pick, KeyCode::Esc | KeyCode::Char('q')) => return Ok(Outcome::Quit),
+            (Step::Pick, KeyCode::Char('s')) => return Ok(Outcome::Skipped),
+            (Step::Pick, KeyCode::Char('j') | KeyCode::Down) => {
+                let last = setup.networks.len().saturating_sub(1);
+                let i = setup.state.selected().map_or(0, |i| (i + 1).min(last));
+                setup.state.select(Some(i));
+            }
+            (Step::Pick, KeyCode::Char('k') | KeyCode::Up) => {
+                let i = setup.state.selected().map_or(0, |i| i.saturating_sub(1));
+                setup.state.select(Some(i));
+            }
+            (Step::Pick, KeyCode::Char(' ')) => {
+                if let Some(selected) =
+                    setup.state.selected().and_then(|i| setup.selected.get_mut(i))
+                {
+                    *selected = !*selected;
+                }
+            }
+            (Step::Pick, KeyCode::Char('a')) => {
+                let all = setup.selected.iter().all(|selected| *selected);
+                setup.selected.iter_mut().for_each(|selected| *selected = !all);
+            }
+            (Step::Pick, KeyCode::Enter) if setup.selected.contains(&true) => {
+                setup.step = Step::ApiKey;
+            }
+            (Step::ApiKey, KeyCode::Enter) => return Ok(Outcome::Done),
+            (Step::ApiKey, KeyCode::Esc) => setup.step = Step::Pick,
+            (Step::ApiKey, KeyCode::Backspace) => {
+                setup.api_key.pop();
+            }
+            (Step::ApiKey, KeyCode::Char(c)) => setup.api_key.push(c),
+            _ => {}
+        }
+    }
+}
+
+fn ui(f: &mut Frame, setup: &mut Setup) {
+    let area = f.size().inner(&Margin { vertical: 1, horizontal: 2 });
+    let [main_area, footer_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area);
+
+    let info_text = match setup.step {
+        Step::Pick => {
+            let items = setup.networks.iter().zip(&setup.selected).map(|(network, selected)| {
+                let check = if *selected { "[x]" } else { "[ ]" };
+                let label = network["label"].as_str().unwrap_or_default();
+                match network["stack"].as_str() {
+                    Some(stack) => format!("{check} {label} ({stack})"),
+                    None => format!("{check} {label}"),
+                }
+            });
+            let count = setup.selected.iter().filter(|selected| **selected).count();
+            let title = format!("No network config found, pick the networks to monitor ({count})");
+            let list = List::new(items)
+                .highlight_symbol(" █ ")
+                .highlight_spacing(HighlightSpacing::Always)
+                .block(Block::default().borders(Borders::ALL).title(title));
+            f.render_stateful_widget(list, main_area, &mut setup.state);
+            PICK_INFO_TEXT
+        }
+        Step::ApiKey => {
+            let alchemy =
+                setup.picked().filter(|network| alchemy_subdomain(network).is_some()).count();
+            let text = Text::from(vec![
+                Line::from(
+                    "Public RPC endpoints are rate limited. Optionally paste an Alchemy API key",
+                ),
+                Line::from(format!(
+                    "to use Alchemy for the {alchemy} picked networks it serves, keeping the public"
+                )),
+                Line::from("endpoints as fallbacks. Leave it empty to only use public endpoints."),
+                Line::from(""),
+                Line::from(format!("Alchemy API key: {}", "*".repeat(setup.api_key.len()))),
+            ]);
+            let paragraph = Paragraph::new(text)
+                .block(Block::default().borders(Borders::ALL).title("API keys"));
+            f.render_widget(paragraph, main_area);
+            KEY_INFO_TEXT
+        }
+    };
+    f.render_widget(Paragraph::new(info_text).centered(), footer_area);
+}
@@ -0,0 +1,112 @@
+use crate::networks::read_networks;
+use crate::supervisor::{Control, SupervisorHandle};
+use crate::types::NetworkMetrics;
+use std::time::Duration;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tracing::{info, warn};
+
+/// How long the block caches and pending alert deliveries are each waited for on shutdown
+pub const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What a process signal asks of the collector in `serve` and `headless` modes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signal {
+    /// SIGTERM or SIGINT: flush the output and alert sinks, then exit
+    Shutdown,
+    /// SIGHUP: reload the network config and alert sinks
+    Reload,
+    /// SIGUSR1: write the current metrics of every network to the log
+    DumpMetrics,
+}
+
+/// Listens for the signals a process supervisor sends. Only Ctrl+C is handled outside unix.
+pub fn listen() -> eyre::Result<Receiver<Signal>> {
+    let (tx, rx) = channel(8);
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let kinds = [
+            (SignalKind::terminate(), Signal::Shutdown),
+            (SignalKind::interrupt(), Signal::Shutdown),
+            (SignalKind::hangup(), Signal::Reload),
+            (SignalKind::user_defined1(), Signal::DumpMetrics),
+        ];
+        for (kind, sent) in kinds {
+            let mut stream = signal(kind)?;
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                while stream.recv().await.is_some() {
+                    if tx.send(sent).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::spawn(async move {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if tx.send(Signal::Shutdown).await.is_err() {
+                return;
+            }
+        }
+    });
+    Ok(rx)
+}
+
+/// Reloads the network config at `path` on [`Signal::Reload`], without waiting for it to be
+/// noticed as changed. Signals are passed on to the returned receiver unchanged.
+pub fn reload_networks(
+    path: &'static str,
+    control: Sender<Control>,
+    mut signals: Receiver<Signal>,
+) -> Receiver<Signal> {
+    let (tx, forwarded) = channel(8);
+    tokio::spawn(async move {
+        while let Some(signal) = signals.recv().await {
+            if signal == Signal::Reload {
+                match read_networks(path) {
+                    Ok(networks) => {
+                        info!("reloading {path} on SIGHUP");
+                        let _ = control.send(Control::Reload(networks)).await;
+                    }
+                    Err(err) => warn!("ignoring invalid {path}: {err}"),
+                }
+            }
+            if tx.send(signal).await.is_err() {
+                return;
+            }
+        }
+    });
+    forwarded
+}
+
+/// Stops the streamers on [`Signal::Shutdown`], saving their block caches, so that no more
+/// messages are sent once it is passed on. Signals are passed on to the returned receiver.
+pub fn stop_streamers(
+    supervisor: SupervisorHandle,
+    mut signals: Receiver<Signal>,
+) -> Receiver<Signal> {
+    let (tx, forwarded) = channel(8);
+    tokio::spawn(async move {
+        while let Some(signal) = signals.recv().await {
+            if signal == Signal::Shutdown {
+                supervisor.shutdown(FLUSH_TIMEOUT).await;
+            }
+            if tx.send(signal).await.is_err() {
+                return;
+            }
+        }
+    });
+    forwarded
+}
+
+/// Writes a line per network with its current metrics to the log
+pub fn log_metrics<'a>(metrics: impl IntoIterator<Item = &'a NetworkMetrics>) {
+    for metrics in metrics {
+        info!(
+            "{}: block {}, {:.2} tps, {:.0} gas/s, {:.0} bytes/s",
+            metrics.network, metrics.block, metrics.tps, metrics.gps, metrics.dps,
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! End-to-end tests of the block streamer against a scripted chain served by [`MockRpc`]
+
+use crate::block_streamer::BlockStreamer;
+use crate::mock_rpc::{MockRpc, Step};
+use crate::networks::Network;
+use crate::rpc_pool::RpcPool;
+use crate::types::{BlockMessage, EventKind};
+use std::path::PathBuf;
+use std::time::Duration;
+use std::{env, fs, process};
+use tokio::sync::mpsc::{channel, Receiver};
+use tokio::task::JoinHandle;
+use tokio::time;
+
+/// How long a test waits for the message it expects
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Streams the network served by the mock, polling every 100ms, returning the messages sent
+async fn stream(
+    name: &str,
+    rpc: &MockRpc,
+    configure: impl FnOnce(&mut Network),
+) -> (Receiver<BlockMessage>, JoinHandle<()>) {
+    let mut network = Network {
+        name: name.to_string(),
+        label: name.to_string(),
+        http: rpc.url.clone(),
+        poll_interval_ms: Some(100),
+        ..Default::default()
+    };
+    configure(&mut network);
+    // a block cache left by an earlier run would be loaded as a warm start
+    let cache_dir = cache_dir(name);
+    let _ = fs::remove_dir_all(&cache_dir);
+    let (tx, rx) = channel(64);
+    let (_, commands) = channel(8);
+    let pool = RpcPool::new(&[network.clone()]).unwrap();
+    let streamer = BlockStreamer::new(network, &pool, &[60], commands, tx).await.unwrap();
+    let mut streamer = streamer.with_cache_dir(&cache_dir);
+    let task = tokio::spawn(async move {
+        let _ = streamer.start().await;
+    });
+    (rx, task)
+}
+
+/// Reads messages until `matches` picks one, panicking after [`TIMEOUT`]
+async fn expect<T>(
+    rx: &mut Receiver<BlockMessage>,
+    mut matches: impl FnMut(&BlockMessage) -> Option<T>,
+) -> T {
+    let found = time::timeout(TIMEOUT, async {
+        while let Some(message) = rx.recv().await {
+            if let Some(found) = matches(&message) {
+                return found;
+            }
+        }
+        panic!("the streamer stopped");
+    });
+    found.await.expect("no matching message in time")
+}
+
+fn cleanup(name: &str, task: JoinHandle<()>) {
+    task.abort();
+    let _ = fs::remove_dir_all(cache_dir(name));
+}
+
+/// Directory of the test's block cache, out of the working tree
+fn cache_dir(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("rollups-{name}-{}", process::id()))
+}
+
+#[tokio::test]
+async fn reports_metrics_of_new_blocks() {
+    let rpc = MockRpc::start(2).await.unwrap();
+    rpc.apply(&Step::Mine { blocks: 5, txs: 10 });
+    let (mut rx, task) = stream("mock-metrics", &rpc, |_| {}).await;
+    rpc.play(vec![Step::Wait(Duration::from_millis(300)), Step::Mine { blocks: 3, txs: 10 }]);
+    let metrics = expect(&mut rx, |message| match message {
+        BlockMessage::UpdateNetwork(metrics) if metrics.block == 8 => Some(metrics.clone()),
+        _ => None,
+    })
+    .await;
+    assert!(metrics.tps > 0.0);
+    assert!(metrics.gps > 0.0);
+    cleanup("mock-metrics", task);
+}
+
+#[tokio::test]
+async fn reports_reorgs() {
+    let rpc = MockRpc::start(2).await.unwrap();
+    rpc.apply(&Step::Mine { blocks: 5, txs: 1 });
+    let (mut rx, task) = stream("mock-reorg", &rpc, |_| {}).await;
+    expect(&mut rx, |message| match message {
+        BlockMessage::UpdateNetwork(metrics) if metrics.block == 5 => Some(()),
+        _ => None,
+    })
+    .await;
+    rpc.apply(&Step::Reorg { depth: 2 });
+    let (block, depth) = expect(&mut rx, |message| match message {
+        BlockMessage::Event(event) => match event.kind {
+            EventKind::Reorg { block, depth } => Some((block, depth)),
+            _ => None,
+        },
+        _ => None,
+    })
+    .await;
+    assert_eq!((block, depth), (4, 2));
+    assert!(rpc.requests("eth_getBlockByHash") >= 2);
+    cleanup("mock-reorg", task);
+}
+
+#[tokio::test]
+async fn reports_stalls() {
+    let rpc = MockRpc::start(2).await.unwrap();
+    rpc.apply(&Step::Mine { blocks: 5, txs: 1 });
+    rpc.apply(&Step::Stall { seconds: 120 });
+    let (mut rx, task) = stream("mock-stall", &rpc, |_| {}).await;
+    let seconds = expect(&mut rx, |message| match message {
+        BlockMessage::Event(event) => match event.kind {
+            EventKind::Stall { seconds } => Some(seconds),
+            _ => None,
+        },
+        _ => None,
+    })
+    .await;
+    assert!(seconds >= 120);
+    cleanup("mock-stall", task);
+}
+
+#[tokio::test]
+async fn counts_blocks_delivered_in_bursts() {
+    let rpc = MockRpc::start(2).await.unwrap();
+    rpc.apply(&Step::Mine { blocks: 2, txs: 10 });
+    // room for the burst's timestamps after the blocks already streamed
+    rpc.apply(&Step::Stall { seconds: 20 });
+    let (mut rx, task) = stream("mock-burst", &rpc, |network| network.smooth_bursts = true).await;
+    expect(&mut rx, |message| match message {
+        BlockMessage::UpdateNetwork(metrics) if metrics.block == 2 => Some(()),
+        _ => None,
+    })
+    .await;
+    rpc.apply(&Step::Mine { blocks: 6, txs: 10 });
+    let metrics = expect(&mut rx, |message| match message {
+        BlockMessage::UpdateNetwork(metrics) if metrics.block == 8 => Some(metrics.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!((metrics.session.blocks, metrics.session.txs), (8, 80));
+    assert!(metrics.tps > 0.0);
+    cleanup("mock-burst", task);
+}
@@ -0,0 +1,336 @@
+use crate::address_book::AddressBook;
+use crate::block_streamer::{BlockStreamer, StreamerCommand};
+use crate::networks::{read_networks, Network};
+use crate::rpc_pool::RpcPool;
+use crate::types::BlockMessage;
+use futures::future::join_all;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time;
+use tracing::{error, info, info_span, warn, Instrument};
+
+/// How often the network config is checked for changes
+const CONFIG_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Runtime requests to the supervisor, e.g. from the console
+#[derive(Clone, Debug)]
+pub enum Control {
+    /// Sets the metrics windows of every network in seconds
+    SetWindows(Vec<u64>),
+    AddNetwork(Box<Network>),
+    /// Pauses or resumes polling of the network with the given label
+    SetPaused(String, bool),
+    /// Stops polling the network with the given label for good
+    Remove(String),
+    /// Reconciles the running networks with the networks read from the changed network config
+    Reload(Vec<Network>),
+    /// Stops every streamer once it has saved its block cache, then signals the sender
+    Shutdown(Sender<()>),
+}
+
+/// Lets the UI control the supervisor and read the networks it polls
+#[derive(Clone)]
+pub struct SupervisorHandle {
+    pub control: Sender<Control>,
+    /// Networks being polled, kept up to date by the supervisor
+    pub networks: Arc<Mutex<Vec<Network>>>,
+}
+
+impl SupervisorHandle {
+    /// Stops the streamers, saving their block caches, waiting for them at most `timeout`
+    pub async fn shutdown(&self, timeout: Duration) {
+        let (done_tx, mut done) = channel(1);
+        if self.control.send(Control::Shutdown(done_tx)).await.is_err() {
+            return;
+        }
+        if time::timeout(timeout, done.recv()).await.is_err() {
+            warn!("stopped before every block cache was saved");
+        }
+    }
+}
+
+/// A running streamer task and its command channel
+struct StreamerHandle {
+    commands: Sender<StreamerCommand>,
+    /// Stops the streamer after saving its block cache
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+/// Owns the streamer tasks and applies runtime control requests to them
+pub struct Supervisor {
+    rpc_pool: RpcPool,
+    /// The running streamers, keyed by network label
+    streamers: HashMap<String, StreamerHandle>,
+    /// Networks last read from the network config, keyed by name. Networks added from the
+    /// console aren't among them and are left alone on reload.
+    configured: HashMap<String, Network>,
+    /// Networks being polled, whether configured or added from the console
+    running: Arc<Mutex<Vec<Network>>>,
+    /// Labels of the paused networks, kept paused when their streamer is restarted
+    paused: HashSet<String>,
+    /// Metrics windows, applied to networks added later too
+    windows: Vec<u64>,
+    /// Whether the streamers send a record of every block
+    record_blocks: bool,
+    /// Labels of addresses, naming the builders of blocks
+    address_book: AddressBook,
+    tx: Sender<BlockMessage>,
+}
+
+impl Supervisor {
+    pub fn new(rpc_pool: RpcPool, windows: Vec<u64>, tx: Sender<BlockMessage>) -> Self {
+        Self {
+            rpc_pool,
+            streamers: HashMap::new(),
+            configured: HashMap::new(),
+            running: Arc::default(),
+            paused: HashSet::new(),
+            windows,
+            record_blocks: false,
+            address_book: AddressBook::default(),
+            tx,
+        }
+    }
+
+    /// Names the builders of blocks by the labels of the address book too
+    pub fn with_address_book(mut self, address_book: AddressBook) -> Self {
+        self.address_book = address_book;
+        self
+    }
+
+    /// Has the streamers send a record of every block, to be recorded or served to watchers
+    pub fn with_block_records(mut self, record_blocks: bool) -> Self {
+        self.record_blocks = record_blocks;
+        self
+    }
+
+    /// The networks being polled, kept up to date as they are added, changed and removed
+    pub fn networks(&self) -> Arc<Mutex<Vec<Network>>> {
+        self.running.clone()
+    }
+
+    /// Spawns the streamers of the networks read from the network config
+    pub async fn start(&mut self, networks: &[Network]) -> eyre::Result<()> {
+        for network in networks {
+            self.spawn(network.clone()).await?;
+            self.configured.insert(network.name.clone(), network.clone());
+        }
+        Ok(())
+    }
+
+    pub async fn spawn(&mut self, network: Network) -> eyre::Result<()> {
+        let (commands_tx, commands) = channel(8);
+        let mut streamer = BlockStreamer::new(
+            network.clone(),
+            &self.rpc_pool,
+            &self.windows,
+            commands,
+            self.tx.clone(),
+        )
+        .await?
+        .with_block_records(self.record_blocks)
+        .with_address_labels(self.address_book.for_network(&network.name));
+        if self.paused.contains(&network.label) {
+            let _ = commands_tx.send(StreamerCommand::SetPaused(true)).await;
+        }
+        {
+            let mut running = self.running.lock().unwrap();
+            running.retain(|running| running.label != network.label);
+            running.push(network.clone());
+        }
+        let span = info_span!("streamer", network = network.label);
+        let (stop_tx, stop) = oneshot::channel();
+        let task = tokio::spawn(
+            async move {
+                let stopped = tokio::select! {
+                    stopped = streamer.start() => stopped,
+                    Ok(()) = stop => {
+                        streamer.save_cache().await;
+                        Ok(())
+                    }
+                };
+                if let Err(err) = stopped {
+                    error!("streamer stopped: {err}");
+                }
+            }
+            .instrument(span),
+        );
+        let handle = StreamerHandle { commands: commands_tx, stop: stop_tx, task };
+        self.streamers.insert(network.label, handle);
+        Ok(())
+    }
+
+    pub async fn run(mut self, mut control: Receiver<Control>) {
+        while let Some(control) = control.recv().await {
+            match control {
+                Control::SetWindows(windows) => {
+                    for streamer in self.streamers.values() {
+                        let command = StreamerCommand::SetWindows(windows.clone());
+                        let _ = streamer.commands.send(command).await;
+                    }
+                    info!("metrics windows set to {windows:?}s");
+                    self.windows = windows;
+                }
+                Control::SetPaused(label, paused) => match self.streamers.get(&label) {
+                    Some(streamer) => {
+                        let _ = streamer.commands.send(StreamerCommand::SetPaused(paused)).await;
+                        info!("{} {label}", if paused { "paused" } else { "resumed" });
+                        match paused {
+                            true => self.paused.insert(label),
+                            false => self.paused.remove(&label),
+                        };
+                    }
+                    None => warn!("no network `{label}` to pause or resume"),
+                },
+                Control::Remove(label) => {
+                    if !self.remove(&label).await {
+                        warn!("no network `{label}` to remove");
+                    }
+                }
+                Control::AddNetwork(network) => {
+                    let label = network.label.clone();
+                    match self.add(*network).await {
+                        Ok(()) => info!("added network {label}"),
+                        Err(err) => warn!("failed to add network {label}: {err}"),
+                    }
+                }
+                Control::Reload(networks) => self.reload(networks).await,
+                Control::Shutdown(done) => {
+                    let stopping = self.streamers.drain().map(|(_, streamer)| {
+                        let _ = streamer.stop.send(());
+                        streamer.task
+                    });
+                    join_all(stopping).await;
+                    let _ = done.send(()).await;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Spawns the networks added to the config, stops the ones removed from it and restarts the
+    /// changed ones, e.g. with new endpoints. A changed network is updated in place, keeping its
+    /// row, history, note and pause state, also when it is relabeled.
+    async fn reload(&mut self, networks: Vec<Network>) {
+        let mut previous = std::mem::take(&mut self.configured);
+        for network in networks {
+            let name = network.name.clone();
+            match previous.remove(&name) {
+                Some(old) if is_unchanged(&old, &network) => {}
+                Some(old) if old.label == network.label => {
+                    let label = network.label.clone();
+                    match self.replace(network.clone()).await {
+                        Ok(()) => info!("updated network {label}"),
+                        Err(err) => warn!("failed to update network {label}: {err}"),
+                    }
+                }
+                Some(old) => {
+                    let (from, to) = (old.label, network.label.clone());
+                    match self.rename(&from, network.clone()).await {
+                        Ok(()) => info!("renamed network {from} to {to}"),
+                        Err(err) => warn!("failed to rename network {from} to {to}: {err}"),
+                    }
+                }
+                None => {
+                    let label = network.label.clone();
+                    match self.add(network.clone()).await {
+                        Ok(()) => info!("reloaded network {label}"),
+                        Err(err) => warn!("failed to reload network {label}: {err}"),
+                    }
+                }
+            }
+            self.configured.insert(name, network);
+        }
+        for old in previous.into_values() {
+            self.remove(&old.label).await;
+        }
+    }
+
+    /// Stops the streamer of a network and withdraws it, returning whether it was running
+    async fn remove(&mut self, label: &str) -> bool {
+        let Some(streamer) = self.streamers.remove(label) else {
+            return false;
+        };
+        // the streamer holds no state worth saving beyond its periodic block cache
+        streamer.task.abort();
+        self.running.lock().unwrap().retain(|network| network.label != label);
+        self.paused.remove(label);
+        info!("removed network {label}");
+        let _ = self.tx.send(BlockMessage::RemoveNetwork(label.to_string())).await;
+        true
+    }
+
+    /// Restarts the streamer of a changed network under the same label, announcing its new
+    /// description without withdrawing it first
+    async fn replace(&mut self, network: Network) -> eyre::Result<()> {
+        if let Some(streamer) = self.streamers.remove(&network.label) {
+            streamer.task.abort();
+        }
+        self.rpc_pool.add(&network)?;
+        self.tx.send(BlockMessage::AddNetwork((&network).into())).await?;
+        self.spawn(network).await
+    }
+
+    /// Restarts the streamer of a relabeled network under its new label, moving its pause state
+    /// and announcing the rename before its new description
+    async fn rename(&mut self, from: &str, network: Network) -> eyre::Result<()> {
+        if self.streamers.contains_key(&network.label) {
+            eyre::bail!("network `{}` already exists", network.label);
+        }
+        if let Some(streamer) = self.streamers.remove(from) {
+            streamer.task.abort();
+        }
+        self.running.lock().unwrap().retain(|running| running.label != from);
+        if self.paused.remove(from) {
+            self.paused.insert(network.label.clone());
+        }
+        let to = network.label.clone();
+        self.tx.send(BlockMessage::RenameNetwork { from: from.to_string(), to }).await?;
+        self.replace(network).await
+    }
+
+    async fn add(&mut self, network: Network) -> eyre::Result<()> {
+        if self.streamers.contains_key(&network.label) {
+            eyre::bail!("network `{}` already exists", network.label);
+        }
+        self.rpc_pool.add(&network)?;
+        self.tx.send(BlockMessage::AddNetwork((&network).into())).await?;
+        self.spawn(network).await
+    }
+}
+
+fn is_unchanged(old: &Network, new: &Network) -> bool {
+    let (old, new) = (serde_json::to_value(old), serde_json::to_value(new));
+    matches!((old, new), (Ok(old), Ok(new)) if old == new)
+}
+
+/// Checks the network config at `path` for changes and asks the supervisor to reload it. A config
+/// that fails to parse, e.g. while it is being edited, is skipped until it changes again.
+pub async fn watch_config(path: &'static str, control: Sender<Control>) {
+    let modified = || fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let mut last: Option<SystemTime> = modified();
+    let mut ticks = time::interval(CONFIG_CHECK_INTERVAL);
+    loop {
+        ticks.tick().await;
+        let current = modified();
+        if current == last {
+            continue;
+        }
+        last = current;
+        match read_networks(path) {
+            Ok(networks) => {
+                info!("{path} changed, reloading networks");
+                if control.send(Control::Reload(networks)).await.is_err() {
+                    return;
+                }
+            }
+            Err(err) => warn!("ignoring invalid {path}: {err}"),
+        }
+    }
+}
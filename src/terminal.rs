@@ -0,0 +1,178 @@
+use clap::ValueEnum;
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent, KeyEventKind},
+    execute,
+    terminal::{
+        self, disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    },
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::Rect;
+use ratatui::{Terminal, TerminalOptions, Viewport};
+use serde_derive::Serialize;
+use std::env;
+use std::io::{self, Stdout, Write};
+use std::process::{self, Stdio};
+use tracing::{debug, warn};
+
+/// Colors of the UI
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+    /// 16 colors on legacy Windows consoles, full colors elsewhere
+    #[default]
+    Auto,
+    /// 24-bit colors
+    Full,
+    /// The 16 standard colors, for consoles without 24-bit color support
+    Basic,
+}
+
+impl Palette {
+    /// Whether the 16 standard colors should be used
+    pub fn is_basic(self) -> bool {
+        match self {
+            Palette::Auto => is_legacy_console(),
+            Palette::Full => false,
+            Palette::Basic => true,
+        }
+    }
+}
+
+/// Whether this is a Windows console host other than Windows Terminal, which doesn't render
+/// 24-bit colors reliably. Support for it is experimental, the layout is untested there.
+fn is_legacy_console() -> bool {
+    cfg!(windows) && env::var_os("WT_SESSION").is_none() && env::var_os("TERM_PROGRAM").is_none()
+}
+
+pub struct Screen {
+    pub terminal: Terminal<CrosstermBackend<Stdout>>,
+    mouse_captured: bool,
+    passive: bool,
+}
+
+impl Screen {
+    /// Switches to raw mode on the alternate screen. Mouse capture isn't supported by some
+    /// Windows consoles and is only attempted.
+    ///
+    /// In `passive` mode the UI is instead drawn inline, below the shell prompt, without
+    /// switching screens or capturing the mouse, for multiplexers that handle those poorly.
+    pub fn enter(passive: bool) -> io::Result<Self> {
+        if is_legacy_console() {
+            warn!(
+                "support for the legacy Windows console is experimental, prefer Windows Terminal"
+            );
+        }
+        enable_raw_mode()?;
+        if passive {
+            let terminal = inline_terminal().inspect_err(|_| {
+                let _ = disable_raw_mode();
+            })?;
+            return Ok(Self { terminal, mouse_captured: false, passive });
+        }
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+        let mouse_captured = match execute!(stdout, EnableMouseCapture) {
+            Ok(()) => true,
+            Err(err) => {
+                debug!("mouse capture unavailable: {err}");
+                false
+            }
+        };
+        let terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+        Ok(Self { terminal, mouse_captured, passive })
+    }
+
+    /// Restores the terminal, leaving the prompt where an inline UI started
+    pub fn leave(mut self) -> io::Result<()> {
+        disable_raw_mode()?;
+        if self.passive {
+            self.terminal.clear()?;
+            return self.terminal.show_cursor();
+        }
+        if self.mouse_captured {
+            execute!(self.terminal.backend_mut(), DisableMouseCapture)?;
+        }
+        execute!(self.terminal.backend_mut(), LeaveAlternateScreen)?;
+        self.terminal.show_cursor()
+    }
+}
+
+/// A terminal drawing below the cursor over the height of the screen. Terminals that don't
+/// report the cursor position are drawn over from the top instead.
+fn inline_terminal() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
+    let (columns, rows) = terminal::size()?;
+    let options = TerminalOptions { viewport: Viewport::Inline(rows) };
+    match Terminal::with_options(CrosstermBackend::new(io::stdout()), options) {
+        Ok(terminal) => Ok(terminal),
+        Err(err) => {
+            debug!("inline viewport unavailable: {err}");
+            let viewport = Viewport::Fixed(Rect::new(0, 0, columns, rows));
+            let mut terminal = Terminal::with_options(
+                CrosstermBackend::new(io::stdout()),
+                TerminalOptions { viewport },
+            )?;
+            terminal.clear()?;
+            Ok(terminal)
+        }
+    }
+}
+
+/// Whether a key event should be handled. Windows consoles report key releases, which are
+/// ignored, and repeats while a key is held, which only move and scroll.
+pub fn is_handled(key: &KeyEvent) -> bool {
+    match key.kind {
+        KeyEventKind::Press => true,
+        KeyEventKind::Repeat => matches!(
+            key.code,
+            KeyCode::Up
+                | KeyCode::Down
+                | KeyCode::PageUp
+                | KeyCode::PageDown
+                | KeyCode::Left
+                | KeyCode::Right
+                | KeyCode::Char('j' | 'k')
+                | KeyCode::Backspace
+        ),
+        KeyEventKind::Release => false,
+    }
+}
+
+/// Copies text to the clipboard with the OSC 52 escape sequence, which most terminals support,
+/// also over SSH and in tmux with `set-clipboard` on
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    stdout.flush()
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+    encoded
+}
+
+/// Opens a URL in the default browser, without its output reaching the UI
+pub fn open_url(url: &str) -> io::Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = process::Command::new("cmd");
+        command.args(["/C", "start", "", url]);
+        command
+    } else {
+        let opener = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+        let mut command = process::Command::new(opener);
+        command.arg(url);
+        command
+    };
+    command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn()?;
+    Ok(())
+}
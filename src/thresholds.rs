@@ -0,0 +1,357 @@
+use crate::alerts::Severity;
+use crate::types::{BlockMessage, EventKind, NetworkEvent};
+use chrono::Utc;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{channel, Receiver};
+
+/// A network lags once its newest block is this many block times old, see [`lag`]
+pub const LAG_BLOCK_TIMES: f64 = 5.0;
+/// Block times under this many seconds count as this long in [`lag`], so that brief gaps
+/// between the blocks of fast chains aren't taken for lag
+const MIN_LAG_BLOCK_TIME: f64 = 6.0;
+
+/// A value of a network's metrics or health a threshold can be set on
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdMetric {
+    Tps,
+    /// Gas per second
+    Gps,
+    /// Bytes per second
+    Dps,
+    /// DA bytes per second
+    Da,
+    /// Seconds since the newest block
+    BlockAge,
+    /// Polls that failed in a row, above 0 while the RPC is down
+    FailedPolls,
+    /// Age of the newest block in block times, see [`lag`]
+    Lag,
+    /// Ratio of the TPS to its usual level at this hour, only known to the UI's color rules
+    TpsBaseline,
+    /// A custom metric of the network, by label
+    Custom(String),
+}
+
+impl ThresholdMetric {
+    /// Value of the metric in the message, `None` if the message doesn't carry it
+    fn value(&self, message: &BlockMessage) -> Option<f64> {
+        match (self, message) {
+            (ThresholdMetric::Tps, BlockMessage::UpdateNetwork(metrics)) => Some(metrics.tps),
+            (ThresholdMetric::Gps, BlockMessage::UpdateNetwork(metrics)) => Some(metrics.gps),
+            (ThresholdMetric::Dps, BlockMessage::UpdateNetwork(metrics)) => Some(metrics.dps),
+            (ThresholdMetric::Da, BlockMessage::UpdateNetwork(metrics)) => Some(metrics.da),
+            (ThresholdMetric::BlockAge, BlockMessage::UpdateNetwork(metrics)) => {
+                metrics.block_age.map(|age| age as f64)
+            }
+            (ThresholdMetric::Lag, BlockMessage::UpdateNetwork(metrics)) => {
+                metrics.block_age.map(|age| lag(age, metrics.block_time))
+            }
+            (ThresholdMetric::Custom(label), BlockMessage::UpdateNetwork(metrics)) => {
+                metrics.custom.iter().find(|custom| custom.label == *label)?.value
+            }
+            (ThresholdMetric::FailedPolls, BlockMessage::Health(health)) => {
+                Some(health.consecutive_errors as f64)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ThresholdMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThresholdMetric::Tps => write!(f, "tps"),
+            ThresholdMetric::Gps => write!(f, "gps"),
+            ThresholdMetric::Dps => write!(f, "dps"),
+            ThresholdMetric::Da => write!(f, "da"),
+            ThresholdMetric::BlockAge => write!(f, "block age"),
+            ThresholdMetric::FailedPolls => write!(f, "failed polls"),
+            ThresholdMetric::Lag => write!(f, "lag"),
+            ThresholdMetric::TpsBaseline => write!(f, "tps to baseline"),
+            ThresholdMetric::Custom(label) => write!(f, "{label}"),
+        }
+    }
+}
+
+/// Limits a metric is checked against, crossed when it is above `above` or below `below`
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct Limits {
+    #[serde(default)]
+    pub above: Option<f64>,
+    #[serde(default)]
+    pub below: Option<f64>,
+}
+
+impl Limits {
+    pub fn validate(&self, metric: &ThresholdMetric) -> eyre::Result<()> {
+        if self.above.is_none() && self.below.is_none() {
+            eyre::bail!("rule on {metric} needs a limit `above` or `below`");
+        }
+        Ok(())
+    }
+
+    pub fn is_crossed(&self, value: f64) -> bool {
+        self.above.is_some_and(|above| value > above)
+            || self.below.is_some_and(|below| value < below)
+    }
+
+    /// The condition, e.g. `tps above 100`
+    pub fn describe(&self, metric: &ThresholdMetric) -> String {
+        match (self.above, self.below) {
+            (Some(above), Some(below)) => format!("{metric} outside {below}..{above}"),
+            (Some(above), None) => format!("{metric} above {above}"),
+            (None, Some(below)) => format!("{metric} below {below}"),
+            (None, None) => metric.to_string(),
+        }
+    }
+}
+
+/// A rule raising an alert while a metric of a network is above or below a limit
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Threshold {
+    /// Name of the alert, e.g. `base tps low`, describing the condition unless set
+    #[serde(default)]
+    pub name: Option<String>,
+    pub metric: ThresholdMetric,
+    #[serde(flatten)]
+    pub limits: Limits,
+    /// Seconds the condition must hold before the alert fires
+    #[serde(default)]
+    pub for_secs: u64,
+    /// Network labels the rule applies to; all networks if empty
+    #[serde(default)]
+    pub networks: Vec<String>,
+    #[serde(default = "default_severity")]
+    pub severity: Severity,
+}
+
+fn default_severity() -> Severity {
+    Severity::Warning
+}
+
+impl Threshold {
+    pub fn validate(&self) -> eyre::Result<()> {
+        if self.metric == ThresholdMetric::TpsBaseline {
+            eyre::bail!("`tps_baseline` is only available to color rules");
+        }
+        self.limits.validate(&self.metric)
+    }
+
+    pub fn name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => self.limits.describe(&self.metric),
+        }
+    }
+}
+
+/// Age in seconds of a network's newest block in its mean block times, at least
+/// [`MIN_LAG_BLOCK_TIME`] each, or in those minimum block times until the block time is known
+pub fn lag(age: u64, block_time: Option<f64>) -> f64 {
+    age as f64 / block_time.unwrap_or_default().max(MIN_LAG_BLOCK_TIME)
+}
+
+/// Condition of a threshold on one network
+#[derive(Default)]
+struct State {
+    /// Since when the limit has been crossed
+    breached: Option<Instant>,
+    firing: bool,
+}
+
+/// Checks the thresholds against the metrics and health read from `rx`, adding an event when one
+/// fires, once its limit has been crossed for its `for_secs`, and when it clears again. Messages
+/// are passed on to the returned receiver unchanged.
+pub fn evaluate(
+    thresholds: Vec<Threshold>,
+    mut rx: Receiver<BlockMessage>,
+) -> Receiver<BlockMessage> {
+    if thresholds.is_empty() {
+        return rx;
+    }
+    let (tx, forwarded) = channel(8);
+    tokio::spawn(async move {
+        let mut states: HashMap<(usize, String), State> = HashMap::new();
+        while let Some(message) = rx.recv().await {
+            let events = match &message {
+                BlockMessage::UpdateNetwork(metrics) => {
+                    check(&thresholds, &mut states, &metrics.network, &message)
+                }
+                BlockMessage::Health(health) => {
+                    check(&thresholds, &mut states, &health.network, &message)
+                }
+                BlockMessage::RemoveNetwork(label)
+                | BlockMessage::RenameNetwork { from: label, .. } => {
+                    states.retain(|(_, network), _| network != label);
+                    Vec::new()
+                }
+                _ => Vec::new(),
+            };
+            if tx.send(message).await.is_err() {
+                return;
+            }
+            for event in events {
+                if tx.send(BlockMessage::Event(event)).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+    forwarded
+}
+
+/// Updates the state of the thresholds on the network with the values in the message, returning
+/// the events of those firing or clearing
+fn check(
+    thresholds: &[Threshold],
+    states: &mut HashMap<(usize, String), State>,
+    network: &str,
+    message: &BlockMessage,
+) -> Vec<NetworkEvent> {
+    let mut events = Vec::new();
+    for (i, threshold) in thresholds.iter().enumerate() {
+        if !threshold.networks.is_empty() && !threshold.networks.iter().any(|n| n == network) {
+            continue;
+        }
+        let Some(value) = threshold.metric.value(message) else { continue };
+        let state = states.entry((i, network.to_string())).or_default();
+        if threshold.limits.is_crossed(value) {
+            let since = *state.breached.get_or_insert_with(Instant::now);
+            if state.firing || since.elapsed() < Duration::from_secs(threshold.for_secs) {
+                continue;
+            }
+        } else {
+            state.breached = None;
+            if !state.firing {
+                continue;
+            }
+        }
+        state.firing = !state.firing;
+        let kind = EventKind::Threshold {
+            rule: threshold.name(),
+            value,
+            severity: threshold.severity,
+            cleared: !state.firing,
+        };
+        events.push(NetworkEvent { network: network.to_string(), at: Utc::now(), kind });
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CustomValue, NetworkHealth, NetworkMetrics};
+
+    fn threshold(metric: ThresholdMetric, limits: Limits, for_secs: u64) -> Threshold {
+        Threshold {
+            name: None,
+            metric,
+            limits,
+            for_secs,
+            networks: Vec::new(),
+            severity: Severity::Warning,
+        }
+    }
+
+    fn below(limit: f64) -> Limits {
+        Limits { above: None, below: Some(limit) }
+    }
+
+    fn update(network: &str, tps: f64) -> BlockMessage {
+        let metrics = NetworkMetrics { network: network.to_string(), tps, ..Default::default() };
+        BlockMessage::UpdateNetwork(Box::new(metrics))
+    }
+
+    /// Whether each event cleared its threshold
+    fn cleared(events: &[NetworkEvent]) -> Vec<bool> {
+        events
+            .iter()
+            .map(|event| match event.kind {
+                EventKind::Threshold { cleared, .. } => cleared,
+                _ => panic!("unexpected event {:?}", event.kind),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fires_once_while_crossed_and_clears() {
+        let thresholds = [threshold(ThresholdMetric::Tps, below(1.0), 0)];
+        let mut states = HashMap::new();
+        let mut observe =
+            |tps| cleared(&check(&thresholds, &mut states, "base", &update("base", tps)));
+        assert!(observe(2.0).is_empty());
+        assert_eq!(observe(0.5), [false]);
+        assert!(observe(0.2).is_empty());
+        assert_eq!(observe(1.5), [true]);
+        assert!(observe(1.5).is_empty());
+    }
+
+    #[test]
+    fn waits_for_the_condition_to_hold() {
+        let thresholds = [threshold(ThresholdMetric::Tps, below(1.0), 3600)];
+        let mut states = HashMap::new();
+        assert!(check(&thresholds, &mut states, "base", &update("base", 0.5)).is_empty());
+        assert!(states[&(0, "base".to_string())].breached.is_some());
+        // recovering before the hour is up resets the condition
+        assert!(check(&thresholds, &mut states, "base", &update("base", 2.0)).is_empty());
+        assert!(states[&(0, "base".to_string())].breached.is_none());
+    }
+
+    #[test]
+    fn applies_to_the_listed_networks_only() {
+        let mut threshold = threshold(ThresholdMetric::Tps, below(1.0), 0);
+        threshold.networks = vec!["base".to_string()];
+        let thresholds = [threshold];
+        let mut states = HashMap::new();
+        assert!(check(&thresholds, &mut states, "zora", &update("zora", 0.5)).is_empty());
+        let events = check(&thresholds, &mut states, "base", &update("base", 0.5));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].network, "base");
+    }
+
+    #[test]
+    fn reads_the_metric_from_its_message() {
+        let health = NetworkHealth {
+            network: "base".to_string(),
+            consecutive_errors: 3,
+            ..Default::default()
+        };
+        let health = BlockMessage::Health(health);
+        assert_eq!(ThresholdMetric::FailedPolls.value(&health), Some(3.0));
+        assert_eq!(ThresholdMetric::Tps.value(&health), None);
+        let custom = CustomValue { label: "queue".to_string(), value: Some(7.0) };
+        let metrics = NetworkMetrics { custom: vec![custom], ..Default::default() };
+        let update = BlockMessage::UpdateNetwork(Box::new(metrics));
+        assert_eq!(ThresholdMetric::Custom("queue".to_string()).value(&update), Some(7.0));
+        assert_eq!(ThresholdMetric::Custom("other".to_string()).value(&update), None);
+        assert_eq!(ThresholdMetric::FailedPolls.value(&update), None);
+    }
+
+    #[test]
+    fn limits_need_a_bound_and_describe_the_condition() {
+        let range = Limits { above: Some(100.0), below: Some(1.0) };
+        assert!(range.is_crossed(101.0) && range.is_crossed(0.5) && !range.is_crossed(50.0));
+        assert_eq!(range.describe(&ThresholdMetric::Tps), "tps outside 1..100");
+        assert_eq!(below(1.0).describe(&ThresholdMetric::BlockAge), "block age below 1");
+        assert!(Limits::default().validate(&ThresholdMetric::Tps).is_err());
+        assert!(below(1.0).validate(&ThresholdMetric::Tps).is_ok());
+    }
+
+    #[tokio::test]
+    async fn adds_events_after_the_message_crossing_the_limit() {
+        let thresholds = vec![threshold(ThresholdMetric::Tps, below(1.0), 0)];
+        let (tx, rx) = channel(8);
+        let mut rx = evaluate(thresholds, rx);
+        tx.send(update("base", 0.5)).await.unwrap();
+        assert!(matches!(rx.recv().await, Some(BlockMessage::UpdateNetwork(_))));
+        let Some(BlockMessage::Event(event)) = rx.recv().await else {
+            panic!("expected a threshold event");
+        };
+        assert_eq!(cleared(&[event]), [false]);
+    }
+}
@@ -0,0 +1,305 @@
+use chrono::Utc;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+const PATH: &str = "cache/timeseries.json";
+/// Hourly TPS of earlier versions, migrated into the store once
+const LEGACY_PATH: &str = "cache/baseline.json";
+/// Hourly rollups are kept for 30 days
+const KEPT_HOURS: i64 = 30 * 24;
+/// Minutes are rolled up into their hour and the store saved at most this often
+const COMPACT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A rate tracked by the store
+#[derive(Clone, Copy, Debug)]
+pub enum Rate {
+    Tps,
+    Gas,
+    Data,
+    Da,
+}
+
+/// Sums of the rates sampled over a minute or an hour
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Aggregate {
+    tps: f64,
+    gps: f64,
+    dps: f64,
+    da: f64,
+    samples: u64,
+    /// Samples of the TPS alone, migrated from the baseline of earlier versions
+    #[serde(default, skip_serializing_if = "is_zero")]
+    tps_samples: u64,
+}
+
+fn is_zero(count: &u64) -> bool {
+    *count == 0
+}
+
+impl Aggregate {
+    /// A single sample of transactions, gas, bytes and DA bytes per second
+    pub fn sample(tps: f64, gps: f64, dps: f64, da: f64) -> Self {
+        Self { tps, gps, dps, da, samples: 1, tps_samples: 0 }
+    }
+
+    fn merge(&mut self, other: &Aggregate) {
+        self.tps += other.tps;
+        self.gps += other.gps;
+        self.dps += other.dps;
+        self.da += other.da;
+        self.samples += other.samples;
+        self.tps_samples += other.tps_samples;
+    }
+
+    /// Mean of the rate over the samples, `None` without samples
+    pub fn mean(&self, rate: Rate) -> Option<f64> {
+        let (sum, samples) = match rate {
+            Rate::Tps => (self.tps, self.samples + self.tps_samples),
+            Rate::Gas => (self.gps, self.samples),
+            Rate::Data => (self.dps, self.samples),
+            Rate::Da => (self.da, self.samples),
+        };
+        (samples > 0).then(|| sum / samples as f64)
+    }
+}
+
+/// Samples of one network, by minutes and hours since the epoch
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Series {
+    /// Minutes not rolled up yet, those of the current hour
+    minutes: BTreeMap<i64, Aggregate>,
+    hours: BTreeMap<i64, Aggregate>,
+}
+
+/// Rates of every network persisted across runs, appended by the minute and downsampled to hourly
+/// rollups kept for [`KEPT_HOURS`], so days of history take a bounded amount of memory
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TimeSeries {
+    /// Series by network label
+    networks: HashMap<String, Series>,
+    #[serde(skip)]
+    last_compacted: Option<Instant>,
+}
+
+impl TimeSeries {
+    /// Reads the series of previous runs, migrating the baseline of earlier versions if there
+    /// are none, a missing file being an empty store
+    pub fn load() -> eyre::Result<Self> {
+        let file = match File::open(PATH) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Self::migrate(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    /// Converts the baseline of earlier versions, which is removed once the store is saved
+    fn migrate() -> eyre::Result<Self> {
+        let file = match File::open(LEGACY_PATH) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+        let legacy: LegacyBaseline = serde_json::from_reader(BufReader::new(file))?;
+        let series = Self::from(legacy);
+        series.save()?;
+        fs::remove_file(LEGACY_PATH)?;
+        info!("migrated the TPS baseline of {LEGACY_PATH} to {PATH}");
+        Ok(series)
+    }
+
+    pub fn save(&self) -> eyre::Result<()> {
+        Self::write(&self.to_json()?)
+    }
+
+    pub fn to_json(&self) -> eyre::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Writes a store serialized with [`Self::to_json`], blocking on the file system
+    pub fn write(json: &[u8]) -> eyre::Result<()> {
+        let path = Path::new(PATH);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        writer.write_all(json)?;
+        writer.flush()?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Adds a sample to the current minute. At most every [`COMPACT_INTERVAL`] the store is
+    /// compacted and returned serialized, to be saved with [`Self::write`] off the caller's lock.
+    pub fn record(&mut self, network: &str, sample: Aggregate) -> eyre::Result<Option<Vec<u8>>> {
+        let minute = Utc::now().timestamp() / 60;
+        let series = self.networks.entry(network.to_string()).or_default();
+        series.minutes.entry(minute).or_default().merge(&sample);
+        if self.last_compacted.is_some_and(|at| at.elapsed() < COMPACT_INTERVAL) {
+            return Ok(None);
+        }
+        // a failed save is retried at the next interval rather than on every sample
+        self.last_compacted = Some(Instant::now());
+        self.compact(current_hour());
+        self.to_json().map(Some)
+    }
+
+    /// Moves the series of a renamed network to its new label, saved with the next compaction
+    pub fn rename(&mut self, from: &str, to: &str) {
+        if let Some(series) = self.networks.remove(from) {
+            self.networks.insert(to.to_string(), series);
+        }
+    }
+
+    /// Rolls the minutes of past hours up into their hour and drops the hours past retention,
+    /// along with networks left without samples
+    fn compact(&mut self, hour: i64) {
+        for series in self.networks.values_mut() {
+            let current = series.minutes.split_off(&(hour * 60));
+            for (minute, aggregate) in std::mem::replace(&mut series.minutes, current) {
+                series.hours.entry(minute.div_euclid(60)).or_default().merge(&aggregate);
+            }
+            series.hours.retain(|&h, _| hour - h < KEPT_HOURS);
+        }
+        self.networks.retain(|_, series| !series.minutes.is_empty() || !series.hours.is_empty());
+    }
+
+    /// Samples of the network over an hour since the epoch, including minutes not rolled up yet
+    pub fn hour(&self, network: &str, hour: i64) -> Aggregate {
+        let Some(series) = self.networks.get(network) else {
+            return Aggregate::default();
+        };
+        let mut aggregate = series.hours.get(&hour).copied().unwrap_or_default();
+        for minute in series.minutes.range(hour * 60..(hour + 1) * 60).map(|(_, m)| m) {
+            aggregate.merge(minute);
+        }
+        aggregate
+    }
+
+    /// Samples of the network over the last `hours` hours up to the current one, oldest first
+    pub fn hourly(&self, network: &str, hours: usize) -> Vec<Aggregate> {
+        let current = current_hour();
+        (0..hours as i64).rev().map(|back| self.hour(network, current - back)).collect()
+    }
+}
+
+/// Hours since the epoch
+pub fn current_hour() -> i64 {
+    Utc::now().timestamp() / 3600
+}
+
+/// Hourly TPS by network label and hours since the epoch, persisted by earlier versions
+#[derive(Deserialize)]
+struct LegacyBaseline {
+    networks: HashMap<String, BTreeMap<i64, LegacyHour>>,
+}
+
+#[derive(Deserialize)]
+struct LegacyHour {
+    tps_sum: f64,
+    samples: u64,
+}
+
+impl From<LegacyBaseline> for TimeSeries {
+    fn from(legacy: LegacyBaseline) -> Self {
+        let networks = legacy
+            .networks
+            .into_iter()
+            .map(|(label, hours)| {
+                let hours = hours
+                    .into_iter()
+                    .map(|(hour, legacy)| {
+                        let aggregate = Aggregate {
+                            tps: legacy.tps_sum,
+                            tps_samples: legacy.samples,
+                            ..Default::default()
+                        };
+                        (hour, aggregate)
+                    })
+                    .collect();
+                (label, Series { minutes: BTreeMap::new(), hours })
+            })
+            .collect();
+        Self { networks, last_compacted: None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(minutes: &[(i64, f64)]) -> TimeSeries {
+        let mut series = Series::default();
+        for &(minute, tps) in minutes {
+            series.minutes.entry(minute).or_default().merge(&Aggregate::sample(tps, 1.0, 2.0, 0.0));
+        }
+        let networks = HashMap::from([("a".to_string(), series)]);
+        TimeSeries { networks, last_compacted: None }
+    }
+
+    #[test]
+    fn compact_rolls_past_minutes_up_into_their_hour() {
+        let hour = 1000;
+        let mut store = series(&[(999 * 60, 1.0), (999 * 60 + 59, 3.0), (hour * 60 + 5, 10.0)]);
+        store.compact(hour);
+        let series = &store.networks["a"];
+        assert_eq!(series.minutes.keys().copied().collect::<Vec<_>>(), [hour * 60 + 5]);
+        assert_eq!(series.hours.keys().copied().collect::<Vec<_>>(), [999]);
+        let past = store.hour("a", 999);
+        assert_eq!(past.samples, 2);
+        assert_eq!(past.mean(Rate::Tps), Some(2.0));
+        assert_eq!(past.mean(Rate::Data), Some(2.0));
+        // the current hour is read from its minutes until rolled up
+        assert_eq!(store.hour("a", hour).mean(Rate::Tps), Some(10.0));
+        assert_eq!(store.hour("a", 998).mean(Rate::Tps), None);
+    }
+
+    #[test]
+    fn compact_drops_hours_past_retention_and_empty_networks() {
+        let hour = 10_000;
+        let oldest_kept = hour - KEPT_HOURS + 1;
+        let mut store = series(&[(oldest_kept * 60, 1.0), ((oldest_kept - 1) * 60, 2.0)]);
+        store.networks.insert("b".to_string(), Series::default());
+        store
+            .networks
+            .get_mut("b")
+            .unwrap()
+            .minutes
+            .insert(0, Aggregate::sample(1.0, 0.0, 0.0, 0.0));
+        store.compact(hour);
+        assert_eq!(store.networks["a"].hours.keys().copied().collect::<Vec<_>>(), [oldest_kept]);
+        assert!(!store.networks.contains_key("b"));
+    }
+
+    #[test]
+    fn rename_moves_the_series_to_the_new_label() {
+        let mut store = series(&[(999 * 60, 1.0)]);
+        store.rename("a", "b");
+        assert!(!store.networks.contains_key("a"));
+        assert_eq!(store.hour("b", 999).samples, 1);
+    }
+
+    #[test]
+    fn legacy_baseline_migrates_to_tps_only_hours() {
+        let json = r#"{"networks":{"a":{"1000":{"tps_sum":30.0,"samples":3}}}}"#;
+        let mut store = TimeSeries::from(serde_json::from_str::<LegacyBaseline>(json).unwrap());
+        assert_eq!(store.hour("a", 1000).mean(Rate::Tps), Some(10.0));
+        assert_eq!(store.hour("a", 1000).mean(Rate::Gas), None);
+        // samples recorded since are merged with the migrated ones
+        store
+            .networks
+            .get_mut("a")
+            .unwrap()
+            .minutes
+            .insert(1000 * 60, Aggregate::sample(2.0, 4.0, 0.0, 0.0));
+        let hour = store.hour("a", 1000);
+        assert_eq!(hour.mean(Rate::Tps), Some(8.0));
+        assert_eq!(hour.mean(Rate::Gas), Some(4.0));
+    }
+}
@@ -0,0 +1,62 @@
+use crate::types::EventKind;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// TPS is sampled this often, the metrics being averages over the window anyway
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+/// Number of samples the rolling mean and standard deviation are computed over
+const SAMPLES: usize = 60;
+/// Samples needed before anomalies are flagged
+const MIN_SAMPLES: usize = 20;
+/// Standard deviation floor relative to the mean, so very steady networks don't flag noise
+const MIN_RELATIVE_STDDEV: f64 = 0.01;
+pub const DEFAULT_Z_SCORE: f64 = 3.0;
+
+/// Flags TPS spikes and drops beyond a z-score of the rolling mean over the session.
+///
+/// Anomalies are edge triggered: a sustained anomaly is only reported once, when TPS first
+/// crosses the threshold.
+pub struct TpsAnomalyDetector {
+    z_score: f64,
+    samples: VecDeque<f64>,
+    last_sample: Option<Instant>,
+    anomalous: bool,
+}
+
+impl TpsAnomalyDetector {
+    pub fn new(z_score: f64) -> Self {
+        Self { z_score, samples: VecDeque::new(), last_sample: None, anomalous: false }
+    }
+
+    /// Observes the current TPS, returning an anomaly if it starts one
+    pub fn observe(&mut self, tps: f64) -> Option<EventKind> {
+        if self.last_sample.is_some_and(|at| at.elapsed() < SAMPLE_INTERVAL) {
+            return None;
+        }
+        self.last_sample = Some(Instant::now());
+
+        let stats = self.stats();
+        if self.samples.len() == SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(tps);
+
+        let (mean, stddev) = stats?;
+        let z = (tps - mean) / stddev.max(mean * MIN_RELATIVE_STDDEV).max(f64::EPSILON);
+        let anomalous = z.abs() >= self.z_score;
+        let started = anomalous && !self.anomalous;
+        self.anomalous = anomalous;
+        started.then_some(EventKind::TpsAnomaly { tps, mean, z })
+    }
+
+    /// Mean and standard deviation of the samples
+    fn stats(&self) -> Option<(f64, f64)> {
+        if self.samples.len() < MIN_SAMPLES {
+            return None;
+        }
+        let n = self.samples.len() as f64;
+        let mean = self.samples.iter().sum::<f64>() / n;
+        let variance = self.samples.iter().map(|tps| (tps - mean).powi(2)).sum::<f64>() / n;
+        Some((mean, variance.sqrt()))
+    }
+}
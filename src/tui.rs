@@ -1,179 +1,1486 @@
 /// Based on the table example from ratatui
-use crate::networks::Network;
-use crate::types::BlockMessage;
-use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+use crate::address_book::{AddressBook, AddressLabels};
+use crate::alert_sinks::AlertRouter;
+use crate::alerts::{Alert, AlertLog};
+use crate::baseline;
+use crate::capabilities::Capability;
+use crate::color_rules::{cell_format, CellColumn, CellFormat};
+use crate::columns::{Column, Sort};
+use crate::console::{ConsoleCommand, HELP};
+use crate::effective_config::EffectiveConfig;
+use crate::frame_stats::FrameStats;
+use crate::history::History;
+use crate::logging::LogTail;
+use crate::networks::{url_authority, Network};
+use crate::notes::Notes;
+use crate::selectors::describe_call;
+use crate::settings::TimeZone;
+use crate::signals::FLUSH_TIMEOUT;
+use crate::supervisor::{Control, SupervisorHandle};
+use crate::terminal::{self, Palette, Screen};
+use crate::thresholds::{self, ThresholdMetric, LAG_BLOCK_TIMES};
+use crate::timeseries::{Aggregate, Rate, TimeSeries};
+use crate::types::{
+    BlockMessage, BridgeActivity, BudgetStatus, CustomValue, EventKind, GasToken, InboxQueue,
+    LatencyHistogram, Log, NetworkEvent, NetworkHealth, NetworkInfo, PollStatus, RecentBlock,
+    SessionTotals, TxGasStats, TxSample, VerificationLag, WindowMetrics, ZkBatches,
+    LATENCY_BUCKETS_MS,
 };
+use crate::ui_state::UiState;
+use crate::units::{csv_field, format_bytes, format_count, format_fee, format_token, Units};
+use alloy_primitives::{Address, Selector, B256};
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::Direction;
 use ratatui::prelude::Alignment;
 use ratatui::widgets::Borders;
 use ratatui::{
-    backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Layout, Margin, Rect},
-    style::{self, Color, Style, Stylize},
+    backend::Backend,
+    layout::{Constraint, Flex, Layout, Margin, Rect},
+    style::{self, Color, Modifier, Style, Styled, Stylize},
     terminal::{Frame, Terminal},
-    text::{Line, Text},
-    widgets::{Block, BorderType, Cell, HighlightSpacing, Paragraph, Row, Table, TableState},
+    text::{Line, Span, Text},
+    widgets::{
+        Block, BorderType, Cell, Clear, HighlightSpacing, Paragraph, Row, Sparkline, Table,
+        TableState,
+    },
 };
-use std::collections::HashMap;
-use std::io;
+use serde_derive::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use style::palette::tailwind;
 use tokio::sync::mpsc::Receiver;
+use tokio::task;
 use tokio::time::{self, Duration};
+use tracing::warn;
 use unicode_width::UnicodeWidthStr;
 
 const PALETTE: tailwind::Palette = tailwind::BLUE;
-const INFO_TEXT: &str = "(Esc) quit | (↑) move up | (↓) move down";
+/// How long keys are waited for between frames
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+/// How long keys are waited for between frames while frames are over budget
+const DEGRADED_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const INFO_TEXT: &str =
+    "(Esc) quit | (↑↓) move | (Enter) details | (p) pause | (:) console | (?) all keys";
+/// Keys of the table view, listed by `?`
+const KEYS: &[(&str, &str)] = &[
+    ("Esc / q", "quit"),
+    ("↑↓", "move"),
+    ("Enter", "details of the network"),
+    ("p", "pause or resume polling the network"),
+    ("n", "add a network"),
+    ("x", "remove the network"),
+    ("t", "hide or show testnets"),
+    ("G", "group networks"),
+    ("s / o / f", "sort by the column, reverse, freeze the order"),
+    ("←→", "select a column"),
+    ("⇧←→", "resize the column"),
+    ("m", "mark the timeline"),
+    ("h", "hourly history"),
+    ("w", "rate window"),
+    ("e", "events"),
+    ("a", "alerts"),
+    ("l", "logs"),
+    ("L", "log of the network"),
+    ("r", "RPC endpoints"),
+    ("g / d", "gas and data units"),
+    ("F", "frame times"),
+    (":", "console, `help` lists its commands"),
+];
+/// Group header of networks without a group, listed after the groups
+const UNGROUPED: &str = "Other";
+/// Narrowest a column can be resized to
+const MIN_COLUMN_WIDTH: u16 = 3;
+/// Group header of the testnets, listed last
+const TESTNETS_GROUP: &str = "Testnets";
+const DETAIL_INFO_TEXT: &str = "(Esc) back | (q) quit | (↑↓) transactions | (y) copy hash | (o) open in explorer | (m) mark | (h) hourly | (g) gas units | (d) data units";
+const EVENTS_INFO_TEXT: &str = "(Esc) back | (q) quit";
+const LOGS_INFO_TEXT: &str = "(Esc) back | (q) quit";
+const ENDPOINTS_INFO_TEXT: &str = "(Esc) back | (q) quit | (c) capabilities";
+/// Shades of the latency distribution bars, from no requests to the busiest bucket
+const SHADES: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const ALERTS_INFO_TEXT: &str =
+    "(Esc) back | (↑↓) move | (Space) acknowledge | (A) acknowledge all | (c) clear acknowledged";
+/// Time span covered by the event timeline
+const TIMELINE_SPAN: chrono::Duration = chrono::Duration::minutes(15);
+/// How often the totals and per-network metrics are sampled for the sparklines
+const HISTORY_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// Number of samples kept, covering the last 10 minutes
+const HISTORY_SAMPLES: usize = 600;
+/// Number of notable events kept across all networks
+const MAX_EVENTS: usize = 200;
+/// Marks user markers on the event timeline
+const MARKER_GLYPH: char = '▼';
+/// Number of network log lines kept across all networks
+const MAX_NETWORK_LOGS: usize = 500;
+/// Lines scrolled by PgUp/PgDn in the network log pane
+const NETWORK_LOG_SCROLL: usize = 5;
+/// Height of the network log pane below the table, including its borders
+const NETWORK_LOG_HEIGHT: u16 = 10;
+/// Number of recent events shown in the detail view
+const DETAIL_EVENTS: usize = 5;
+/// Rows that haven't received a metrics update for this long are dimmed
+const STALE_AFTER: Duration = Duration::from_secs(10);
+/// A network is down after this many failed polls in a row
+const DOWN_AFTER_ERRORS: u32 = 3;
+/// Endpoints slower than this mark a network as degraded
+const SLOW_LATENCY_MS: u64 = 1000;
+
+/// Connection health of a network, from its streamer's recent polls
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HealthStatus {
+    Healthy,
+    /// Polls failing or slow, but still succeeding recently
+    Degraded,
+    /// Polls failing repeatedly or no successful poll recently
+    Down,
+}
+
+impl HealthStatus {
+    fn of(health: &NetworkHealth) -> Self {
+        let since_success = health
+            .last_success
+            .map(|at| (Utc::now() - at).to_std().unwrap_or_default())
+            .unwrap_or(Duration::MAX);
+        if health.consecutive_errors >= DOWN_AFTER_ERRORS || since_success >= STALE_AFTER {
+            HealthStatus::Down
+        } else if health.consecutive_errors > 0
+            || health.latency_ms.is_some_and(|latency| latency > SLOW_LATENCY_MS)
+        {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Healthy
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            HealthStatus::Healthy => "● ok",
+            HealthStatus::Degraded => "● degraded",
+            HealthStatus::Down => "● down",
+        }
+    }
+}
 
 #[derive(Clone, Default)]
 struct Metrics {
     pub gps: f64,
     pub tps: f64,
     pub dps: f64,
+    /// Data availability bytes per second
+    pub da: f64,
+}
+
+impl From<&WindowMetrics> for Metrics {
+    fn from(window: &WindowMetrics) -> Self {
+        Self { gps: window.gps, tps: window.tps, dps: window.dps, da: window.da }
+    }
 }
 
 impl Metrics {
-    fn cells(&self) -> [String; 3] {
+    fn cells(&self, units: Units) -> [String; 4] {
         [
             format!("{:.2}", self.tps),
-            format!("{:.2}", self.gps / 1024.0 / 1024.0),
-            format!("{:.2}", self.dps / 1024.0),
+            format!("{:.2}", units.gas.scale(self.gps)),
+            format!("{:.2}", units.data.scale(self.dps)),
+            format!("{:.2}", units.data.scale(self.da)),
         ]
     }
+
+    fn add(&mut self, other: &Metrics) {
+        self.gps += other.gps;
+        self.tps += other.tps;
+        self.dps += other.dps;
+        self.da += other.da;
+    }
+
+    /// Percentages of the given totals
+    fn share_of(&self, totals: &Metrics) -> Metrics {
+        let percent =
+            |value: f64, total: f64| if total > 0.0 { value / total * 100.0 } else { 0.0 };
+        Metrics {
+            tps: percent(self.tps, totals.tps),
+            gps: percent(self.gps, totals.gps),
+            dps: percent(self.dps, totals.dps),
+            da: percent(self.da, totals.da),
+        }
+    }
+
+    /// Share of total tx/gas/data as a cell, `-` without any
+    fn share_cell(&self, totals: &Metrics) -> String {
+        let share = self.share_of(totals);
+        if share.tps > 0.0 || share.gps > 0.0 || share.dps > 0.0 {
+            format!("{:.0}/{:.0}/{:.0}%", share.tps, share.gps, share.dps)
+        } else {
+            "-".to_string()
+        }
+    }
+}
+
+/// Sampled history and session peaks of the totals or a network's metrics
+#[derive(Clone)]
+struct MetricsHistory {
+    tps: History,
+    gps: History,
+    dps: History,
+    da: History,
+    /// When each sample was taken, to place markers on the sparklines
+    times: VecDeque<Instant>,
+    peaks: Metrics,
+    last_sample: Option<Instant>,
+}
+
+impl Default for MetricsHistory {
+    fn default() -> Self {
+        Self {
+            tps: History::new(HISTORY_SAMPLES),
+            gps: History::new(HISTORY_SAMPLES),
+            dps: History::new(HISTORY_SAMPLES),
+            da: History::new(HISTORY_SAMPLES),
+            times: VecDeque::with_capacity(HISTORY_SAMPLES),
+            peaks: Metrics::default(),
+            last_sample: None,
+        }
+    }
+}
+
+impl MetricsHistory {
+    fn record(&mut self, metrics: &Metrics) {
+        self.peaks.tps = self.peaks.tps.max(metrics.tps);
+        self.peaks.gps = self.peaks.gps.max(metrics.gps);
+        self.peaks.dps = self.peaks.dps.max(metrics.dps);
+        self.peaks.da = self.peaks.da.max(metrics.da);
+        if self.last_sample.is_some_and(|at| at.elapsed() < HISTORY_SAMPLE_INTERVAL) {
+            return;
+        }
+        self.tps.push(metrics.tps);
+        self.gps.push(metrics.gps);
+        self.dps.push(metrics.dps);
+        self.da.push(metrics.da);
+        if self.times.len() == HISTORY_SAMPLES {
+            self.times.pop_front();
+        }
+        self.times.push_back(Instant::now());
+        self.last_sample = Some(Instant::now());
+    }
+
+    /// Sparkline column of the first sample taken at or after `at` when the last `width`
+    /// samples are shown, `None` if `at` is older than the oldest of them
+    fn column(&self, at: Instant, width: usize) -> Option<usize> {
+        let skip = self.times.len().saturating_sub(width);
+        if at < *self.times.get(skip)? {
+            return None;
+        }
+        let shown = self.times.len() - skip;
+        let column = self.times.iter().skip(skip).position(|&time| time >= at).unwrap_or(shown);
+        (column < width).then_some(column)
+    }
+}
+
+/// A point in the session marked by the user, e.g. "incident declared"
+struct Marker {
+    at: DateTime<Utc>,
+    instant: Instant,
+    label: String,
 }
 
 #[derive(Clone, Default)]
 struct NetworkMetrics {
     name: String,
     block: u64,
+    session: SessionTotals,
+    tx_gas: Option<TxGasStats>,
+    top_contracts: Vec<(Address, usize)>,
+    builders: Vec<(String, f64)>,
+    top_selectors: Vec<(Option<Selector>, usize)>,
+    bridge: Option<BridgeActivity>,
+    mints: Option<f64>,
+    blobs: Option<f64>,
+    l1_freshness: Option<u64>,
+    base_fees: f64,
+    base_fee: Option<u128>,
+    priority_fee: Option<u128>,
+    /// Native value transferred per second
+    value: Option<f64>,
+    gas_token: GasToken,
+    inbox: Option<InboxQueue>,
+    zk_batches: Option<ZkBatches>,
+    verification: Option<VerificationLag>,
+    custom: Vec<CustomValue>,
+    /// Threshold alerts currently firing, by rule
+    firing: Vec<String>,
+    endpoint: Option<String>,
+    budget: Option<BudgetStatus>,
+    polling: Option<PollStatus>,
+    health: Option<NetworkHealth>,
+    /// Rates over each metrics window, `metrics` being those of the displayed window
+    windows: Vec<WindowMetrics>,
+    labels: AddressLabels,
+    updated: Option<Instant>,
+    /// Whether metric overrides are configured for this network
+    adjusted: bool,
+    /// Rollup stack the network is built on
+    stack: Option<String>,
+    /// Group the network is listed under when the table is grouped
+    group: Option<String>,
+    /// Testnets are listed after mainnets and left out of totals
+    testnet: bool,
+    /// A composite index of other networks
+    index: bool,
+    /// Polling is paused, the last known values are kept
+    paused: bool,
+    /// Remote collector the metrics come from
+    source: Option<String>,
     metrics: Metrics,
+    history: MetricsHistory,
+    /// Mean seconds between blocks over the window
+    block_time: Option<f64>,
+    /// Age of the newest block when the metrics were updated
+    block_age: Option<u64>,
+    /// Newest first
+    recent_blocks: Vec<RecentBlock>,
+    /// Newest first
+    tx_samples: Vec<TxSample>,
+    explorer: Option<String>,
+    /// Time zone block timestamps are shown in, overriding the settings
+    time_zone: Option<TimeZone>,
 }
 
 impl NetworkMetrics {
-    fn cells(&self) -> [String; 5] {
+    /// Network label annotated with overrides and the remote collector, if any
+    fn display_name(&self) -> String {
+        let mut name = if self.index { format!("Σ {}", self.name) } else { self.name.clone() };
+        if self.adjusted {
+            name.push('*');
+        }
+        if let Some(source) = &self.source {
+            name = format!("{name} ({source})");
+        }
+        name
+    }
+
+    /// Group the row is listed under, testnets being grouped together
+    fn group_name(&self) -> &str {
+        match &self.group {
+            _ if self.testnet => TESTNETS_GROUP,
+            Some(group) => group,
+            None => UNGROUPED,
+        }
+    }
+
+    /// Whether the network counts towards the totals, which testnets and indices don't
+    fn in_totals(&self) -> bool {
+        !self.testnet && !self.index
+    }
+
+    /// Connection health, unknown while paused or before the first poll
+    fn health_status(&self) -> Option<HealthStatus> {
+        self.health.as_ref().filter(|_| !self.paused).map(HealthStatus::of)
+    }
+
+    fn cells(&self, units: Units, totals: &Metrics) -> [String; 16] {
+        let [tps, gps, dps, da] = self.metrics.cells(units);
         [
-            self.name.clone(),
-            self.block.to_string(),
-            format!("{:.2}", self.metrics.tps),
-            format!("{:.2}", self.metrics.gps / 1024.0 / 1024.0),
-            format!("{:.2}", self.metrics.dps / 1024.0),
+            self.display_name(),
+            if self.index { "-".to_string() } else { self.block.to_string() },
+            self.block_time.map_or("-".to_string(), |seconds| format!("{seconds:.1}s")),
+            self.last_block_age().map_or("-".to_string(), |age| format!("{age}s")),
+            tps,
+            gps,
+            dps,
+            da,
+            self.base_fee.map_or("-".to_string(), |fee| format_fee(fee, &self.gas_token)),
+            self.priority_fee.map_or("-".to_string(), |fee| format_fee(fee, &self.gas_token)),
+            self.value.map_or("-".to_string(), |value| format_token(value, &self.gas_token)),
+            if self.in_totals() { self.metrics.share_cell(totals) } else { "-".to_string() },
+            self.builders.first().map_or("-".to_string(), |(builder, share)| {
+                format!("{builder} {:.0}%", share * 100.0)
+            }),
+            self.verification
+                .and_then(|lag| lag.age)
+                .map_or("-".to_string(), |age| format!("{}m", age / 60)),
+            match self.updated {
+                _ if self.paused => "paused".to_string(),
+                Some(at) => format!("{}s", at.elapsed().as_secs()),
+                None => "-".to_string(),
+            },
+            self.health_status().map_or("-", HealthStatus::label).to_string(),
         ]
     }
+
+    /// Current value of a metric color rules and thresholds are set on
+    fn metric_value(&self, metric: &ThresholdMetric) -> Option<f64> {
+        match metric {
+            ThresholdMetric::Tps => Some(self.metrics.tps),
+            ThresholdMetric::Gps => Some(self.metrics.gps),
+            ThresholdMetric::Dps => Some(self.metrics.dps),
+            ThresholdMetric::Da => Some(self.metrics.da),
+            ThresholdMetric::BlockAge => self.last_block_age().map(|age| age as f64),
+            ThresholdMetric::Lag => self.lag(),
+            // read from the history by the app
+            ThresholdMetric::TpsBaseline => None,
+            ThresholdMetric::FailedPolls => {
+                self.health.as_ref().map(|health| health.consecutive_errors as f64)
+            }
+            ThresholdMetric::Custom(label) => {
+                self.custom.iter().find(|custom| custom.label == *label)?.value
+            }
+        }
+    }
+
+    /// Seconds since the newest block, counting on from the last update
+    fn last_block_age(&self) -> Option<u64> {
+        let since_update = match self.updated {
+            Some(at) if !self.paused => at.elapsed().as_secs(),
+            _ => 0,
+        };
+        self.block_age.map(|age| age + since_update)
+    }
+
+    /// Age of the newest block in block times, unknown while paused
+    fn lag(&self) -> Option<f64> {
+        let age = self.last_block_age().filter(|_| !self.paused)?;
+        Some(thresholds::lag(age, self.block_time))
+    }
+
+    /// Whether the newest block is far older than the block time, e.g. the chain has halted or
+    /// the endpoint serves an old head
+    fn is_lagging(&self) -> bool {
+        self.lag().is_some_and(|lag| lag > LAG_BLOCK_TIMES)
+    }
+
+    fn is_stale(&self) -> bool {
+        if self.paused {
+            return true;
+        }
+        match self.updated {
+            Some(at) => at.elapsed() >= STALE_AFTER,
+            None => true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum View {
+    #[default]
+    Table,
+    /// Detail view for the network with the given label
+    Detail(String),
+    /// Notable events across all networks on a shared timeline
+    Events,
+    /// History of fired alerts
+    Alerts,
+    /// Tail of the log file
+    Logs,
+    /// Request latency and errors of every RPC endpoint
+    Endpoints,
+}
+
+/// A row of the networks table
+enum TableRow<'a> {
+    /// Header of a group with the subtotals of its networks
+    Group {
+        name: &'a str,
+        subtotals: Metrics,
+        networks: usize,
+        collapsed: bool,
+    },
+    Network(&'a NetworkMetrics),
+}
+
+impl TableRow<'_> {
+    fn is_network(&self, name: &str) -> bool {
+        matches!(self, TableRow::Network(item) if item.name == name)
+    }
 }
 
 struct App {
+    view: View,
+    address_book: AddressBook,
     longest_name: u16,
+    /// Mainnets first, then testnets, each in the order of `sort` within their group if grouped
     items: Vec<NetworkMetrics>,
+    sort: Sort,
+    /// Columns left out of the table
+    hidden_columns: Vec<Column>,
+    /// Widths of the columns resized with Shift+←/→, the others sized to fit
+    column_widths: BTreeMap<Column, u16>,
+    /// Column resized with Shift+←/→, the network column unless one was focused with ←/→
+    focused_column: Option<Column>,
+    /// Widths of the visible columns as last drawn, resizing starts from
+    drawn_widths: Vec<(Column, u16)>,
+    /// Testnets are left out of the table
+    hide_testnets: bool,
+    /// Networks are listed under a header per group with its subtotals
+    grouped: bool,
+    /// Groups whose networks are hidden, leaving their header
+    collapsed_groups: BTreeSet<String>,
+    notes: Notes,
+    /// Configuration the app was started with, plus the networks added from the console
+    config: EffectiveConfig,
+    /// Hourly rates of previous runs to compare the current TPS against
+    time_series: TimeSeries,
+    /// The time series serialized for saving, written once the app is unlocked
+    unsaved_history: Option<Vec<u8>>,
+    /// Whether the sparklines show the stored hourly means instead of the session's samples
+    hourly_history: bool,
+    /// Whether the endpoints view shows the capability matrix instead of request statistics
+    show_capabilities: bool,
+    /// Index of the metrics window displayed
+    window: usize,
+    /// Latest metrics of the mainnets in the displayed window, making up the totals
     latest: HashMap<String, Metrics>,
     totals: Metrics,
+    totals_history: MetricsHistory,
+    units: Units,
+    events: VecDeque<NetworkEvent>,
+    alerts: AlertLog,
+    alerts_state: TableState,
+    log_tail: LogTail,
+    markers: Vec<Marker>,
+    /// Log lines sent by the streamers, shown in a pane below the table
+    network_logs: VecDeque<Log>,
+    show_network_logs: bool,
+    /// Lines the network log pane is scrolled back from the newest line
+    network_log_scroll: usize,
+    /// Runtime control of the streamers, unavailable when watching a remote collector
+    supervisor: Option<SupervisorHandle>,
+    /// Input of the `:` prompt while it is open
+    console: Option<String>,
+    /// Outcome of the last console command
+    console_message: Option<String>,
+    /// Transaction selected in the detail view, kept while newer samples push it down
+    selected_tx: Option<B256>,
+    started: Instant,
+    last_update: Option<Instant>,
+    /// Time spent per frame, degrading the charts while over budget
+    frame_stats: FrameStats,
+    /// Whether the frame time diagnostics are drawn over the view
+    show_frame_stats: bool,
+    /// Whether the keys of the table view are listed over it, until the next key
+    show_keys: bool,
     state: TableState,
     colors: TableColors,
 }
 
 impl App {
-    fn new(networks: Vec<Network>) -> Self {
-        let items = networks
-            .iter()
-            .map(|n| NetworkMetrics { name: n.label.clone(), ..Default::default() })
-            .collect::<Vec<_>>();
-        let longest_name =
-            networks.iter().map(|n| UnicodeWidthStr::width(n.label.as_str())).max().unwrap_or(0)
-                as u16;
-        Self {
+    fn new(
+        config: EffectiveConfig,
+        address_book: AddressBook,
+        log_tail: LogTail,
+        supervisor: Option<SupervisorHandle>,
+    ) -> Self {
+        let mut app = Self {
+            view: View::Table,
+            address_book,
             state: TableState::default().with_selected(0),
-            longest_name,
-            colors: TableColors::new(),
-            items,
+            longest_name: 0,
+            colors: TableColors::new(config.palette),
+            items: Vec::new(),
+            sort: Sort::default(),
+            hidden_columns: Vec::new(),
+            column_widths: BTreeMap::new(),
+            focused_column: None,
+            drawn_widths: Vec::new(),
+            hide_testnets: false,
+            grouped: false,
+            collapsed_groups: BTreeSet::new(),
+            config: EffectiveConfig::default(),
+            notes: Notes::read().unwrap_or_else(|err| {
+                warn!("ignoring network notes: {err}");
+                Notes::default()
+            }),
+            time_series: TimeSeries::load().unwrap_or_else(|err| {
+                warn!("ignoring metrics history: {err}");
+                TimeSeries::default()
+            }),
+            unsaved_history: None,
+            hourly_history: false,
+            show_capabilities: false,
+            window: 0,
             latest: HashMap::new(),
             totals: Metrics::default(),
+            totals_history: MetricsHistory::default(),
+            units: Units::default(),
+            events: VecDeque::new(),
+            alerts: AlertLog::default(),
+            alerts_state: TableState::default(),
+            log_tail,
+            markers: Vec::new(),
+            network_logs: VecDeque::new(),
+            show_network_logs: false,
+            network_log_scroll: 0,
+            supervisor,
+            console: None,
+            console_message: None,
+            selected_tx: None,
+            started: Instant::now(),
+            last_update: None,
+            frame_stats: FrameStats::new(Duration::from_millis(config.frame_budget_ms)),
+            show_frame_stats: false,
+            show_keys: false,
+        };
+        for network in &config.networks {
+            app.add_network(network.into());
+        }
+        app.config = config;
+        match UiState::load() {
+            Ok(state) => app.restore(state),
+            Err(err) => warn!("ignoring saved UI state: {err}"),
+        }
+        app
+    }
+
+    /// Restores the UI state of the previous session, as far as it applies to the networks
+    /// and windows of this one
+    fn restore(&mut self, state: UiState) {
+        self.hide_testnets = state.hide_testnets;
+        self.grouped = state.grouped;
+        self.collapsed_groups = state.collapsed_groups;
+        self.sort = state.sort;
+        self.hidden_columns = state.hidden_columns;
+        self.column_widths = state.column_widths;
+        self.sort_items();
+        self.units = Units { gas: state.gas_unit, data: state.data_unit };
+        if state.window < self.config.windows.len() {
+            self.window = state.window;
+        }
+        self.show_network_logs = state.show_network_logs;
+        self.hourly_history = state.hourly_history;
+        self.state.select(Some(0));
+        self.reselect(state.selected);
+        self.view = match state.view {
+            View::Detail(name) if !self.items.iter().any(|item| item.name == name) => View::Table,
+            view => view,
+        };
+    }
+
+    /// Whether the metrics are stored in the history. Replayed blocks would distort it, and a
+    /// mirrored session is stored by the instance sharing it.
+    fn records_history(&self) -> bool {
+        !matches!(self.config.mode.as_str(), "demo" | "replay" | "attach")
+    }
+
+    fn ui_state(&self) -> UiState {
+        UiState {
+            view: self.view.clone(),
+            selected: self.selected_item().map(|item| item.name.clone()),
+            hide_testnets: self.hide_testnets,
+            grouped: self.grouped,
+            collapsed_groups: self.collapsed_groups.clone(),
+            sort: self.sort,
+            hidden_columns: self.hidden_columns.clone(),
+            column_widths: self.column_widths.clone(),
+            gas_unit: self.units.gas,
+            data_unit: self.units.data,
+            window: self.window,
+            show_network_logs: self.show_network_logs,
+            hourly_history: self.hourly_history,
+        }
+    }
+
+    /// Adds a row for the network, or updates the row with the same label in place, keeping its
+    /// metrics, history and pause state
+    fn add_network(&mut self, network: NetworkInfo) {
+        let item = match self.items.iter().position(|item| item.name == network.label) {
+            Some(i) => &mut self.items[i],
+            None => {
+                let item = NetworkMetrics { name: network.label.clone(), ..Default::default() };
+                self.items.push(item);
+                self.items.last_mut().unwrap()
+            }
+        };
+        item.labels = self.address_book.for_network(&network.name);
+        item.adjusted = network.adjusted;
+        item.group = network.group.or_else(|| network.stack.clone());
+        item.stack = network.stack;
+        item.testnet = network.testnet;
+        item.index = network.index;
+        item.source = network.source;
+        item.explorer = network.explorer;
+        item.time_zone = network.time_zone;
+        item.gas_token = network.gas_token;
+        let width = UnicodeWidthStr::width(item.display_name().as_str()) as u16;
+        // group headers read `▾ <group> (<networks>)`
+        let header = UnicodeWidthStr::width(item.group_name()) as u16 + 7;
+        let left_totals = !item.in_totals() && self.latest.remove(&network.label).is_some();
+        self.longest_name = self.longest_name.max(width).max(header);
+        if left_totals {
+            self.update_totals();
+        }
+    }
+
+    /// Networks shown in the table, in the order of the items
+    fn visible_items(&self) -> impl Iterator<Item = &NetworkMetrics> {
+        self.items.iter().filter(|item| !(self.hide_testnets && item.testnet))
+    }
+
+    /// Labels of the custom metrics of the networks shown, each a column after the others
+    fn custom_columns(&self) -> Vec<&str> {
+        let mut labels = Vec::new();
+        for custom in self.visible_items().flat_map(|item| item.custom.iter()) {
+            if !labels.contains(&custom.label.as_str()) {
+                labels.push(custom.label.as_str());
+            }
         }
+        labels
+    }
+
+    /// Formatting of a network's cell by the color rules matching it
+    fn cell_format(&self, data: &NetworkMetrics, column: CellColumn) -> Option<CellFormat> {
+        cell_format(&self.config.color_rules, &data.name, column, |metric| match metric {
+            // the TPS of a row that stopped updating isn't compared
+            ThresholdMetric::TpsBaseline => match data.is_stale() {
+                true => None,
+                false => baseline::ratio(&self.time_series, &data.name, data.metrics.tps),
+            },
+            metric => data.metric_value(metric),
+        })
+    }
+
+    /// Rows of the table, the networks being listed under a header per group if grouped
+    fn table_rows(&self) -> Vec<TableRow<'_>> {
+        if !self.grouped {
+            return self.visible_items().map(TableRow::Network).collect();
+        }
+        let items = self.visible_items().collect::<Vec<_>>();
+        let mut rows = Vec::new();
+        for members in items.chunk_by(|a, b| a.group_name() == b.group_name()) {
+            let name = members[0].group_name();
+            let mut subtotals = Metrics::default();
+            for item in members.iter().filter(|item| !item.index) {
+                subtotals.add(&item.metrics);
+            }
+            let collapsed = self.collapsed_groups.contains(name);
+            rows.push(TableRow::Group { name, subtotals, networks: members.len(), collapsed });
+            if !collapsed {
+                rows.extend(members.iter().map(|item| TableRow::Network(item)));
+            }
+        }
+        rows
+    }
+
+    fn selected_row(&self) -> Option<TableRow<'_>> {
+        self.table_rows().into_iter().nth(self.state.selected()?)
+    }
+
+    /// The network of the selected row, `None` if a group header is selected
+    fn selected_item(&self) -> Option<&NetworkMetrics> {
+        match self.selected_row()? {
+            TableRow::Network(item) => Some(item),
+            TableRow::Group { .. } => None,
+        }
+    }
+
+    /// Selects the row of the network if shown, otherwise keeping the selection within the rows
+    fn reselect(&mut self, name: Option<String>) {
+        let selected = {
+            let rows = self.table_rows();
+            let row = name.and_then(|name| rows.iter().position(|row| row.is_network(&name)));
+            let last = rows.len().saturating_sub(1);
+            row.or(self.state.selected()).map(|i| i.min(last))
+        };
+        self.state.select(selected);
     }
 
     pub fn next(&mut self) {
-        let i =
-            self.state.selected().map_or(0, |i| (i + 1).min(self.items.len().saturating_sub(1)));
+        let last = self.table_rows().len().saturating_sub(1);
+        let i = self.state.selected().map_or(0, |i| (i + 1).min(last));
         self.state.select(Some(i));
     }
 
+    /// Sorts the rows, mainnets first and grouped if enabled. A frozen order is kept, moving
+    /// networks added since ahead of the testnets.
+    fn sort_items(&mut self) {
+        self.sort_by_column();
+        // the sort is stable, keeping the order within each group
+        if self.grouped {
+            self.items.sort_by(|a, b| {
+                let key = |item: &NetworkMetrics| (item.testnet, item.group.is_none());
+                key(a).cmp(&key(b)).then_with(|| a.group_name().cmp(b.group_name()))
+            });
+        }
+    }
+
+    fn sort_by_column(&mut self) {
+        let Sort { column, descending, frozen } = self.sort;
+        if frozen {
+            self.items.sort_by_key(|item| item.testnet);
+            return;
+        }
+        self.items.sort_by(|a, b| {
+            let order = match column {
+                Column::Network => {
+                    a.display_name().to_lowercase().cmp(&b.display_name().to_lowercase())
+                }
+                Column::Block => a.block.cmp(&b.block),
+                Column::Gas => a.metrics.gps.total_cmp(&b.metrics.gps),
+                Column::Data => a.metrics.dps.total_cmp(&b.metrics.dps),
+                _ => a.metrics.tps.total_cmp(&b.metrics.tps),
+            };
+            a.testnet.cmp(&b.testnet).then(if descending { order.reverse() } else { order })
+        });
+    }
+
+    /// Changes the order of the rows, keeping the selected network selected
+    fn set_sort(&mut self, sort: Sort) {
+        let selected = self.selected_item().map(|item| item.name.clone());
+        self.sort = sort;
+        self.sort_items();
+        self.reselect(selected);
+    }
+
+    /// Lists the networks under their groups or back in a single list, keeping the selected
+    /// network selected
+    fn toggle_grouped(&mut self) {
+        let selected = self.selected_item().map(|item| item.name.clone());
+        self.grouped = !self.grouped;
+        self.sort_items();
+        self.reselect(selected);
+    }
+
+    /// Hides or shows the networks of a group, its header staying in place
+    fn toggle_group(&mut self, name: String) {
+        if !self.collapsed_groups.remove(&name) {
+            self.collapsed_groups.insert(name);
+        }
+        self.reselect(None);
+    }
+
+    fn set_column_hidden(&mut self, column: Column, hidden: bool) -> String {
+        if column == Column::Network {
+            return "the network column can't be hidden".to_string();
+        }
+        self.hidden_columns.retain(|&c| c != column);
+        if hidden {
+            self.hidden_columns.push(column);
+            format!("hid the {} column", column.name())
+        } else {
+            format!("showing the {} column", column.name())
+        }
+    }
+
+    /// Columns shown in the table, in display order
+    fn visible_columns(&self) -> Vec<Column> {
+        Column::ALL.into_iter().filter(|column| !self.hidden_columns.contains(column)).collect()
+    }
+
+    /// Width of the column as resized, or its default sized to fit
+    fn column_constraint(&self, column: Column) -> Constraint {
+        match self.column_widths.get(&column) {
+            Some(&width) => Constraint::Length(width),
+            None => column.constraint(self.longest_name),
+        }
+    }
+
+    /// Focuses the next or previous visible column for resizing
+    fn move_column_focus(&mut self, forward: bool) {
+        let columns = self.visible_columns();
+        let current = self
+            .focused_column
+            .and_then(|focused| columns.iter().position(|&column| column == focused));
+        let i = match (current, forward) {
+            (None, _) => 0,
+            (Some(i), true) => (i + 1) % columns.len(),
+            (Some(i), false) => (i + columns.len() - 1) % columns.len(),
+        };
+        self.focused_column = Some(columns[i]);
+    }
+
+    /// Widens or narrows the focused column, starting from its width as drawn
+    fn resize_column(&mut self, delta: i16) {
+        let column = self.focused_column.unwrap_or(Column::Network);
+        let drawn = self.drawn_widths.iter().find(|(drawn, _)| *drawn == column);
+        let Some(width) = self.column_widths.get(&column).or(drawn.map(|(_, width)| width)) else {
+            return;
+        };
+        let width = width.saturating_add_signed(delta).max(MIN_COLUMN_WIDTH);
+        self.column_widths.insert(column, width);
+    }
+
+    fn toggle_testnets(&mut self) {
+        let selected = self.selected_item().map(|item| item.name.clone());
+        self.hide_testnets = !self.hide_testnets;
+        self.reselect(selected);
+    }
+
     pub fn previous(&mut self) {
         let i = self.state.selected().map_or(0, |i| i.saturating_sub(1));
         self.state.select(Some(i));
     }
 
+    pub fn next_alert(&mut self) {
+        let last = self.alerts.len().saturating_sub(1);
+        let i = self.alerts_state.selected().map_or(0, |i| (i + 1).min(last));
+        self.alerts_state.select(Some(i));
+    }
+
+    pub fn previous_alert(&mut self) {
+        let i = self.alerts_state.selected().map_or(0, |i| i.saturating_sub(1));
+        self.alerts_state.select(Some(i));
+    }
+
     pub fn update(&mut self, message: BlockMessage) {
         match message {
+            BlockMessage::AddNetwork(network) => self.add_network(network),
+            BlockMessage::RemoveNetwork(label) => self.drop_network(&label),
+            BlockMessage::RenameNetwork { from, to } => self.rename_network(&from, &to),
             BlockMessage::UpdateNetwork(nm) => {
-                let metrics = Metrics { tps: nm.tps, gps: nm.gps, dps: nm.dps };
-                self.latest.insert(nm.network.clone(), metrics.clone());
+                let metrics = match nm.windows.get(self.window) {
+                    Some(window) => window.into(),
+                    None => Metrics { tps: nm.tps, gps: nm.gps, dps: nm.dps, da: nm.da },
+                };
+                let Some(in_totals) =
+                    self.items.iter().find(|d| d.name == nm.network).map(|d| d.in_totals())
+                else {
+                    // sent by a removed network before it stopped
+                    return;
+                };
+                if in_totals {
+                    let mut metrics = metrics.clone();
+                    // the blobs of an L1 hold the data of the L2s, already counted by their
+                    // estimates
+                    if nm.blobs.is_some() {
+                        metrics.da = 0.0;
+                    }
+                    self.latest.insert(nm.network.clone(), metrics);
+                }
+                let sample = Aggregate::sample(nm.tps, nm.gps, nm.dps, nm.da);
+                if self.records_history() {
+                    match self.time_series.record(&nm.network, sample) {
+                        Ok(Some(json)) => self.unsaved_history = Some(json),
+                        Ok(None) => {}
+                        Err(err) => warn!("failed to save metrics history: {err}"),
+                    }
+                }
                 if let Some(data) = self.items.iter_mut().find(|d| d.name == nm.network) {
                     data.block = nm.block;
+                    data.session = nm.session;
+                    data.tx_gas = nm.tx_gas;
+                    data.top_contracts = nm.top_contracts;
+                    data.builders = nm.builders;
+                    data.top_selectors = nm.top_selectors;
+                    data.bridge = nm.bridge;
+                    data.mints = nm.mints;
+                    data.blobs = nm.blobs;
+                    data.l1_freshness = nm.l1_freshness;
+                    data.base_fees = nm.base_fees;
+                    data.base_fee = nm.base_fee;
+                    data.priority_fee = nm.priority_fee;
+                    data.value = nm.value;
+                    data.inbox = nm.inbox;
+                    data.custom = nm.custom;
+                    data.zk_batches = nm.zk_batches;
+                    data.verification = nm.verification;
+                    data.endpoint = nm.endpoint;
+                    data.budget = nm.budget;
+                    data.polling = nm.polling;
+                    data.recent_blocks = nm.recent_blocks;
+                    data.block_time = nm.block_time;
+                    data.block_age = nm.block_age;
+                    data.tx_samples = nm.tx_samples;
+                    data.windows = nm.windows;
+                    data.history.record(&metrics);
+                    data.updated = Some(Instant::now());
                     data.metrics = metrics.clone();
                 }
-                self.items.sort_by(|a, b| {
-                    b.metrics.tps.partial_cmp(&a.metrics.tps).unwrap_or(std::cmp::Ordering::Equal)
-                });
-                self.totals = self.latest.values().fold(Metrics::default(), |mut acc, metrics| {
-                    acc.gps += metrics.gps;
-                    acc.tps += metrics.tps;
-                    acc.dps += metrics.dps;
-                    acc
-                });
+                self.last_update = Some(Instant::now());
+                self.sort_items();
+                self.update_totals();
+                self.totals_history.record(&self.totals);
+            }
+            BlockMessage::Event(event) => {
+                if let EventKind::Threshold { rule, cleared, .. } = &event.kind {
+                    if let Some(data) = self.items.iter_mut().find(|d| d.name == event.network) {
+                        data.firing.retain(|firing| firing != rule);
+                        if !cleared {
+                            data.firing.push(rule.clone());
+                        }
+                    }
+                }
+                self.alerts.push(Alert::from_event(&event));
+                // alerts are listed newest first, keep the selection on the same alert
+                if let Some(i) = self.alerts_state.selected() {
+                    self.alerts_state.select(Some((i + 1).min(self.alerts.len() - 1)));
+                }
+                if self.events.len() == MAX_EVENTS {
+                    self.events.pop_front();
+                }
+                self.events.push_back(event);
+            }
+            BlockMessage::Health(health) => {
+                if let Some(data) = self.items.iter_mut().find(|d| d.name == health.network) {
+                    data.health = Some(health);
+                }
+            }
+            BlockMessage::Log(log) => {
+                if self.network_logs.len() == MAX_NETWORK_LOGS {
+                    self.network_logs.pop_front();
+                }
+                self.network_logs.push_back(log);
+                // keep the scrolled back lines in view as new lines arrive
+                if self.network_log_scroll > 0 {
+                    self.network_log_scroll =
+                        (self.network_log_scroll + 1).min(self.network_logs.len() - 1);
+                }
+            }
+            // blocks are only recorded, their metrics arrive with the network updates
+            BlockMessage::Block(_) => {}
+        }
+    }
+
+    fn update_totals(&mut self) {
+        self.totals = self.latest.values().fold(Metrics::default(), |mut acc, metrics| {
+            acc.add(metrics);
+            acc
+        });
+    }
+
+    /// Number of metrics windows the networks report
+    fn window_count(&self) -> usize {
+        self.items.iter().map(|item| item.windows.len()).max().unwrap_or_default()
+    }
+
+    /// Displays the metrics of the next window, wrapping around to the main window
+    fn cycle_window(&mut self) {
+        self.set_window((self.window + 1) % self.window_count().max(1));
+    }
+
+    fn set_window(&mut self, window: usize) {
+        self.window = window;
+        for item in &mut self.items {
+            if let Some(metrics) = item.windows.get(window) {
+                item.metrics = metrics.into();
+                if let Some(latest) = self.latest.get_mut(&item.name) {
+                    *latest = item.metrics.clone();
+                }
+            }
+        }
+        self.update_totals();
+    }
+
+    /// Length of the displayed window, e.g. `5m`
+    fn window_label(&self) -> Option<String> {
+        let seconds = self.items.iter().find_map(|item| item.windows.get(self.window))?.seconds;
+        Some(match seconds {
+            seconds if seconds % 3600 == 0 => format!("{}h", seconds / 3600),
+            seconds if seconds % 60 == 0 => format!("{}m", seconds / 60),
+            seconds => format!("{seconds}s"),
+        })
+    }
+
+    /// Opens the detail view of the selected network, or collapses or expands the selected group
+    pub fn open_detail(&mut self) {
+        match self.selected_row() {
+            Some(TableRow::Network(item)) => {
+                self.view = View::Detail(item.name.clone());
+                self.selected_tx = None;
+            }
+            Some(TableRow::Group { name, .. }) => self.toggle_group(name.to_string()),
+            None => {}
+        }
+    }
+
+    /// The network shown in the detail view
+    fn detail_item(&self) -> Option<&NetworkMetrics> {
+        let View::Detail(name) = &self.view else {
+            return None;
+        };
+        self.items.iter().find(|item| &item.name == name)
+    }
+
+    fn move_tx_selection(&mut self, down: bool) {
+        let Some(samples) = self.detail_item().map(|item| &item.tx_samples) else {
+            return;
+        };
+        let position =
+            self.selected_tx.and_then(|hash| samples.iter().position(|tx| tx.hash == hash));
+        let i = match position {
+            Some(i) if down => (i + 1).min(samples.len() - 1),
+            Some(i) => i.saturating_sub(1),
+            None => 0,
+        };
+        self.selected_tx = samples.get(i).map(|tx| tx.hash);
+    }
+
+    fn copy_selected_tx(&mut self) {
+        let message = match self.selected_tx {
+            Some(hash) => match terminal::copy_to_clipboard(&hash.to_string()) {
+                Ok(()) => format!("copied {hash}"),
+                Err(err) => format!("failed to copy: {err}"),
+            },
+            None => "select a transaction with ↑↓ first".to_string(),
+        };
+        self.console_message = Some(message);
+    }
+
+    fn open_selected_tx(&mut self) {
+        let Some(item) = self.detail_item() else {
+            return;
+        };
+        let message = match (self.selected_tx, &item.explorer) {
+            (None, _) => "select a transaction with ↑↓ first".to_string(),
+            (Some(_), None) => format!("no explorer configured for {}", item.name),
+            (Some(hash), Some(explorer)) => {
+                let url = format!("{}/tx/{hash}", explorer.trim_end_matches('/'));
+                match terminal::open_url(&url) {
+                    Ok(()) => format!("opened {url}"),
+                    Err(err) => format!("failed to open {url}: {err}"),
+                }
+            }
+        };
+        self.console_message = Some(message);
+    }
+
+    /// Runs a console command, returning whether the app should quit
+    fn run_command(&mut self, input: &str) -> bool {
+        let message = match input.parse::<ConsoleCommand>() {
+            Ok(ConsoleCommand::Quit) => return true,
+            Ok(ConsoleCommand::Help) => HELP.to_string(),
+            Ok(ConsoleCommand::Window(windows)) => {
+                match self.send_control(Control::SetWindows(windows.clone())) {
+                    Ok(()) => {
+                        let seconds = windows.iter().map(|w| format!("{w}s")).collect::<Vec<_>>();
+                        self.config.windows = windows;
+                        // the windows of the networks change with their next update
+                        self.set_window(0);
+                        format!("windows set to {}", seconds.join(", "))
+                    }
+                    Err(err) => err,
+                }
+            }
+            Ok(ConsoleCommand::Add { url, label }) => {
+                let label = label.unwrap_or_else(|| url_authority(&url).to_string());
+                let network = Network {
+                    name: label.to_lowercase().replace(' ', "-"),
+                    label: label.clone(),
+                    http: url,
+                    ..Default::default()
+                };
+                match self.send_control(Control::AddNetwork(Box::new(network))) {
+                    Ok(()) => format!("adding {label}"),
+                    Err(err) => err,
+                }
             }
-            BlockMessage::Log(_) => {}
+            Ok(ConsoleCommand::Pause(network)) => self.set_paused(&network, true),
+            Ok(ConsoleCommand::Resume(network)) => self.set_paused(&network, false),
+            Ok(ConsoleCommand::Remove(network)) => self.remove_network(&network),
+            Ok(ConsoleCommand::Hide(column)) => self.set_column_hidden(column, true),
+            Ok(ConsoleCommand::Show(column)) => self.set_column_hidden(column, false),
+            Ok(ConsoleCommand::ResetWidths) => {
+                self.column_widths.clear();
+                "restored the column widths".to_string()
+            }
+            Ok(ConsoleCommand::Note(args)) => self.set_note(&args),
+            Ok(ConsoleCommand::Mark(label)) => {
+                let at = Utc::now();
+                let message =
+                    format!("marked {label} at {}", at.with_timezone(&Local).format("%H:%M:%S"));
+                self.markers.push(Marker { at, instant: Instant::now(), label });
+                message
+            }
+            Ok(ConsoleCommand::DumpConfig { path }) => {
+                if let Some(supervisor) = &self.supervisor {
+                    self.config.networks = supervisor.networks.lock().unwrap().clone();
+                }
+                match self.config.write(path) {
+                    Ok(path) => format!("configuration written to {path}"),
+                    Err(err) => format!("config dump failed: {err}"),
+                }
+            }
+            Ok(ConsoleCommand::ExportCsv { path }) => match self.export_csv(path) {
+                Ok(path) => match self.export_markers(&path) {
+                    Ok(Some(markers)) => format!("exported to {path} and {markers}"),
+                    Ok(None) => format!("exported to {path}"),
+                    Err(err) => format!("exported to {path}, exporting markers failed: {err}"),
+                },
+                Err(err) => format!("export failed: {err}"),
+            },
+            Err(err) => err,
+        };
+        self.console_message = Some(message);
+        false
+    }
+
+    /// Pauses or resumes the network whose label matches case-insensitively
+    fn set_paused(&mut self, network: &str, paused: bool) -> String {
+        let Some(item) = self.items.iter().find(|item| item.name.eq_ignore_ascii_case(network))
+        else {
+            return format!("unknown network `{network}`");
+        };
+        let label = item.name.clone();
+        if let Err(err) = self.send_control(Control::SetPaused(label.clone(), paused)) {
+            return err;
+        }
+        if let Some(item) = self.items.iter_mut().find(|item| item.name == label) {
+            item.paused = paused;
+        }
+        format!("{} {label}", if paused { "paused" } else { "resumed" })
+    }
+
+    /// Sets the note of the network whose label starts `args` case-insensitively, the longest
+    /// matching label winning, to the rest of `args`
+    fn set_note(&mut self, args: &str) -> String {
+        let Some(label) = self
+            .items
+            .iter()
+            .map(|item| item.name.as_str())
+            .filter(|label| {
+                args.get(..label.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(label))
+                    && !args[label.len()..].starts_with(|c: char| !c.is_whitespace())
+            })
+            .max_by_key(|label| label.len())
+            .map(String::from)
+        else {
+            return format!("no network matching `{args}`");
+        };
+        let note = args[label.len()..].trim();
+        match self.notes.set(&label, note) {
+            Ok(()) if note.is_empty() => format!("cleared note of {label}"),
+            Ok(()) => format!("noted on {label}"),
+            Err(err) => format!("failed to save note: {err}"),
+        }
+    }
+
+    /// Stops polling a network and drops its row, its metrics leaving the totals
+    fn remove_network(&mut self, network: &str) -> String {
+        let Some(item) = self.items.iter().find(|item| item.name.eq_ignore_ascii_case(network))
+        else {
+            return format!("unknown network `{network}`");
+        };
+        let label = item.name.clone();
+        if let Err(err) = self.send_control(Control::Remove(label.clone())) {
+            return err;
+        }
+        self.drop_network(&label);
+        format!("removed {label}")
+    }
+
+    /// Drops the row and metrics of a network no longer polled
+    fn drop_network(&mut self, label: &str) {
+        if !self.items.iter().any(|item| item.name == label) {
+            return;
+        }
+        self.items.retain(|item| item.name != label);
+        self.latest.remove(label);
+        self.update_totals();
+        self.reselect(None);
+    }
+
+    /// Moves the row, note and history of a network to its new label, keeping its place
+    fn rename_network(&mut self, from: &str, to: &str) {
+        let Some(item) = self.items.iter_mut().find(|item| item.name == from) else {
+            return;
+        };
+        item.name = to.to_string();
+        if let Some(metrics) = self.latest.remove(from) {
+            self.latest.insert(to.to_string(), metrics);
+        }
+        self.time_series.rename(from, to);
+        if let Err(err) = self.notes.rename(from, to) {
+            warn!("failed to move the note of {from} to {to}: {err}");
+        }
+    }
+
+    /// Opens the console to remove the selected network, confirmed with Enter
+    fn prompt_remove_selected(&mut self) {
+        if let Some(item) = self.selected_item() {
+            self.console = Some(format!("remove {}", item.name));
+        }
+    }
+
+    /// Toggles polling of the selected network
+    fn toggle_pause_selected(&mut self) {
+        if let Some(item) = self.selected_item() {
+            let (label, paused) = (item.name.clone(), !item.paused);
+            self.console_message = Some(self.set_paused(&label, paused));
         }
     }
+
+    fn send_control(&self, control: Control) -> Result<(), String> {
+        let Some(supervisor) = &self.supervisor else {
+            return Err(
+                "not available when watching a remote collector, replaying or attached".to_string()
+            );
+        };
+        supervisor.control.try_send(control).map_err(|err| format!("failed: {err}"))
+    }
+
+    /// Writes the current metrics of every network to a CSV file, returning its path
+    fn export_csv(&self, path: Option<String>) -> eyre::Result<String> {
+        let path = path.unwrap_or_else(|| {
+            format!("exports/rollups-{}.csv", Local::now().format("%Y%m%d-%H%M%S"))
+        });
+        if let Some(dir) = Path::new(&path).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = BufWriter::new(File::create(&path)?);
+        writeln!(
+            file,
+            "network,block,tps,gas_per_second,bytes_per_second,da_bytes_per_second,value_per_second,note"
+        )?;
+        for item in &self.items {
+            let Metrics { tps, gps, dps, da } = item.metrics;
+            let note = csv_field(self.notes.get(&item.name).unwrap_or_default());
+            let value = item.value.map_or(String::new(), |value| format!("{value:.0}"));
+            writeln!(
+                file,
+                "{},{},{tps:.2},{gps:.0},{dps:.0},{da:.0},{value},{note}",
+                csv_field(&item.name),
+                item.block
+            )?;
+        }
+        file.flush()?;
+        Ok(path)
+    }
+
+    /// Writes the markers next to the CSV export at `path`, returning the markers file path if
+    /// there are any
+    fn export_markers(&self, path: &str) -> eyre::Result<Option<String>> {
+        if self.markers.is_empty() {
+            return Ok(None);
+        }
+        let path = Path::new(path);
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let path = path.with_file_name(format!("{stem}-markers.csv"));
+        let mut file = BufWriter::new(File::create(&path)?);
+        writeln!(file, "time,label")?;
+        for marker in &self.markers {
+            writeln!(file, "{},{}", marker.at.to_rfc3339(), csv_field(&marker.label))?;
+        }
+        file.flush()?;
+        Ok(Some(path.display().to_string()))
+    }
+
+    /// Session totals of the mainnets
+    fn session_totals(&self) -> SessionTotals {
+        self.items.iter().filter(|item| item.in_totals()).fold(
+            SessionTotals::default(),
+            |mut acc, item| {
+                acc += item.session;
+                acc
+            },
+        )
+    }
 }
 
-pub async fn tui(networks: Vec<Network>, mut rx: Receiver<BlockMessage>) -> eyre::Result<()> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+pub async fn tui(
+    config: EffectiveConfig,
+    address_book: AddressBook,
+    alert_router: AlertRouter,
+    log_tail: LogTail,
+    supervisor: Option<SupervisorHandle>,
+    mut rx: Receiver<BlockMessage>,
+) -> eyre::Result<()> {
+    let mut screen = Screen::enter(config.passive)?;
 
-    let app = Arc::new(Mutex::new(App::new(networks)));
+    let app = Arc::new(Mutex::new(App::new(config, address_book, log_tail, supervisor)));
     let app_clone = app.clone();
     tokio::spawn(async move {
         while let Some(message) = rx.recv().await {
-            let mut app = app_clone.lock().unwrap();
-            app.update(message);
+            if let BlockMessage::Event(event) = &message {
+                alert_router.route(&Alert::from_event(event));
+            }
+            let history = {
+                let mut app = app_clone.lock().unwrap();
+                let started = Instant::now();
+                app.update(message);
+                app.frame_stats.add_update(started.elapsed());
+                app.unsaved_history.take()
+            };
+            if let Some(json) = history {
+                let saved = task::spawn_blocking(move || TimeSeries::write(&json)).await;
+                if let Err(err) = saved.map_err(eyre::Report::from).and_then(|saved| saved) {
+                    warn!("failed to save metrics history: {err}");
+                }
+            }
         }
     });
 
-    let res = run_app(&mut terminal, app).await;
+    let res = run_app(&mut screen.terminal, app.clone()).await;
 
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
-    terminal.show_cursor()?;
+    screen.leave()?;
 
     if let Err(err) = res {
         println!("{err:?}");
     }
 
+    let supervisor = app.lock().unwrap().supervisor.clone();
+    if let Some(supervisor) = supervisor {
+        supervisor.shutdown(FLUSH_TIMEOUT).await;
+    }
+    let app = app.lock().unwrap();
+    if app.records_history() {
+        if let Err(err) = app.time_series.save() {
+            warn!("failed to save metrics history: {err}");
+        }
+    }
+    // a mirror would overwrite the UI state of the session it mirrors
+    if app.config.mode != "attach" {
+        if let Err(err) = app.ui_state().save() {
+            warn!("failed to save UI state: {err}");
+        }
+    }
+    print_summary(&app);
     Ok(())
 }
 
+/// Prints the cumulative per-network counters once the TUI has exited
+fn print_summary(app: &App) {
+    let width = (app.longest_name as usize).max("Network".len());
+    println!("Session summary ({})", format_elapsed(app.started.elapsed()));
+    println!("{:<width$} {:>10} {:>10} {:>10} {:>10}", "Network", "Blocks", "Txs", "Gas", "Data");
+    let mut items = app.items.iter().collect::<Vec<_>>();
+    items.sort_by_key(|item| Reverse(item.session.txs));
+    let total = ("Total".to_string(), app.session_totals());
+    for (name, session) in
+        items.into_iter().map(|item| (item.name.clone(), item.session)).chain([total])
+    {
+        println!(
+            "{name:<width$} {:>10} {:>10} {:>10} {:>10}",
+            format_count(session.blocks),
+            format_count(session.txs),
+            format_count(session.gas),
+            format_bytes(session.data),
+        );
+    }
+}
+
 async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: Arc<Mutex<App>>) -> io::Result<()> {
-    let mut interval = time::interval(Duration::from_millis(25));
+    let mut interval = time::interval(POLL_INTERVAL);
 
     loop {
+        let started = Instant::now();
+        let mut lock_wait = Duration::ZERO;
         terminal.draw(|f| {
             let mut app = app.lock().unwrap();
+            lock_wait = started.elapsed();
             ui(f, &mut app)
         })?;
+        let draw = started.elapsed() - lock_wait;
+        // frames are drawn less often while slow, keys still being handled as they come
+        let poll = {
+            let mut app = app.lock().unwrap();
+            app.frame_stats.record(lock_wait, draw);
+            if app.frame_stats.degraded() {
+                DEGRADED_POLL_INTERVAL
+            } else {
+                POLL_INTERVAL
+            }
+        };
 
-        if event::poll(Duration::from_millis(25))? {
+        if event::poll(poll)? {
             if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
+                if terminal::is_handled(&key) {
                     let mut app = app.lock().unwrap();
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
-                        KeyCode::Char('j') | KeyCode::Down => app.next(),
-                        KeyCode::Char('k') | KeyCode::Up => app.previous(),
-                        _ => {}
+                    if handle_key(&mut app, key) {
+                        return Ok(());
                     }
                 }
             }
@@ -183,6 +1490,101 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: Arc<Mutex<App>>) -
     }
 }
 
+/// Handles a key press, returning whether the app should quit
+fn handle_key(app: &mut App, key: KeyEvent) -> bool {
+    let code = key.code;
+    if let Some(input) = &mut app.console {
+        match code {
+            KeyCode::Enter => {
+                let input = app.console.take().unwrap_or_default();
+                return app.run_command(&input);
+            }
+            KeyCode::Esc => app.console = None,
+            KeyCode::Backspace if input.is_empty() => app.console = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+        return false;
+    }
+    app.console_message = None;
+    if app.show_keys {
+        app.show_keys = false;
+        return false;
+    }
+    match (&app.view, code) {
+        (_, KeyCode::Char(':')) => app.console = Some(String::new()),
+        (View::Table, KeyCode::Char('?')) => app.show_keys = true,
+        (_, KeyCode::Char('q')) | (View::Table, KeyCode::Esc) => return true,
+        (_, KeyCode::Char('g')) => app.units.gas = app.units.gas.next(),
+        (_, KeyCode::Char('d')) => app.units.data = app.units.data.next(),
+        (_, KeyCode::Char('F')) => app.show_frame_stats = !app.show_frame_stats,
+        (View::Table, KeyCode::Enter) => app.open_detail(),
+        (View::Table, KeyCode::Char('p')) => app.toggle_pause_selected(),
+        (View::Table, KeyCode::Char('x')) => app.prompt_remove_selected(),
+        (View::Table, KeyCode::Char('n')) => app.console = Some("add ".to_string()),
+        (View::Table, KeyCode::Char('t')) => app.toggle_testnets(),
+        (View::Table, KeyCode::Char('G')) => app.toggle_grouped(),
+        (View::Table, KeyCode::Left | KeyCode::Right)
+            if key.modifiers.contains(KeyModifiers::SHIFT) =>
+        {
+            app.resize_column(if code == KeyCode::Right { 1 } else { -1 })
+        }
+        (View::Table, KeyCode::Left) => app.move_column_focus(false),
+        (View::Table, KeyCode::Right) => app.move_column_focus(true),
+        (View::Table, KeyCode::Char('s')) => app.set_sort(app.sort.next_column()),
+        (View::Table, KeyCode::Char('o')) => {
+            app.set_sort(Sort { descending: !app.sort.descending, frozen: false, ..app.sort })
+        }
+        (View::Table, KeyCode::Char('f')) => {
+            app.set_sort(Sort { frozen: !app.sort.frozen, ..app.sort })
+        }
+        (View::Table, KeyCode::Char('w')) => app.cycle_window(),
+        (View::Table, KeyCode::Char('e')) => app.view = View::Events,
+        (View::Table, KeyCode::Char('a')) => app.view = View::Alerts,
+        (View::Table, KeyCode::Char('l')) => app.view = View::Logs,
+        (View::Table, KeyCode::Char('r')) => app.view = View::Endpoints,
+        (View::Detail(_), KeyCode::Char('j') | KeyCode::Down) => app.move_tx_selection(true),
+        (View::Detail(_), KeyCode::Char('k') | KeyCode::Up) => app.move_tx_selection(false),
+        (View::Detail(_), KeyCode::Char('y')) => app.copy_selected_tx(),
+        (View::Detail(_), KeyCode::Char('o')) => app.open_selected_tx(),
+        (View::Table | View::Detail(_), KeyCode::Char('m')) => {
+            app.console = Some("mark ".to_string());
+        }
+        (View::Endpoints, KeyCode::Char('c')) => app.show_capabilities = !app.show_capabilities,
+        (View::Table | View::Detail(_), KeyCode::Char('h')) => {
+            app.hourly_history = !app.hourly_history;
+        }
+        (View::Table, KeyCode::Char('L')) => app.show_network_logs = !app.show_network_logs,
+        (View::Table, KeyCode::PageUp) if app.show_network_logs => {
+            let last = app.network_logs.len().saturating_sub(1);
+            app.network_log_scroll = (app.network_log_scroll + NETWORK_LOG_SCROLL).min(last);
+        }
+        (View::Table, KeyCode::PageDown) if app.show_network_logs => {
+            app.network_log_scroll = app.network_log_scroll.saturating_sub(NETWORK_LOG_SCROLL);
+        }
+        (View::Table, KeyCode::Char('j') | KeyCode::Down) => app.next(),
+        (View::Table, KeyCode::Char('k') | KeyCode::Up) => app.previous(),
+        (View::Alerts, KeyCode::Char('j') | KeyCode::Down) => app.next_alert(),
+        (View::Alerts, KeyCode::Char('k') | KeyCode::Up) => app.previous_alert(),
+        (View::Alerts, KeyCode::Enter | KeyCode::Char(' ')) => {
+            if let Some(i) = app.alerts_state.selected() {
+                app.alerts.toggle_acknowledged(i);
+            }
+        }
+        (View::Alerts, KeyCode::Char('A')) => app.alerts.acknowledge_all(),
+        (View::Alerts, KeyCode::Char('c')) => {
+            app.alerts.clear_acknowledged();
+            app.alerts_state.select(None);
+        }
+        (_, KeyCode::Esc | KeyCode::Backspace) => app.view = View::Table,
+        _ => {}
+    }
+    false
+}
+
 fn ui(f: &mut Frame, app: &mut App) {
     let outer_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -208,107 +1610,1105 @@ fn ui(f: &mut Frame, app: &mut App) {
     let inner_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(6), // Total
+            Constraint::Length(1), // Status bar
+            Constraint::Length(9), // Total
             Constraint::Min(0),    // Main table
             Constraint::Length(3), // Footer
         ])
         .split(content_area);
 
-    render_totals(f, app, inner_layout[0]);
-    render_table(f, app, inner_layout[1]);
-    render_footer(f, app, inner_layout[2]);
+    render_status_bar(f, app, inner_layout[0]);
+    render_totals(f, app, inner_layout[1]);
+    match app.view {
+        View::Table if app.show_network_logs => {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(NETWORK_LOG_HEIGHT)])
+                .split(inner_layout[2]);
+            render_table(f, app, layout[0]);
+            render_network_logs(f, app, layout[1]);
+        }
+        View::Table => render_table(f, app, inner_layout[2]),
+        View::Detail(_) => render_detail(f, app, inner_layout[2]),
+        View::Events => render_events(f, app, inner_layout[2]),
+        View::Alerts => render_alerts(f, app, inner_layout[2]),
+        View::Logs => render_logs(f, app, inner_layout[2]),
+        View::Endpoints if app.show_capabilities => render_capabilities(f, app, inner_layout[2]),
+        View::Endpoints => render_endpoints(f, app, inner_layout[2]),
+    }
+    render_footer(f, app, inner_layout[3]);
+    if app.show_frame_stats {
+        render_frame_stats(f, app, outer_layout[0]);
+    }
+    if app.show_keys {
+        render_keys(f, app, outer_layout[0]);
+    }
+}
+
+/// Lists the keys of the table view in the middle of the screen
+fn render_keys(f: &mut Frame, app: &App, area: Rect) {
+    let key_width =
+        KEYS.iter().map(|(key, _)| UnicodeWidthStr::width(*key)).max().unwrap_or_default();
+    let lines = KEYS
+        .iter()
+        .map(|(key, action)| {
+            let padding = " ".repeat(key_width - UnicodeWidthStr::width(*key));
+            Line::from(vec![Span::from(format!("{key}{padding}  ")).bold(), Span::from(*action)])
+        })
+        .collect::<Vec<_>>();
+    let width = lines.iter().map(Line::width).max().unwrap_or_default() as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let area = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width: width.min(area.width),
+        height: height.min(area.height),
+    };
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+        .block(Block::default().borders(Borders::ALL).title("Keys, any key closes"));
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// Draws the mean time spent per frame and on each part of it in the top right corner
+fn render_frame_stats(f: &mut Frame, app: &App, area: Rect) {
+    let stats = &app.frame_stats;
+    let mean = stats.mean();
+    let ms = |duration: Duration| format!("{:.1}ms", duration.as_secs_f64() * 1000.0);
+    let budget = match stats.budget() {
+        budget if budget.is_zero() => "no budget".to_string(),
+        budget => format!("budget {}", ms(budget)),
+    };
+    let lines = [
+        format!("frame {} (max {}), {budget}", ms(mean.total()), ms(stats.max())),
+        format!(
+            "draw {} | lock {} | update {}",
+            ms(mean.draw),
+            ms(mean.lock_wait),
+            ms(mean.update)
+        ),
+        if stats.degraded() { "charts paused, slower refresh" } else { "full detail" }.to_string(),
+    ];
+    let width = lines.iter().map(|line| line.len() as u16).max().unwrap_or_default() + 4;
+    let area = Rect {
+        x: area.right().saturating_sub(width + 2),
+        y: area.y + 1,
+        width: width.min(area.width),
+        height: (lines.len() as u16 + 2).min(area.height),
+    };
+    let text = lines.into_iter().map(Line::from).collect::<Vec<_>>();
+    let paragraph = Paragraph::new(text)
+        .style(Style::default().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+        .block(Block::default().borders(Borders::ALL).title("Frame times"));
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn render_status_bar(f: &mut Frame, app: &mut App, area: Rect) {
+    let last_update = app
+        .last_update
+        .map_or("-".to_string(), |at| format!("{:.1}s ago", at.elapsed().as_secs_f64()));
+    let mut status = format!(
+        "{} | session {} | {} blocks | last update {}",
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        format_elapsed(app.started.elapsed()),
+        app.session_totals().blocks,
+        last_update,
+    );
+    let unacknowledged = app.alerts.unacknowledged();
+    if unacknowledged > 0 {
+        status = format!("{unacknowledged} unacknowledged alerts | {status}");
+    }
+    let status_bar = Paragraph::new(Line::from(status))
+        .style(Style::default().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+        .right_aligned();
+    f.render_widget(status_bar, area.inner(&Margin { vertical: 0, horizontal: 2 }));
+}
+
+/// Formats seconds in the past as the largest whole unit, e.g. `3m ago`
+fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{seconds}s ago"),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+/// Formats a duration as `HH:MM:SS`
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
 }
 
 fn render_totals(f: &mut Frame, app: &mut App, area: Rect) {
-    let header_titles = ["TPS", "MGas/s", "KB/s"];
+    let area = area.inner(&Margin { vertical: 1, horizontal: 2 });
+    let title = match (app.hourly_history, app.window_label()) {
+        (true, _) => "Totals, hourly means".to_string(),
+        (false, Some(window)) => format!("Totals ({window} window)"),
+        (false, None) => "Totals".to_string(),
+    };
+    let networks = app
+        .items
+        .iter()
+        .filter(|item| item.in_totals())
+        .map(|item| item.name.as_str())
+        .collect::<Vec<_>>();
+    render_history(f, app, area, &title, &app.totals, &app.totals_history, &networks);
+}
 
+/// Renders the current value, session peak and sparkline of TPS, gas and data side by side. The
+/// sparklines show the session's samples, or the stored hourly means summed over `networks`.
+fn render_history(
+    f: &mut Frame,
+    app: &App,
+    area: Rect,
+    title: &str,
+    current: &Metrics,
+    history: &MetricsHistory,
+    networks: &[&str],
+) {
     let header_style = Style::default().fg(app.colors.header_fg).bg(app.colors.header_bg);
-    let header = header_titles
-        .into_iter()
-        .map(|title| Cell::from(Text::from(title).alignment(Alignment::Center)).style(header_style))
-        .collect::<Row>()
-        .style(header_style)
-        .height(1);
+    let value_style = Style::default().fg(app.colors.row_fg).bg(app.colors.buffer_bg);
 
-    let totals_row = app
-        .totals
-        .cells()
-        .into_iter()
-        .map(|total| {
-            Cell::from(Text::from(total).alignment(Alignment::Center))
-                .style(Style::default().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
-        })
-        .collect::<Row>()
-        .height(1);
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 4); 4])
+        .split(block.inner(area));
+    f.render_widget(block, area);
 
-    let totals_table = Table::new(
-        vec![totals_row],
-        [Constraint::Percentage(33), Constraint::Percentage(33), Constraint::Percentage(34)],
-    )
-    .header(header)
-    .block(Block::default().borders(Borders::ALL).title("Totals"));
+    let units = app.units;
+    let [current_tps, current_gps, current_dps, current_da] = current.cells(units);
+    let [peak_tps, peak_gps, peak_dps, peak_da] = history.peaks.cells(units);
+    let totals = [
+        ("TPS", current_tps, peak_tps, &history.tps, Rate::Tps),
+        (units.gas.label(), current_gps, peak_gps, &history.gps, Rate::Gas),
+        (units.data.label(), current_dps, peak_dps, &history.dps, Rate::Data),
+        (units.data.da_label(), current_da, peak_da, &history.da, Rate::Da),
+    ];
 
-    let table_area = area.inner(&Margin { vertical: 1, horizontal: 2 });
+    for ((title, current, peak, samples, rate), area) in totals.into_iter().zip(columns.iter()) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Length(1), Constraint::Min(0)])
+            .split(*area);
+        f.render_widget(Paragraph::new(title).style(header_style).centered(), rows[0]);
+        f.render_widget(
+            Paragraph::new(format!("{current} (peak {peak})")).style(value_style).centered(),
+            rows[1],
+        );
+        if app.frame_stats.degraded() {
+            continue;
+        }
+        let width = rows[2].width as usize;
+        let data = match app.hourly_history {
+            true => hourly_sparkline(&app.time_series, networks, rate, width),
+            false => samples.sparkline(width),
+        };
+        let sparkline = Sparkline::default()
+            .data(&data)
+            .style(Style::default().fg(app.colors.sparkline_fg).bg(app.colors.buffer_bg));
+        f.render_widget(sparkline, rows[2]);
+        // markers are placed on the session's samples
+        if !app.hourly_history {
+            render_markers(f, app, rows[2], history);
+        }
+    }
+}
 
-    f.render_widget(totals_table, table_area);
+/// Hourly means of a rate over the last `hours` hours summed over the networks, oldest first,
+/// scaled like the session's samples
+fn hourly_sparkline(series: &TimeSeries, networks: &[&str], rate: Rate, hours: usize) -> Vec<u64> {
+    let mut sums = vec![0.0; hours];
+    for network in networks {
+        for (sum, hour) in sums.iter_mut().zip(series.hourly(network, hours)) {
+            *sum += hour.mean(rate).unwrap_or_default();
+        }
+    }
+    sums.into_iter().map(|sum| (sum * 100.0) as u64).collect()
+}
+
+/// Draws a vertical line through a sparkline at each marker within its span
+fn render_markers(f: &mut Frame, app: &App, area: Rect, history: &MetricsHistory) {
+    let buffer = f.buffer_mut();
+    for marker in &app.markers {
+        let Some(column) = history.column(marker.instant, area.width as usize) else {
+            continue;
+        };
+        for y in area.top()..area.bottom() {
+            buffer.get_mut(area.x + column as u16, y).set_symbol("│").set_fg(app.colors.marker_fg);
+        }
+    }
+}
+
+fn table_title(app: &App) -> String {
+    let notes = [
+        (app.grouped, "grouped"),
+        (app.sort.frozen, "order frozen"),
+        (app.hide_testnets, "testnets hidden"),
+    ]
+    .into_iter()
+    .filter_map(|(applies, note)| applies.then_some(note))
+    .collect::<Vec<_>>();
+    match notes.is_empty() {
+        true => "Networks".to_string(),
+        false => format!("Networks ({})", notes.join(", ")),
+    }
 }
 
 fn render_table(f: &mut Frame, app: &mut App, area: Rect) {
     let header_style = Style::default().fg(app.colors.header_fg).bg(app.colors.header_bg);
-    let header_titles = ["Network", "Block", "TPS", "MGas/s", "KB/s"];
-    let header = header_titles
+    let columns = app.visible_columns();
+    let custom = app.custom_columns();
+    let header = columns
         .iter()
-        .enumerate()
-        .map(|(i, &title)| {
-            let alignment = if i > 0 { Alignment::Right } else { Alignment::Left };
-            Cell::from(Text::from(title).alignment(alignment))
+        .map(|&column| {
+            let alignment =
+                if column == Column::Network { Alignment::Left } else { Alignment::Right };
+            let title = match app.sort {
+                Sort { frozen: false, descending, column: sorted } if sorted == column => {
+                    format!("{} {}", column.title(app.units), if descending { '↓' } else { '↑' })
+                }
+                _ => column.title(app.units).to_string(),
+            };
+            let cell = Cell::from(Text::from(title).alignment(alignment));
+            if app.focused_column == Some(column) {
+                cell.underlined()
+            } else {
+                cell
+            }
         })
+        .chain(custom.iter().map(|label| Cell::from(Text::from(label.to_string()).right_aligned())))
         .collect::<Row>()
         .style(header_style)
         .height(1);
 
-    let rows = app.items.iter().map(|data| {
-        let color = app.colors.normal_row_color;
-        let item = data.cells();
-        item.into_iter()
-            .enumerate()
-            .map(|(i, content)| {
-                let alignment = if i > 0 { Alignment::Right } else { Alignment::Left };
-                let content = if content == "0.00" { "-".to_string() } else { content };
-                Cell::from(Text::from(format!("\n{}\n", content)).alignment(alignment))
+    let rows = app.table_rows().into_iter().map(|row| {
+        let data = match row {
+            TableRow::Network(data) => data,
+            TableRow::Group { name, subtotals, networks, collapsed } => {
+                let arrow = if collapsed { '▸' } else { '▾' };
+                let title = format!("{arrow} {name} ({networks})");
+                let in_totals = name != TESTNETS_GROUP;
+                return group_row(app, &columns, custom.len(), title, &subtotals, in_totals);
+            }
+        };
+        // rows with a threshold alert firing stand out
+        let color = match data.firing.is_empty() {
+            true => app.colors.normal_row_color,
+            false => app.colors.alert_row_bg,
+        };
+        let fg = match data {
+            data if data.is_stale() => app.colors.stale_row_fg,
+            data if data.testnet => app.colors.testnet_row_fg,
+            _ => app.colors.row_fg,
+        };
+        let modifier = match data {
+            data if data.testnet => Modifier::ITALIC,
+            data if data.index => Modifier::BOLD,
+            _ => Modifier::empty(),
+        };
+        let item = data.cells(app.units, &app.totals);
+        columns
+            .iter()
+            .map(|&column| {
+                let alignment =
+                    if column == Column::Network { Alignment::Left } else { Alignment::Right };
+                let content = &item[column.index()];
+                let content = if content == "0.00" { "-" } else { content };
+                let format = app.cell_format(data, CellColumn::Fixed(column));
+                let text = Text::from(cell_text(content, format.as_ref())).alignment(alignment);
+                let cell = match data.health_status() {
+                    Some(status) if column == Column::Status => {
+                        Cell::from(text).fg(app.colors.health_fg(status))
+                    }
+                    _ => Cell::from(text),
+                };
+                apply_format(cell, format)
             })
+            .chain(custom.iter().map(|&label| {
+                let value = data.custom.iter().find(|custom| custom.label == label);
+                let content = format_custom(value.and_then(|custom| custom.value));
+                let format = app.cell_format(data, CellColumn::Custom(label));
+                let cell =
+                    Cell::from(Text::from(cell_text(&content, format.as_ref())).right_aligned());
+                apply_format(cell, format)
+            }))
             .collect::<Row>()
-            .style(Style::default().fg(app.colors.row_fg).bg(color))
+            .style(Style::default().fg(fg).bg(color).add_modifier(modifier))
             .height(2)
     });
 
     let bar = " █ ";
+    let widths = columns
+        .iter()
+        .map(|&column| app.column_constraint(column))
+        .chain(custom.iter().map(|label| Constraint::Min(UnicodeWidthStr::width(*label) as u16)))
+        .collect::<Vec<_>>();
+    let t = Table::new(rows, widths.clone())
+        .header(header)
+        .highlight_symbol(Text::from(vec!["".into(), bar.into(), bar.into(), "".into()]))
+        .bg(app.colors.buffer_bg)
+        .highlight_spacing(HighlightSpacing::Always)
+        .block(Block::default().borders(Borders::ALL).title(table_title(app)));
+
+    let area = area.inner(&Margin { vertical: 0, horizontal: 2 });
+    let [shares_area, table_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+
+    f.render_widget(Paragraph::new(stack_shares(app)).fg(app.colors.row_fg), shares_area);
+    // the widths the table lays its columns out with, inside its borders and highlight symbol
+    let inner = table_area.width.saturating_sub(2 + bar.width() as u16);
+    let drawn =
+        Layout::horizontal(widths).flex(Flex::Start).spacing(1).split(Rect::new(0, 0, inner, 1));
+    app.drawn_widths =
+        columns.iter().zip(drawn.iter()).map(|(&column, rect)| (column, rect.width)).collect();
+    f.render_stateful_widget(t, table_area, &mut app.state);
+}
+
+/// Header row of a group with its subtotals, and its share of the totals if it counts towards them
+fn group_row(
+    app: &App,
+    columns: &[Column],
+    custom_columns: usize,
+    title: String,
+    subtotals: &Metrics,
+    in_totals: bool,
+) -> Row<'static> {
+    let [tps, gps, dps, da] = subtotals.cells(app.units);
+    let cells = columns.iter().map(|&column| {
+        let content = match column {
+            Column::Network => title.clone(),
+            Column::Tps => tps.clone(),
+            Column::Gas => gps.clone(),
+            Column::Data => dps.clone(),
+            Column::Da => da.clone(),
+            Column::Share if in_totals => subtotals.share_cell(&app.totals),
+            _ => String::new(),
+        };
+        let alignment = if column == Column::Network { Alignment::Left } else { Alignment::Right };
+        Cell::from(Text::from(format!("\n{content}\n")).alignment(alignment))
+    });
+    let custom = (0..custom_columns).map(|_| Cell::from(""));
+    Row::new(cells.chain(custom))
+        .style(
+            Style::default()
+                .fg(app.colors.header_fg)
+                .bg(app.colors.header_bg)
+                .add_modifier(Modifier::BOLD),
+        )
+        .height(2)
+}
+
+/// Formats the value of a custom metric, whole numbers such as block numbers in full
+fn format_custom(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value.fract() == 0.0 && value.abs() < 1e15 => format!("{value:.0}"),
+        Some(value) => format!("{value:.2}"),
+        None => "-".to_string(),
+    }
+}
+
+/// Text of a two line high table cell, after the symbol of the color rules applied to it
+fn cell_text(content: &str, format: Option<&CellFormat>) -> String {
+    match format.and_then(|format| format.symbol.as_deref()) {
+        Some(symbol) => format!("\n{symbol} {content}\n"),
+        None => format!("\n{content}\n"),
+    }
+}
+
+/// Styles a cell with the color rules applied to it
+fn apply_format(cell: Cell<'_>, format: Option<CellFormat>) -> Cell<'_> {
+    match format {
+        Some(format) => {
+            let style = Styled::style(&cell).patch(format.style);
+            cell.style(style)
+        }
+        None => cell,
+    }
+}
+
+/// Share of total tx/gas/data per rollup stack, largest first by txs
+fn stack_shares(app: &App) -> String {
+    let mut stacks: Vec<(&str, Metrics)> = Vec::new();
+    for item in app.items.iter().filter(|item| item.in_totals()) {
+        let stack = item.stack.as_deref().unwrap_or("Other");
+        let share = item.metrics.share_of(&app.totals);
+        match stacks.iter_mut().find(|(name, _)| *name == stack) {
+            Some((_, total)) => {
+                total.tps += share.tps;
+                total.gps += share.gps;
+                total.dps += share.dps;
+            }
+            None => stacks.push((stack, share)),
+        }
+    }
+    stacks.sort_by(|a, b| b.1.tps.total_cmp(&a.1.tps));
+    let shares = stacks
+        .iter()
+        .map(|(stack, share)| {
+            format!("{stack} {:.0}/{:.0}/{:.0}%", share.tps, share.gps, share.dps)
+        })
+        .collect::<Vec<_>>();
+    format!("Share by stack (tx/gas/data): {}", shares.join(" | "))
+}
+
+fn render_detail(f: &mut Frame, app: &mut App, area: Rect) {
+    let View::Detail(name) = &app.view else {
+        return;
+    };
+    let Some(item) = app.items.iter().find(|item| &item.name == name) else {
+        return;
+    };
+
+    let [tps, gps, dps, da] = item.metrics.cells(app.units);
+    let updated =
+        item.updated.map_or("-".to_string(), |at| format!("{}s ago", at.elapsed().as_secs()));
+    let session = item.session;
+    let (median_tx_gas, max_tx_gas) =
+        item.tx_gas.map_or(("-".to_string(), "-".to_string()), |stats| {
+            (format_count(stats.median), format_count(stats.max))
+        });
+    let baseline =
+        baseline::tps(&app.time_series, &item.name).map_or("-".to_string(), |baseline| {
+            let change = (item.metrics.tps / baseline - 1.0) * 100.0;
+            format!("{baseline:.2} (now {change:+.0}%)")
+        });
+    let inbox = item.inbox.map_or("-".to_string(), |queue| match queue.oldest_age {
+        Some(age) => format!("{} pending, oldest {age}s", queue.depth),
+        None => format!("{} pending", queue.depth),
+    });
+    // batches each L1 step trails the newest sealed batch by
+    let zk = item.zk_batches.unwrap_or_default();
+    let lag = |batch: Option<u64>| match (item.zk_batches, batch) {
+        (Some(batches), Some(batch)) => format!("{} batches", batches.sealed.saturating_sub(batch)),
+        _ => "-".to_string(),
+    };
+    let time_zone = item.time_zone.unwrap_or(app.config.time_zone);
+    let last_proof = zk.proven_at.map_or("-".to_string(), |at| {
+        let age = format_age((Utc::now() - at).num_seconds().max(0) as u64);
+        format!("{age}, {}", time_zone.time_of_day(at.timestamp() as u64))
+    });
+    let newest_block =
+        item.recent_blocks.first().map(|block| time_zone.time_of_day(block.timestamp));
+    let mut last_block = match (item.last_block_age(), newest_block) {
+        (Some(age), Some(at)) => format!("{}, {at}", format_age(age)),
+        (Some(age), None) => format_age(age),
+        (None, _) => "-".to_string(),
+    };
+    if item.is_lagging() {
+        last_block.push_str(", lagging");
+    }
+    let verified = item.verification.map_or("-".to_string(), |lag| {
+        let behind = format!("#{}, {} {} behind", lag.verified, lag.behind, lag.unit);
+        match lag.age {
+            Some(age) => format!("{behind}, {}m old", age / 60),
+            None => behind,
+        }
+    });
+    let polling = item.polling.map_or("-".to_string(), |polling| {
+        let mode = match (polling.adaptive, polling.backoff) {
+            (false, _) => "",
+            (true, 0) => " (adaptive)",
+            (true, _) => " (adaptive, backing off)",
+        };
+        format!("{}ms{mode}", polling.interval_ms)
+    });
+    let budget = item.budget.map_or("-".to_string(), |budget| {
+        let degraded = if budget.degraded { ", degraded" } else { "" };
+        format!("{}/{} requests left this hour{degraded}", budget.remaining, budget.per_hour)
+    });
+    let tps_by_window = item
+        .windows
+        .iter()
+        .map(|window| format!("{}s {:.2}", window.seconds, window.tps))
+        .collect::<Vec<_>>();
+    let tps_by_window =
+        if tps_by_window.is_empty() { "-".to_string() } else { tps_by_window.join(" | ") };
+    let health = item.health.as_ref();
+    let status = match (item.health_status(), health) {
+        (Some(status), Some(health)) if health.consecutive_errors > 0 => {
+            format!("{} ({} failed polls in a row)", status.label(), health.consecutive_errors)
+        }
+        (Some(status), _) => status.label().to_string(),
+        (None, _) => "-".to_string(),
+    };
+    let last_success = health
+        .and_then(|health| health.last_success)
+        .map_or("-".to_string(), |at| format!("{}s ago", (Utc::now() - at).num_seconds().max(0)));
+    let mut rows = vec![
+        ("Note", app.notes.get(&item.name).unwrap_or("-").to_string()),
+        ("Health", status),
+        ("Last success", last_success),
+        (
+            "Latency",
+            health
+                .and_then(|health| health.latency_ms)
+                .map_or("-".to_string(), |ms| format!("{ms} ms")),
+        ),
+        (
+            "Last error",
+            health.and_then(|health| health.last_error.clone()).unwrap_or_else(|| "-".to_string()),
+        ),
+        ("Stack", item.stack.clone().unwrap_or_else(|| "-".to_string())),
+        ("Group", item.group.clone().unwrap_or_else(|| "-".to_string())),
+        ("Source", item.source.clone().unwrap_or_else(|| "local".to_string())),
+        ("Endpoint", item.endpoint.clone().unwrap_or_else(|| "-".to_string())),
+        ("Budget", budget),
+        ("Poll interval", polling),
+        ("Block", item.block.to_string()),
+        ("Block time", item.block_time.map_or("-".to_string(), |seconds| format!("{seconds:.2}s"))),
+        ("Last block", last_block),
+        ("Updated", updated),
+        ("TPS", tps),
+        ("TPS by window", tps_by_window),
+        ("Baseline TPS", baseline),
+        (app.units.gas.label(), gps),
+        (app.units.data.label(), dps),
+        (app.units.data.da_label(), da),
+        ("Blobs/s", item.blobs.map_or("-".to_string(), |blobs| format!("{blobs:.2}"))),
+        ("Median tx gas limit", median_tx_gas),
+        ("Max tx gas limit", max_tx_gas),
+        ("Bridge deposits", item.bridge.map_or("-".to_string(), |b| b.deposits.to_string())),
+        ("Bridge withdrawals", item.bridge.map_or("-".to_string(), |b| b.withdrawals.to_string())),
+        ("Base fee", item.base_fee.map_or("-".to_string(), |fee| format_fee(fee, &item.gas_token))),
+        (
+            "Priority fee",
+            item.priority_fee.map_or("-".to_string(), |fee| format_fee(fee, &item.gas_token)),
+        ),
+        ("Base fees/s", format_token(item.base_fees, &item.gas_token)),
+        (
+            "Value transferred/s",
+            item.value.map_or("-".to_string(), |value| format_token(value, &item.gas_token)),
+        ),
+        ("NFT mints/s", item.mints.map_or("-".to_string(), |mints| format!("{mints:.2}"))),
+        ("L1 freshness", item.l1_freshness.map_or("-".to_string(), |age| format!("{age}s"))),
+        ("Inbox queue", inbox),
+        ("L1 batch", item.zk_batches.map_or("-".to_string(), |batches| batches.sealed.to_string())),
+        ("Commit lag", lag(zk.committed)),
+        ("Proof lag", lag(zk.proven)),
+        ("Execution lag", lag(zk.executed)),
+        ("Last proof", last_proof),
+        ("Verified", verified),
+        (
+            "Firing alerts",
+            if item.firing.is_empty() { "-".to_string() } else { item.firing.join(", ") },
+        ),
+        ("", String::new()),
+        ("Session blocks", format_count(session.blocks)),
+        ("Session txs", format_count(session.txs)),
+        ("Session gas", format_count(session.gas)),
+        ("Session data", format_bytes(session.data)),
+        ("Session reorgs", format_count(session.reorgs)),
+    ];
+    let contracts = item
+        .top_contracts
+        .iter()
+        .map(|(address, txs)| format!("{} ({txs} txs)", item.labels.label(address)));
+    push_section(&mut rows, "Top contracts", contracts);
+    let functions = item
+        .top_selectors
+        .iter()
+        .map(|(selector, txs)| format!("{} ({txs} txs)", describe_call(selector.as_ref())));
+    push_section(&mut rows, "Top functions", functions);
+    let builders =
+        item.builders.iter().map(|(builder, share)| format!("{builder} ({:.0}%)", share * 100.0));
+    push_section(&mut rows, "Builders", builders);
+    let custom = item
+        .custom
+        .iter()
+        .map(|custom| format!("{} {}", custom.label, format_custom(custom.value)));
+    push_section(&mut rows, "Custom metrics", custom);
+    let events = recent_events(&app.events, &item.name).map(|event| {
+        format!("{} {}", event.at.with_timezone(&Local).format("%H:%M:%S"), event.kind)
+    });
+    push_section(&mut rows, "Recent events", events);
+
+    let rows = rows.into_iter().map(|(label, value)| {
+        Row::new([Cell::from(label), Cell::from(Text::from(value).alignment(Alignment::Right))])
+    });
+
+    let t = Table::new(rows, [Constraint::Length(20), Constraint::Min(0)])
+        .style(Style::default().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+        .block(Block::default().borders(Borders::ALL).title(item.name.as_str()));
+
+    let area = area.inner(&Margin { vertical: 0, horizontal: 2 });
+    let [history_area, area] =
+        Layout::vertical([Constraint::Length(8), Constraint::Min(0)]).areas(area);
+    let [details_area, side_area] =
+        Layout::horizontal([Constraint::Min(0), Constraint::Length(48)]).areas(area);
+    // the newest blocks, a header and borders
+    let [blocks_area, txs_area] =
+        Layout::vertical([Constraint::Length(13), Constraint::Min(0)]).areas(side_area);
+    let title = match app.hourly_history {
+        true => "Hourly means".to_string(),
+        false => format!(
+            "Last {} minutes",
+            HISTORY_SAMPLES as u64 * HISTORY_SAMPLE_INTERVAL.as_secs() / 60
+        ),
+    };
+    let networks = [item.name.as_str()];
+    render_history(f, app, history_area, &title, &item.metrics, &item.history, &networks);
+    f.render_widget(t, details_area);
+    let time_zone = item.time_zone.unwrap_or(app.config.time_zone);
+    render_recent_blocks(f, app, blocks_area, &item.recent_blocks, time_zone);
+    render_tx_samples(f, app, txs_area, item);
+}
+
+/// Lists the transactions sampled from the newest blocks, newest first
+fn render_tx_samples(f: &mut Frame, app: &App, area: Rect, item: &NetworkMetrics) {
+    let header = ["Hash", "Type", "Gas", "Value"]
+        .into_iter()
+        .enumerate()
+        .map(|(i, title)| {
+            let alignment = if i > 0 { Alignment::Right } else { Alignment::Left };
+            Cell::from(Text::from(title).alignment(alignment))
+        })
+        .collect::<Row>()
+        .style(Style::default().fg(app.colors.header_fg).bg(app.colors.header_bg));
+    let rows = item.tx_samples.iter().map(|tx| {
+        let hash = tx.hash.to_string();
+        let hash = format!("{}…{}", &hash[..8], &hash[hash.len() - 6..]);
+        let values = [
+            tx.tx_type.map_or("-".to_string(), tx_type_label),
+            tx.gas.map_or("-".to_string(), format_count),
+            tx.value.map_or("-".to_string(), |value| format_token(value, &item.gas_token)),
+        ];
+        std::iter::once(Cell::from(hash))
+            .chain(values.map(|value| Cell::from(Text::from(value).alignment(Alignment::Right))))
+            .collect::<Row>()
+    });
+    let selected =
+        app.selected_tx.and_then(|hash| item.tx_samples.iter().position(|tx| tx.hash == hash));
+    let t = Table::new(
+        rows,
+        [Constraint::Length(15), Constraint::Length(7), Constraint::Length(6), Constraint::Min(0)],
+    )
+    .header(header)
+    .highlight_symbol(" █ ")
+    .highlight_spacing(HighlightSpacing::Always)
+    .style(Style::default().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .block(Block::default().borders(Borders::ALL).title("Transactions"));
+    f.render_stateful_widget(t, area, &mut TableState::default().with_selected(selected));
+}
+
+/// Name of an EIP-2718 transaction type
+fn tx_type_label(tx_type: u8) -> String {
+    match tx_type {
+        0 => "legacy".to_string(),
+        1 => "2930".to_string(),
+        2 => "1559".to_string(),
+        3 => "blob".to_string(),
+        4 => "7702".to_string(),
+        // OP Stack L1 deposits
+        0x7e => "deposit".to_string(),
+        tx_type => format!("0x{tx_type:02x}"),
+    }
+}
+
+/// Lists the newest blocks with their time, age, time since the previous block and tx count
+fn render_recent_blocks(
+    f: &mut Frame,
+    app: &App,
+    area: Rect,
+    blocks: &[RecentBlock],
+    time_zone: TimeZone,
+) {
+    let now = Utc::now().timestamp() as u64;
+    let header = ["Block", "Time", "Age", "Δ", "Txs"]
+        .into_iter()
+        .map(|title| Cell::from(Text::from(title).alignment(Alignment::Right)))
+        .collect::<Row>()
+        .style(Style::default().fg(app.colors.header_fg).bg(app.colors.header_bg));
+    let rows = blocks.iter().enumerate().map(|(i, block)| {
+        let time = time_zone.time_of_day(block.timestamp);
+        let age = format_age(now.saturating_sub(block.timestamp));
+        // blocks are newest first, the previous block is the next one in the list
+        let block_time = blocks.get(i + 1).map_or("-".to_string(), |previous| {
+            format!("{}s", block.timestamp.saturating_sub(previous.timestamp))
+        });
+        [block.number.to_string(), time, age, block_time, block.txs.to_string()]
+            .into_iter()
+            .map(|value| Cell::from(Text::from(value).alignment(Alignment::Right)))
+            .collect::<Row>()
+    });
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Min(8),
+            Constraint::Length(9),
+            Constraint::Length(7),
+            Constraint::Length(5),
+            Constraint::Length(5),
+        ],
+    )
+    .header(header)
+    .style(Style::default().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+    .block(Block::default().borders(Borders::ALL).title("Recent blocks"));
+    f.render_widget(t, area);
+}
+
+/// Renders one lane per network with events in the last [`TIMELINE_SPAN`], followed by a
+/// chronological list of those events, so events on different networks can be correlated
+fn render_events(f: &mut Frame, app: &mut App, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title("Events");
+    let area = area.inner(&Margin { vertical: 0, horizontal: 2 });
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let now = Utc::now();
+    let start = now - TIMELINE_SPAN;
+    let events = app.events.iter().filter(|event| event.at >= start).collect::<Vec<_>>();
+    let mut networks = events.iter().map(|event| event.network.as_str()).collect::<Vec<_>>();
+    networks.sort_unstable();
+    networks.dedup();
+
+    let label_width = app.longest_name as usize;
+    let lane_width = (inner.width as usize).saturating_sub(label_width + 1).max(1);
+    let position = |at: DateTime<Utc>| {
+        let offset =
+            (at - start).num_milliseconds() as f64 / TIMELINE_SPAN.num_milliseconds() as f64;
+        ((offset * lane_width as f64) as usize).min(lane_width - 1)
+    };
+
+    let mut lines = networks
+        .iter()
+        .map(|&network| {
+            let mut lane = vec!['·'; lane_width];
+            for event in events.iter().filter(|event| event.network == network) {
+                lane[position(event.at)] = event_glyph(&event.kind);
+            }
+            Line::from(format!("{network:<label_width$} {}", lane.into_iter().collect::<String>()))
+        })
+        .collect::<Vec<_>>();
+    let markers = app.markers.iter().filter(|marker| marker.at >= start).collect::<Vec<_>>();
+    if !markers.is_empty() {
+        let mut lane = vec!['·'; lane_width];
+        for marker in &markers {
+            lane[position(marker.at)] = MARKER_GLYPH;
+        }
+        let lane = lane.into_iter().collect::<String>();
+        lines.push(
+            Line::from(format!("{:<label_width$} {lane}", "markers")).fg(app.colors.marker_fg),
+        );
+    }
+    let axis_start = format!("-{}m", TIMELINE_SPAN.num_minutes());
+    lines.push(Line::from(format!(
+        "{:<label_width$} {axis_start}{:>width$}",
+        "",
+        "now",
+        width = lane_width.saturating_sub(axis_start.len()),
+    )));
+    lines.push(Line::from(""));
+    let mut entries = events
+        .iter()
+        .map(|event| {
+            let at = event.at.with_timezone(&Local).format("%H:%M:%S");
+            let line = Line::from(format!(
+                "{at} {} {:<label_width$} {}",
+                event_glyph(&event.kind),
+                event.network,
+                event.kind
+            ));
+            (event.at, line)
+        })
+        .chain(markers.iter().map(|marker| {
+            let at = marker.at.with_timezone(&Local).format("%H:%M:%S");
+            let line = Line::from(format!(
+                "{at} {MARKER_GLYPH} {:<label_width$} {}",
+                "marker", marker.label
+            ))
+            .fg(app.colors.marker_fg);
+            (marker.at, line)
+        }))
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|(at, _)| std::cmp::Reverse(*at));
+    lines.extend(entries.into_iter().map(|(_, line)| line));
+
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(app.colors.row_fg).bg(app.colors.buffer_bg));
+    f.render_widget(paragraph, inner);
+}
+
+fn render_alerts(f: &mut Frame, app: &mut App, area: Rect) {
+    let header_style = Style::default().fg(app.colors.header_fg).bg(app.colors.header_bg);
+    let header =
+        ["Time", "Network", "Rule", "Value", ""].into_iter().collect::<Row>().style(header_style);
+
+    let rows = app.alerts.iter().map(|alert| {
+        let fg = if alert.acknowledged { app.colors.stale_row_fg } else { app.colors.row_fg };
+        Row::new([
+            alert.at.with_timezone(&Local).format("%H:%M:%S").to_string(),
+            alert.network.clone(),
+            alert.rule.clone(),
+            alert.value.clone(),
+            if alert.acknowledged { "✓".to_string() } else { String::new() },
+        ])
+        .style(Style::default().fg(fg))
+    });
+
     let t = Table::new(
         rows,
         [
+            Constraint::Length(9),
             Constraint::Length(app.longest_name + 1),
+            Constraint::Length(20),
             Constraint::Min(0),
-            Constraint::Min(5),
-            Constraint::Min(5),
-            Constraint::Min(5),
+            Constraint::Length(2),
         ],
     )
     .header(header)
-    .highlight_symbol(Text::from(vec!["".into(), bar.into(), bar.into(), "".into()]))
     .bg(app.colors.buffer_bg)
+    .highlight_symbol(" █ ")
     .highlight_spacing(HighlightSpacing::Always)
-    .block(Block::default().borders(Borders::ALL).title("Networks"));
+    .block(Block::default().borders(Borders::ALL).title(format!("Alerts ({})", app.alerts.len())));
 
     let table_area = area.inner(&Margin { vertical: 0, horizontal: 2 });
+    f.render_stateful_widget(t, table_area, &mut app.alerts_state);
+}
 
-    f.render_stateful_widget(t, table_area, &mut app.state);
+fn event_glyph(kind: &EventKind) -> char {
+    match kind {
+        EventKind::FeeSpike { .. } => '▲',
+        EventKind::Stall { .. } => '■',
+        EventKind::InboxBacklog { .. } => '◆',
+        EventKind::TpsAnomaly { z, .. } if *z > 0.0 => '↑',
+        EventKind::TpsAnomaly { .. } => '↓',
+        EventKind::Reorg { .. } => '↺',
+        EventKind::Threshold { cleared: false, .. } => '!',
+        EventKind::Threshold { cleared: true, .. } => '✓',
+    }
+}
+
+/// Appends a titled list of values to the detail rows, separated by a blank row
+fn push_section(
+    rows: &mut Vec<(&'static str, String)>,
+    title: &'static str,
+    values: impl Iterator<Item = String>,
+) {
+    for (i, value) in values.enumerate() {
+        if i == 0 {
+            rows.push(("", String::new()));
+        }
+        rows.push((if i == 0 { title } else { "" }, value));
+    }
+}
+
+/// Most recent events for a network, newest first
+fn recent_events<'a>(
+    events: &'a VecDeque<NetworkEvent>,
+    network: &'a str,
+) -> impl Iterator<Item = &'a NetworkEvent> {
+    events.iter().rev().filter(move |event| event.network == network).take(DETAIL_EVENTS)
+}
+
+/// Renders the most recent log lines that fit, newest at the bottom
+fn render_logs(f: &mut Frame, app: &mut App, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title("Logs");
+    let area = area.inner(&Margin { vertical: 0, horizontal: 2 });
+    let lines = app.log_tail.last(block.inner(area).height as usize);
+    let text = Text::from(lines.into_iter().map(Line::from).collect::<Vec<_>>());
+    f.render_widget(Paragraph::new(text).fg(app.colors.row_fg).block(block), area);
+}
+
+/// Which optional RPC methods each endpoint supports, with the features skipped without them
+fn render_capabilities(f: &mut Frame, app: &mut App, area: Rect) {
+    let header_style = Style::default().fg(app.colors.header_fg).bg(app.colors.header_bg);
+    let header = ["Network", "Endpoint"]
+        .into_iter()
+        .chain(Capability::ALL.map(Capability::title))
+        .chain(["WebSocket"])
+        .collect::<Row>()
+        .style(header_style);
+
+    let mark = |supported: Option<bool>| match supported {
+        Some(true) => Cell::from("✓").fg(app.colors.healthy_fg),
+        Some(false) => Cell::from("✗").fg(app.colors.down_fg),
+        None => Cell::from("?"),
+    };
+    let (active_fg, inactive_fg) = (app.colors.row_fg, app.colors.stale_row_fg);
+    let rows = app.visible_items().flat_map(|item| {
+        let health = item.health.as_ref();
+        let endpoints = health.into_iter().flat_map(|health| health.endpoints.iter());
+        endpoints.map(move |endpoint| {
+            let host = match endpoint.active {
+                true => format!("● {}", endpoint.host),
+                false => format!("  {}", endpoint.host),
+            };
+            let capabilities = Capability::ALL
+                .map(|capability| mark(endpoint.capabilities.get(&capability).copied()));
+            // the subscription belongs to the network, shown with the endpoint blocks come from
+            let websocket = match (endpoint.active, health.and_then(|health| health.websocket)) {
+                (true, Some(subscribed)) => mark(Some(subscribed)),
+                _ => Cell::from("-"),
+            };
+            let fg = if endpoint.active { active_fg } else { inactive_fg };
+            [Cell::from(item.name.clone()), Cell::from(host)]
+                .into_iter()
+                .chain(capabilities)
+                .chain([websocket])
+                .collect::<Row>()
+                .style(Style::default().fg(fg))
+        })
+    });
+
+    let widths =
+        [Constraint::Length(app.longest_name + 1), Constraint::Min(20)]
+            .into_iter()
+            .chain(Capability::ALL.map(|capability| {
+                Constraint::Length(capability.title().chars().count() as u16 + 2)
+            }))
+            .chain([Constraint::Length(11)]);
+    let skipped = Capability::ALL
+        .into_iter()
+        .filter_map(|capability| Some(format!("{} {}", capability.title(), capability.features()?)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let t = Table::new(rows, widths).header(header).bg(app.colors.buffer_bg).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("RPC capabilities (features skipped without them: {skipped})")),
+    );
+    f.render_widget(t, area.inner(&Margin { vertical: 0, horizontal: 2 }));
+}
+
+/// Lists the request latency and errors of each network's RPC endpoints, the active one marked,
+/// with the distribution of latencies over the buckets from fastest to slowest
+fn render_endpoints(f: &mut Frame, app: &mut App, area: Rect) {
+    let header_style = Style::default().fg(app.colors.header_fg).bg(app.colors.header_bg);
+    let bounds = LATENCY_BUCKETS_MS.map(|bound| bound.to_string()).join("/");
+    let header = [
+        "Network",
+        "Endpoint",
+        "Requests",
+        "Errors",
+        "Mean",
+        "p50",
+        "p90",
+        "p99",
+        "Distribution",
+        "Gzip",
+    ]
+    .into_iter()
+    .collect::<Row>()
+    .style(header_style);
+
+    let quantile = |histogram: &LatencyHistogram, q: f64| match histogram.quantile_ms(q) {
+        Some(ms) => format!("≤{ms}ms"),
+        None if histogram.count > 0 => {
+            format!(">{}ms", LATENCY_BUCKETS_MS[LATENCY_BUCKETS_MS.len() - 1])
+        }
+        None => "-".to_string(),
+    };
+    let (active_fg, inactive_fg) = (app.colors.row_fg, app.colors.stale_row_fg);
+    let rows = app.visible_items().flat_map(|item| {
+        let endpoints = item.health.iter().flat_map(|health| health.endpoints.iter());
+        endpoints.map(move |endpoint| {
+            let histogram = &endpoint.latency;
+            let busiest = histogram.buckets.iter().copied().max().unwrap_or_default().max(1);
+            let distribution = histogram
+                .buckets
+                .iter()
+                .map(|&count| SHADES[(count * 8).div_ceil(busiest) as usize])
+                .collect::<String>();
+            let host = if endpoint.active {
+                format!("● {}", endpoint.host)
+            } else {
+                format!("  {}", endpoint.host)
+            };
+            let fg = if endpoint.active { active_fg } else { inactive_fg };
+            let gzip = match endpoint.transfer {
+                _ if !endpoint.compressed => "off".to_string(),
+                None => "-".to_string(),
+                Some(transfer) if !transfer.gzip => "unsupported".to_string(),
+                Some(transfer) => {
+                    let savings = transfer.savings().unwrap_or_default() * 100.0;
+                    format!("saves {savings:.0}%")
+                }
+            };
+            Row::new([
+                item.name.clone(),
+                host,
+                format_count(histogram.count + endpoint.errors),
+                format_count(endpoint.errors),
+                histogram.mean_ms().map_or("-".to_string(), |ms| format!("{ms:.0}ms")),
+                quantile(histogram, 0.5),
+                quantile(histogram, 0.9),
+                quantile(histogram, 0.99),
+                distribution,
+                gzip,
+            ])
+            .style(Style::default().fg(fg))
+        })
+    });
+
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Length(app.longest_name + 1),
+            Constraint::Min(20),
+            Constraint::Length(9),
+            Constraint::Length(7),
+            Constraint::Length(8),
+            Constraint::Length(9),
+            Constraint::Length(9),
+            Constraint::Length(9),
+            Constraint::Length(13),
+            Constraint::Length(16),
+        ],
+    )
+    .header(header)
+    .bg(app.colors.buffer_bg)
+    .block(
+        Block::default().borders(Borders::ALL).title(format!("RPC endpoints (buckets {bounds}ms)")),
+    );
+    f.render_widget(t, area.inner(&Margin { vertical: 0, horizontal: 2 }));
+}
+
+/// Renders the streamer logs that fit, newest at the bottom unless scrolled back
+fn render_network_logs(f: &mut Frame, app: &mut App, area: Rect) {
+    let title = match app.network_log_scroll {
+        0 => "Network log (PgUp/PgDn scroll)".to_string(),
+        scroll => format!("Network log ({scroll} lines back)"),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let area = area.inner(&Margin { vertical: 0, horizontal: 2 });
+    let height = block.inner(area).height as usize;
+    let end = app.network_logs.len().saturating_sub(app.network_log_scroll);
+    let lines = app
+        .network_logs
+        .range(end.saturating_sub(height)..end)
+        .map(|log| {
+            let at = log.at.with_timezone(&Local).format("%H:%M:%S");
+            Line::from(format!(
+                "{at} {:<width$} {}",
+                log.network,
+                log.message,
+                width = app.longest_name as usize
+            ))
+        })
+        .collect::<Vec<_>>();
+    f.render_widget(Paragraph::new(Text::from(lines)).fg(app.colors.row_fg).block(block), area);
 }
 
 fn render_footer(f: &mut Frame, app: &mut App, area: Rect) {
-    let info_footer = Paragraph::new(Line::from(INFO_TEXT))
-        .style(Style::default().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
-        .centered()
-        .block(
+    let info_text = match app.view {
+        View::Table => INFO_TEXT,
+        View::Detail(_) => DETAIL_INFO_TEXT,
+        View::Events => EVENTS_INFO_TEXT,
+        View::Alerts => ALERTS_INFO_TEXT,
+        View::Logs => LOGS_INFO_TEXT,
+        View::Endpoints => ENDPOINTS_INFO_TEXT,
+    };
+    let info_footer = match (&app.console, &app.console_message) {
+        (Some(input), _) => Paragraph::new(Line::from(format!(":{input}"))),
+        (None, Some(message)) => Paragraph::new(Line::from(message.as_str())).centered(),
+        (None, None) => Paragraph::new(Line::from(info_text)).centered(),
+    };
+    let info_footer =
+        info_footer.style(Style::default().fg(app.colors.row_fg).bg(app.colors.buffer_bg)).block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Double)
@@ -322,19 +2722,65 @@ struct TableColors {
     header_bg: Color,
     header_fg: Color,
     row_fg: Color,
+    stale_row_fg: Color,
+    testnet_row_fg: Color,
+    marker_fg: Color,
+    healthy_fg: Color,
+    degraded_fg: Color,
+    down_fg: Color,
     normal_row_color: Color,
+    alert_row_bg: Color,
+    sparkline_fg: Color,
     footer_border_color: Color,
 }
 
 impl TableColors {
-    const fn new() -> Self {
+    fn new(palette: Palette) -> Self {
+        if palette.is_basic() {
+            return Self::basic();
+        }
         Self {
             buffer_bg: tailwind::SLATE.c950,
             header_bg: PALETTE.c900,
             header_fg: tailwind::SLATE.c200,
             row_fg: tailwind::SLATE.c200,
+            stale_row_fg: tailwind::SLATE.c600,
+            testnet_row_fg: tailwind::SLATE.c400,
+            marker_fg: tailwind::FUCHSIA.c400,
+            healthy_fg: tailwind::GREEN.c400,
+            degraded_fg: tailwind::AMBER.c400,
+            down_fg: tailwind::RED.c400,
             normal_row_color: tailwind::SLATE.c950,
+            alert_row_bg: tailwind::RED.c950,
+            sparkline_fg: PALETTE.c400,
             footer_border_color: PALETTE.c400,
         }
     }
+    /// The 16 standard colors, on the terminal's own background
+    const fn basic() -> Self {
+        Self {
+            buffer_bg: Color::Reset,
+            header_bg: Color::Blue,
+            header_fg: Color::White,
+            row_fg: Color::Reset,
+            stale_row_fg: Color::DarkGray,
+            testnet_row_fg: Color::Cyan,
+            marker_fg: Color::Magenta,
+            healthy_fg: Color::Green,
+            degraded_fg: Color::Yellow,
+            down_fg: Color::Red,
+            normal_row_color: Color::Reset,
+            alert_row_bg: Color::Red,
+            sparkline_fg: Color::LightBlue,
+            footer_border_color: Color::LightBlue,
+        }
+    }
+
+    fn health_fg(&self, status: HealthStatus) -> Color {
+        match status {
+            HealthStatus::Healthy => self.healthy_fg,
+            HealthStatus::Degraded => self.degraded_fg,
+            HealthStatus::Down => self.down_fg,
+        }
+    }
 }
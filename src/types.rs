@@ -1,21 +1,490 @@
-#[derive(Clone, Debug, Default)]
+use crate::alerts::Severity;
+use crate::capabilities::Capabilities;
+use crate::settings::TimeZone;
+use crate::units::format_fee;
+use alloy_primitives::{Address, Selector, B256};
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct NetworkMetrics {
     pub network: String,
     pub block: u64,
     pub gps: f64,
     pub tps: f64,
     pub dps: f64,
+    /// Data availability bytes per second: blob bytes on chains carrying blobs, which are left
+    /// out of the DA totals, otherwise estimated from the transaction data
+    #[serde(default)]
+    pub da: f64,
+    /// Blobs per second, only available on chains carrying blobs such as Ethereum
+    #[serde(default)]
+    pub blobs: Option<f64>,
+    /// Rates over each configured window, the first being the window of `tps`, `gps` and `dps`
+    #[serde(default)]
+    pub windows: Vec<WindowMetrics>,
+    pub session: SessionTotals,
+    /// Only available when full transactions are fetched
+    pub tx_gas: Option<TxGasStats>,
+    /// Most called contracts over the window with their tx counts, only available when full
+    /// transactions are fetched
+    pub top_contracts: Vec<(Address, usize)>,
+    /// Most common function selectors over the window, `None` being native transfers
+    pub top_selectors: Vec<(Option<Selector>, usize)>,
+    /// Only available when bridge contracts are configured
+    pub bridge: Option<BridgeActivity>,
+    /// NFT mints per second, only available when mint tracking is enabled
+    pub mints: Option<f64>,
+    /// Most frequent MEV-Boost builders over the window with their fraction of the blocks, only
+    /// available when builder tracking is enabled
+    #[serde(default)]
+    pub builders: Vec<(String, f64)>,
+    /// Host of the RPC endpoint currently polled
+    pub endpoint: Option<String>,
+    /// Age in seconds of the newest block whose batch landed on L1, only available when batch
+    /// tracking is enabled
+    pub l1_freshness: Option<u64>,
+    /// Base fees paid per second in the smallest unit of the gas token
+    pub base_fees: f64,
+    /// Base fee per gas of the newest block in the smallest unit of the gas token
+    #[serde(default)]
+    pub base_fee: Option<u128>,
+    /// Native value transferred per second in the smallest unit of the gas token, only available
+    /// when full transactions are fetched
+    #[serde(default)]
+    pub value: Option<f64>,
+    /// Mean of the blocks' median priority fees per gas over the window, only available when
+    /// full transactions are fetched
+    #[serde(default)]
+    pub priority_fee: Option<u128>,
+    /// Only available when an L1 inbox is configured
+    pub inbox: Option<InboxQueue>,
+    /// Only available when custom metrics are configured
+    #[serde(default)]
+    pub custom: Vec<CustomValue>,
+    /// Only available when ZK batch tracking is enabled
+    #[serde(default)]
+    pub zk_batches: Option<ZkBatches>,
+    /// Only available when a verifier contract is configured
+    #[serde(default)]
+    pub verification: Option<VerificationLag>,
+    /// Only available when the endpoint has an hourly request budget
+    pub budget: Option<BudgetStatus>,
+    #[serde(default)]
+    pub polling: Option<PollStatus>,
+    /// Mean seconds between blocks over the window
+    #[serde(default)]
+    pub block_time: Option<f64>,
+    /// Seconds from the newest block's timestamp to when the metrics were computed
+    #[serde(default)]
+    pub block_age: Option<u64>,
+    /// Newest blocks in the window, newest first
+    pub recent_blocks: Vec<RecentBlock>,
+    /// Transactions sampled from the newest blocks, newest first
+    #[serde(default)]
+    pub tx_samples: Vec<TxSample>,
+}
+
+/// Rates over the last `seconds`
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct WindowMetrics {
+    pub seconds: u64,
+    pub tps: f64,
+    pub gps: f64,
+    pub dps: f64,
+    #[serde(default)]
+    pub da: f64,
+}
+
+/// The datapoints of a block that are recorded and replayed
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockRecord {
+    pub network: String,
+    pub number: u64,
+    pub timestamp: u64,
+    pub txs: u64,
+    pub gas: u64,
+    pub size: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct RecentBlock {
+    pub number: u64,
+    pub timestamp: u64,
+    pub txs: usize,
+}
+
+/// A transaction of a recent block. Type, gas and value are only available when full
+/// transactions are fetched.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TxSample {
+    pub hash: B256,
+    pub block: u64,
+    pub tx_type: Option<u8>,
+    /// Gas limit
+    pub gas: Option<u64>,
+    /// Value transferred in the smallest unit of the gas token
+    pub value: Option<f64>,
+}
+
+/// L1 progress of the batches of a ZK Stack chain, by batch number
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct ZkBatches {
+    /// Newest batch sealed on L2
+    pub sealed: u64,
+    /// Newest batches committed, proven and executed on L1, `None` if further back than searched
+    pub committed: Option<u64>,
+    pub proven: Option<u64>,
+    pub executed: Option<u64>,
+    /// When the newest proven batch was proven
+    pub proven_at: Option<DateTime<Utc>>,
+}
+
+/// How far the state proven on L1 trails a ZK rollup's head
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct VerificationLag {
+    /// Newest batch or block verified on L1
+    pub verified: u64,
+    /// Batches or blocks produced since
+    pub behind: u64,
+    pub unit: VerifiedUnit,
+    /// Seconds since the verified batch or block was produced
+    pub age: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerifiedUnit {
+    Batches,
+    Blocks,
+}
+
+impl fmt::Display for VerifiedUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifiedUnit::Batches => write!(f, "batches"),
+            VerifiedUnit::Blocks => write!(f, "blocks"),
+        }
+    }
+}
+
+/// Latest value of a custom metric configured on a network
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CustomValue {
+    pub label: String,
+    /// `None` before the first read and after a failed one
+    pub value: Option<f64>,
+}
+
+/// Messages queued in a network's L1 inbox that the sequencer hasn't included yet
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct InboxQueue {
+    pub depth: u64,
+    /// Age in seconds of the oldest queued message, if found
+    pub oldest_age: Option<u64>,
+}
+
+/// Hourly request budget of the endpoint currently polled
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub remaining: u32,
+    pub per_hour: u32,
+    /// Polling is degraded to stay within budget, skipping blocks and log scans
+    pub degraded: bool,
+}
+
+/// How often the network is polled
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct PollStatus {
+    /// Interval between polls in milliseconds, after the request budget and backoff
+    pub interval_ms: u64,
+    /// Whether the interval follows the chain's block time
+    pub adaptive: bool,
+    /// Failed or rate limited polls in a row the interval is backed off for
+    pub backoff: u32,
+}
+
+/// Bridge events observed over the metrics window
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct BridgeActivity {
+    pub deposits: u64,
+    pub withdrawals: u64,
+}
+
+/// Distribution of per-transaction gas limits over the metrics window
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct TxGasStats {
+    pub median: u64,
+    pub max: u64,
+}
+
+/// Cumulative counters since the session started
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct SessionTotals {
+    pub blocks: u64,
+    pub txs: u64,
+    pub gas: u64,
+    pub data: u64,
+    /// Reorgs observed, the blocks they replaced being removed from the other counters
+    #[serde(default)]
+    pub reorgs: u64,
 }
 
-#[derive(Clone, Debug)]
+impl std::ops::AddAssign for SessionTotals {
+    fn add_assign(&mut self, other: Self) {
+        self.blocks += other.blocks;
+        self.txs += other.txs;
+        self.gas += other.gas;
+        self.data += other.data;
+        self.reorgs += other.reorgs;
+    }
+}
+
+/// A network as configured on the instance polling it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetworkInfo {
+    pub name: String,
+    pub label: String,
+    /// Whether metric overrides are configured
+    pub adjusted: bool,
+    #[serde(default)]
+    pub stack: Option<String>,
+    #[serde(default)]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub testnet: bool,
+    /// Remote collector polling the network, `None` if polled by this instance
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub gas_token: GasToken,
+    /// Block explorer transactions are linked to, e.g. `https://basescan.org`
+    #[serde(default)]
+    pub explorer: Option<String>,
+    /// Time zone block timestamps are shown in, the settings' unless configured
+    #[serde(default)]
+    pub time_zone: Option<TimeZone>,
+    /// A composite index of other networks, left out of totals
+    #[serde(default)]
+    pub index: bool,
+}
+
+/// Asset fees are paid in on a network
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasToken {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl Default for GasToken {
+    fn default() -> Self {
+        Self { symbol: "ETH".to_string(), decimals: 18 }
+    }
+}
+
+/// Connection health of a network's streamer, sent after every poll
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct NetworkHealth {
+    pub network: String,
+    /// Polls that failed in a row, 0 after a successful poll
+    pub consecutive_errors: u32,
+    pub last_success: Option<DateTime<Utc>>,
+    /// Smoothed request latency of the active endpoint in milliseconds
+    pub latency_ms: Option<u64>,
+    /// Most recent poll error, kept after recovering
+    pub last_error: Option<String>,
+    /// Request statistics of every endpoint of the network
+    #[serde(default)]
+    pub endpoints: Vec<EndpointStats>,
+    /// Whether the new heads subscription is live, `None` without a WebSocket endpoint
+    #[serde(default)]
+    pub websocket: Option<bool>,
+}
+
+/// Upper bounds of the latency histogram buckets in milliseconds, slower requests falling in a
+/// last, unbounded bucket
+pub const LATENCY_BUCKETS_MS: [u64; 8] = [25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+/// Requests made to an RPC endpoint since the start of the session
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct EndpointStats {
+    pub host: String,
+    /// Whether the network is currently polled from this endpoint
+    pub active: bool,
+    pub latency: LatencyHistogram,
+    pub errors: u64,
+    /// Whether responses are requested gzip compressed
+    #[serde(default)]
+    pub compressed: bool,
+    /// Only available once the endpoint's transfer sizes were measured
+    #[serde(default)]
+    pub transfer: Option<TransferStats>,
+    /// Optional RPC methods probed at startup
+    #[serde(default)]
+    pub capabilities: Capabilities,
+}
+
+/// Bytes of the block responses of an endpoint as received and once decompressed
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct TransferStats {
+    /// Whether the endpoint compressed its latest response when asked to
+    pub gzip: bool,
+    pub compressed_bytes: u64,
+    pub uncompressed_bytes: u64,
+}
+
+impl TransferStats {
+    /// Fraction of the bandwidth compression saves
+    pub fn savings(&self) -> Option<f64> {
+        (self.uncompressed_bytes > 0)
+            .then(|| 1.0 - self.compressed_bytes as f64 / self.uncompressed_bytes as f64)
+    }
+}
+
+/// Latencies of successful requests, counted per bucket of [`LATENCY_BUCKETS_MS`]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LatencyHistogram {
+    /// Requests per bucket, one more than there are bounds
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub sum_seconds: f64,
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, seconds: f64) {
+        if self.buckets.is_empty() {
+            self.buckets = vec![0; LATENCY_BUCKETS_MS.len() + 1];
+        }
+        let ms = seconds * 1000.0;
+        let bucket = LATENCY_BUCKETS_MS.iter().position(|&bound| ms <= bound as f64);
+        self.buckets[bucket.unwrap_or(LATENCY_BUCKETS_MS.len())] += 1;
+        self.count += 1;
+        self.sum_seconds += seconds;
+    }
+
+    /// Upper bound in milliseconds of the bucket holding the `q` quantile, `None` without
+    /// requests or when it falls in the unbounded bucket
+    pub fn quantile_ms(&self, q: f64) -> Option<u64> {
+        let rank = (q * self.count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return LATENCY_BUCKETS_MS.get(bucket).copied();
+            }
+        }
+        None
+    }
+
+    pub fn mean_ms(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum_seconds * 1000.0 / self.count as f64)
+    }
+}
+
+/// A streamer log line explaining what happened to a network, such as a failed request
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Log {
     pub network: String,
+    pub at: DateTime<Utc>,
     pub message: String,
 }
 
-#[derive(Clone, Debug)]
+/// A notable occurrence on a network, such as a fee spike
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetworkEvent {
+    pub network: String,
+    pub at: DateTime<Utc>,
+    pub kind: EventKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeeKind {
+    Base,
+    Priority,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum EventKind {
+    /// Fee rose from a baseline of `from` to `to` wei
+    FeeSpike {
+        fee: FeeKind,
+        from: u128,
+        to: u128,
+        #[serde(default)]
+        token: GasToken,
+    },
+    /// No new block has been produced for `seconds`
+    Stall { seconds: u64 },
+    /// The oldest of `depth` messages queued in the L1 inbox has waited `seconds`
+    InboxBacklog { depth: u64, seconds: u64 },
+    /// TPS deviates from the rolling `mean` of the session by a z-score of `z`
+    TpsAnomaly { tps: f64, mean: f64, z: f64 },
+    /// The chain reorganized, replacing `depth` blocks from `block` on
+    Reorg { block: u64, depth: u64 },
+    /// A configured threshold `rule` fired at `value`, or cleared once back within its limits
+    Threshold { rule: String, value: f64, severity: Severity, cleared: bool },
+}
+
+impl EventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::FeeSpike { fee: FeeKind::Base, .. } => "base fee spike",
+            EventKind::FeeSpike { fee: FeeKind::Priority, .. } => "priority fee spike",
+            EventKind::Stall { .. } => "stall",
+            EventKind::InboxBacklog { .. } => "inbox backlog",
+            EventKind::TpsAnomaly { z, .. } if *z > 0.0 => "tps spike",
+            EventKind::TpsAnomaly { .. } => "tps drop",
+            EventKind::Reorg { .. } => "reorg",
+            EventKind::Threshold { .. } => "threshold",
+        }
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventKind::FeeSpike { from, to, token, .. } => {
+                let ratio = *to as f64 / *from as f64;
+                let (from, to) = (format_fee(*from, token), format_fee(*to, token));
+                write!(f, "{} {from} → {to} ({ratio:.1}x)", self.name())
+            }
+            EventKind::Stall { seconds } => write!(f, "no new block for {seconds}s"),
+            EventKind::InboxBacklog { depth, seconds } => {
+                write!(f, "{depth} inbox messages pending, oldest for {seconds}s")
+            }
+            EventKind::TpsAnomaly { tps, mean, z } => {
+                write!(f, "{} {tps:.2} TPS vs mean {mean:.2} (z {z:+.1})", self.name())
+            }
+            EventKind::Reorg { block, depth } => {
+                write!(f, "reorg replaced {depth} blocks from #{block}")
+            }
+            EventKind::Threshold { rule, value, cleared: false, .. } => {
+                write!(f, "{rule} ({value:.2})")
+            }
+            EventKind::Threshold { rule, value, cleared: true, .. } => {
+                write!(f, "{rule} cleared ({value:.2})")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum BlockMessage {
-    UpdateNetwork(NetworkMetrics),
-    #[allow(dead_code)] // TODO(george): send & display logs in UI
+    /// Announces a network, sent by remote collectors before its metrics
+    AddNetwork(NetworkInfo),
+    UpdateNetwork(Box<NetworkMetrics>),
+    /// A new block, for recording
+    Block(BlockRecord),
+    /// Withdraws the network with the given label once it is no longer polled
+    RemoveNetwork(String),
+    /// Moves a network to a new label, announced under it right after. Consumers without state
+    /// worth keeping withdraw the old label.
+    RenameNetwork {
+        from: String,
+        to: String,
+    },
+    Event(NetworkEvent),
+    Health(NetworkHealth),
     Log(Log),
 }
@@ -0,0 +1,57 @@
+use crate::columns::{Column, Sort};
+use crate::tui::View;
+use crate::units::{DataUnit, GasUnit};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind};
+use std::path::Path;
+
+const PATH: &str = "cache/ui-state.json";
+
+/// The UI state a session ended with, restored on the next launch
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    pub view: View,
+    /// Label of the selected network
+    pub selected: Option<String>,
+    pub hide_testnets: bool,
+    /// Whether the table is grouped, and the groups collapsed
+    pub grouped: bool,
+    pub collapsed_groups: BTreeSet<String>,
+    pub sort: Sort,
+    pub hidden_columns: Vec<Column>,
+    /// Widths of the columns resized with Shift+←/→
+    pub column_widths: BTreeMap<Column, u16>,
+    pub gas_unit: GasUnit,
+    pub data_unit: DataUnit,
+    /// Index of the metrics window displayed
+    pub window: usize,
+    pub show_network_logs: bool,
+    /// Whether the sparklines show hourly means
+    pub hourly_history: bool,
+}
+
+impl UiState {
+    /// Reads the state of the previous session, a missing file being the default state
+    pub fn load() -> eyre::Result<Self> {
+        let file = match File::open(PATH) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    pub fn save(&self) -> eyre::Result<()> {
+        let path = Path::new(PATH);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        serde_json::to_writer_pretty(BufWriter::new(File::create(&tmp)?), self)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
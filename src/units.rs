@@ -0,0 +1,166 @@
+use crate::types::GasToken;
+use serde_derive::{Deserialize, Serialize};
+
+/// Display unit for the gas throughput column
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GasUnit {
+    Gas,
+    #[default]
+    MGas,
+    GGas,
+}
+
+impl GasUnit {
+    pub fn next(self) -> Self {
+        match self {
+            GasUnit::Gas => GasUnit::MGas,
+            GasUnit::MGas => GasUnit::GGas,
+            GasUnit::GGas => GasUnit::Gas,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GasUnit::Gas => "Gas/s",
+            GasUnit::MGas => "MGas/s",
+            GasUnit::GGas => "GGas/s",
+        }
+    }
+
+    /// Converts gas/s into this unit
+    pub fn scale(self, gps: f64) -> f64 {
+        match self {
+            GasUnit::Gas => gps,
+            GasUnit::MGas => gps / 1024.0 / 1024.0,
+            GasUnit::GGas => gps / 1024.0 / 1024.0 / 1024.0,
+        }
+    }
+}
+
+/// Display unit for the data throughput column
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataUnit {
+    #[default]
+    KBs,
+    MBs,
+    GBh,
+}
+
+impl DataUnit {
+    pub fn next(self) -> Self {
+        match self {
+            DataUnit::KBs => DataUnit::MBs,
+            DataUnit::MBs => DataUnit::GBh,
+            DataUnit::GBh => DataUnit::KBs,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DataUnit::KBs => "KB/s",
+            DataUnit::MBs => "MB/s",
+            DataUnit::GBh => "GB/h",
+        }
+    }
+
+    /// Label of the data availability column
+    pub fn da_label(self) -> &'static str {
+        match self {
+            DataUnit::KBs => "DA KB/s",
+            DataUnit::MBs => "DA MB/s",
+            DataUnit::GBh => "DA GB/h",
+        }
+    }
+
+    /// Converts bytes/s into this unit
+    pub fn scale(self, dps: f64) -> f64 {
+        match self {
+            DataUnit::KBs => dps / 1024.0,
+            DataUnit::MBs => dps / 1024.0 / 1024.0,
+            DataUnit::GBh => dps * 3600.0 / 1024.0 / 1024.0 / 1024.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Units {
+    pub gas: GasUnit,
+    pub data: DataUnit,
+}
+
+/// Formats a count with a metric suffix, e.g. `2.1M`
+pub fn format_count(value: u64) -> String {
+    const SUFFIXES: [(f64, &str); 4] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+    let value = value as f64;
+    SUFFIXES
+        .iter()
+        .find(|(threshold, _)| value >= *threshold)
+        .map_or(format!("{value}"), |(threshold, suffix)| {
+            format!("{:.1}{suffix}", value / threshold)
+        })
+}
+
+/// Formats a byte count with a binary suffix, e.g. `12.3 MB`
+pub fn format_bytes(bytes: u64) -> String {
+    const SUFFIXES: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut suffix = 0;
+    while value >= 1024.0 && suffix < SUFFIXES.len() - 1 {
+        value /= 1024.0;
+        suffix += 1;
+    }
+    format!("{value:.1} {}", SUFFIXES[suffix])
+}
+
+/// Formats a fee per gas in the smallest unit of the gas token as a billionth of the token, gwei
+/// for ETH, keeping significant digits for the tiny fees common on L2s
+pub fn format_fee(fee: u128, token: &GasToken) -> String {
+    let nano = fee as f64 / 10f64.powi(token.decimals as i32 - 9);
+    let unit =
+        if token.symbol == "ETH" { "gwei".to_string() } else { format!("n{}", token.symbol) };
+    format!("{} {unit}", format_significant(nano))
+}
+
+/// Formats an amount in the smallest unit of the gas token in whole tokens, e.g. `0.0012 ETH`
+pub fn format_token(amount: f64, token: &GasToken) -> String {
+    let amount = amount / 10f64.powi(token.decimals as i32);
+    format!("{} {}", format_significant(amount), token.symbol)
+}
+
+/// Quotes a CSV field per RFC 4180 if it contains a comma, quote or line break, doubling its
+/// quotes
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_significant(value: f64) -> String {
+    match value {
+        value if value >= 100.0 => format!("{value:.0}"),
+        value if value >= 1.0 => format!("{value:.2}"),
+        value => {
+            let formatted = format!("{value:.6}");
+            formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("Base"), "Base");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("cr\r"), "\"cr\r\"");
+    }
+}
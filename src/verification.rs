@@ -0,0 +1,84 @@
+use crate::inbox::call_u64;
+use crate::rpc_cache::RpcCache;
+use crate::rpc_pool::{MeteredProvider, RpcPool};
+use crate::types::{VerificationLag, VerifiedUnit};
+use crate::zk_batches::quantity;
+use alloy_primitives::Address;
+use alloy_provider::Provider;
+use chrono::Utc;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// L1 contracts recording the state a ZK rollup has proven
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum VerifierContracts {
+    /// `PolygonRollupManager` of Polygon zkEVM and other CDK chains, with the chain's rollup ID
+    PolygonZkevm { rollup_manager: Address, rollup_id: u32 },
+    /// `LineaRollup`, finalizing L2 blocks once their proof is verified
+    Linea { rollup: Address },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VerifierConfig {
+    /// RPC endpoint of the L1 the rollup contract is deployed on
+    pub l1_http: String,
+    #[serde(flatten)]
+    pub contracts: VerifierContracts,
+}
+
+/// Reads how far the state proven on L1 trails a ZK rollup's head
+pub struct VerificationMonitor {
+    l1: MeteredProvider,
+    contracts: VerifierContracts,
+}
+
+impl VerificationMonitor {
+    pub fn new(config: &VerifierConfig, rpc_pool: &RpcPool) -> eyre::Result<Self> {
+        let l1 = rpc_pool.endpoint(&config.l1_http)?;
+        Ok(Self { l1, contracts: config.contracts.clone() })
+    }
+
+    /// Returns the newest verified batch or block and how far behind the head it is. `l2` is the
+    /// network's provider, which knows when the verified state was produced, and `cache` shares
+    /// the head the network was just polled at.
+    pub async fn check(
+        &self,
+        l2: &MeteredProvider,
+        cache: &RpcCache,
+    ) -> eyre::Result<VerificationLag> {
+        let now = Utc::now().timestamp() as u64;
+        match self.contracts {
+            VerifierContracts::PolygonZkevm { rollup_manager, rollup_id } => {
+                let signature = "getLastVerifiedBatch(uint32)";
+                let verified =
+                    call_u64(&self.l1, rollup_manager, signature, &[rollup_id.into()]).await?;
+                let latest: Value =
+                    l2.call(|l2| l2.raw_request("zkevm_batchNumber".into(), ())).await?;
+                let latest = quantity(&latest)
+                    .ok_or_else(|| eyre::eyre!("invalid batch number {latest}"))?;
+                let params = (format!("{verified:#x}"), false);
+                let batch: Value =
+                    l2.call(|l2| l2.raw_request("zkevm_getBatchByNumber".into(), params)).await?;
+                let produced = batch.get("timestamp").and_then(quantity);
+                Ok(VerificationLag {
+                    verified,
+                    behind: latest.saturating_sub(verified),
+                    unit: VerifiedUnit::Batches,
+                    age: produced.map(|at| now.saturating_sub(at)),
+                })
+            }
+            VerifierContracts::Linea { rollup } => {
+                let verified = call_u64(&self.l1, rollup, "currentL2BlockNumber()", &[]).await?;
+                let produced = call_u64(&self.l1, rollup, "currentTimestamp()", &[]).await?;
+                let latest = cache.head(|| l2.call(|l2| l2.get_block_number())).await?;
+                Ok(VerificationLag {
+                    verified,
+                    behind: latest.saturating_sub(verified),
+                    unit: VerifiedUnit::Blocks,
+                    age: Some(now.saturating_sub(produced)),
+                })
+            }
+        }
+    }
+}
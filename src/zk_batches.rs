@@ -0,0 +1,121 @@
+use crate::rpc_pool::MeteredProvider;
+use crate::types::ZkBatches;
+use alloy_provider::Provider;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Batches searched back from the newest for the last committed, proven and executed ones while
+/// none are known yet, over a day of zkSync Era batches
+const LOOKBACK_BATCHES: u64 = 2000;
+
+/// Steps a batch goes through on L1, in order
+#[derive(Clone, Copy)]
+enum Stage {
+    Committed,
+    Proven,
+    Executed,
+}
+
+impl Stage {
+    const ALL: [Stage; 3] = [Stage::Committed, Stage::Proven, Stage::Executed];
+
+    /// Field of the `zks_getL1BatchDetails` result holding when the batch reached the stage
+    fn field(self) -> &'static str {
+        match self {
+            Stage::Committed => "committedAt",
+            Stage::Proven => "provenAt",
+            Stage::Executed => "executedAt",
+        }
+    }
+}
+
+/// Tracks how far the L1 commits, proofs and executions of a ZK Stack chain trail its newest
+/// batch, through the `zks_` RPC methods
+#[derive(Default)]
+pub struct ZkBatchTracker {
+    /// Newest batch known to have reached each stage, searched forward from on the next check
+    reached: [Option<u64>; 3],
+    /// When the batches looked up reached each stage. A stage once reached doesn't change, so
+    /// only batches yet to reach one are looked up again.
+    stages: BTreeMap<u64, [Option<DateTime<Utc>>; 3]>,
+}
+
+impl ZkBatchTracker {
+    pub async fn check(&mut self, provider: &MeteredProvider) -> eyre::Result<ZkBatches> {
+        let sealed: Value =
+            provider.call(|provider| provider.raw_request("zks_L1BatchNumber".into(), ())).await?;
+        let sealed =
+            quantity(&sealed).ok_or_else(|| eyre::eyre!("invalid batch number {sealed}"))?;
+        // a batch reaches each stage after the previous one, bounding the search of the next
+        let mut high = sealed;
+        for (i, stage) in Stage::ALL.into_iter().enumerate() {
+            let low = self.reached[i].unwrap_or(sealed.saturating_sub(LOOKBACK_BATCHES)).min(high);
+            self.reached[i] = self.last_reached(provider, stage, low, high).await?;
+            high = self.reached[i].unwrap_or(low);
+        }
+        let [committed, proven, executed] = self.reached;
+        let proven_at = match proven {
+            Some(batch) => self.reached_at(provider, batch, Stage::Proven).await?,
+            None => None,
+        };
+        // batches before the oldest one searched from aren't looked up again
+        let oldest = self.reached.iter().flatten().min().copied();
+        self.stages = self.stages.split_off(&oldest.unwrap_or(high));
+        Ok(ZkBatches { sealed, committed, proven, executed, proven_at })
+    }
+
+    /// Newest batch between `low` and `high` that reached the stage, `None` if `low` hasn't
+    async fn last_reached(
+        &mut self,
+        provider: &MeteredProvider,
+        stage: Stage,
+        low: u64,
+        high: u64,
+    ) -> eyre::Result<Option<u64>> {
+        if self.reached_at(provider, low, stage).await?.is_none() {
+            return Ok(None);
+        }
+        let (mut low, mut high) = (low, high);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            match self.reached_at(provider, mid, stage).await? {
+                Some(_) => low = mid,
+                None => high = mid - 1,
+            }
+        }
+        Ok(Some(low))
+    }
+
+    /// When the batch reached the stage, looked up unless known
+    async fn reached_at(
+        &mut self,
+        provider: &MeteredProvider,
+        batch: u64,
+        stage: Stage,
+    ) -> eyre::Result<Option<DateTime<Utc>>> {
+        if let Some(at) = self.stages.get(&batch).and_then(|stages| stages[stage as usize]) {
+            return Ok(Some(at));
+        }
+        let details: Value = provider
+            .call(|provider| provider.raw_request("zks_getL1BatchDetails".into(), (batch,)))
+            .await?;
+        let stages = Stage::ALL.map(|stage| reached_at(&details, stage));
+        self.stages.insert(batch, stages);
+        Ok(stages[stage as usize])
+    }
+}
+
+/// When the batch reached the stage, `None` if it hasn't or the batch doesn't exist
+fn reached_at(details: &Value, stage: Stage) -> Option<DateTime<Utc>> {
+    let at = details.get(stage.field())?.as_str()?;
+    Some(DateTime::parse_from_rfc3339(at).ok()?.with_timezone(&Utc))
+}
+
+/// Reads a JSON-RPC quantity, a `0x` prefixed hex string or a plain number
+pub fn quantity(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(hex) => u64::from_str_radix(hex.strip_prefix("0x")?, 16).ok(),
+        value => value.as_u64(),
+    }
+}